@@ -14,11 +14,20 @@ pub enum TraceDeckError {
     #[error("DOCX error: {0}")]
     DocxError(String),
 
+    #[error("PPTX error: {0}")]
+    PptxError(String),
+
     #[error("Image error: {0}")]
     ImageError(String),
 
-    #[error("Unsupported format: {0}")]
-    UnsupportedFormat(String),
+    #[error("OCR error: {0}")]
+    OcrError(String),
+
+    #[error("Data provider error: {0}")]
+    ProviderError(String),
+
+    #[error("Unsupported format: detected {detected}, claimed {claimed}")]
+    UnsupportedFormat { detected: String, claimed: String },
 
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
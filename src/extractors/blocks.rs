@@ -0,0 +1,460 @@
+// Segmentation of page text into classified blocks
+use crate::models::*;
+use std::collections::{HashMap, HashSet};
+
+/// Share of the page height at the top and bottom that holds running
+/// headers, footers and page numbers.
+const MARGIN_ZONE: f32 = 0.08;
+
+/// Share of the page height from the top within which a title sits.
+const TITLE_ZONE: f32 = 0.35;
+
+/// Font size, relative to body text, from which a short block reads as a
+/// heading.
+const HEADING_RATIO: f32 = 1.15;
+
+/// Font size, relative to body text, of a top-level heading.
+const TOP_HEADING_RATIO: f32 = 1.6;
+
+/// Longest block, in words, read as a heading or title.
+const MAX_HEADING_WORDS: usize = 15;
+
+/// Longest block, in words, read as a caption.
+const MAX_CAPTION_WORDS: usize = 40;
+
+/// Glyphs that open a list item. Dashes and asterisks must be followed by
+/// a space, so `-40%` stays a figure.
+const BULLETS: &[char] = &[
+    '•', '·', '▪', '■', '◦', '‣', '○', '●', '✓', '✔', '➢', '►', '▶', '➤', '→', '❖', '-', '–', '—', '*',
+];
+
+const CAPTION_PREFIXES: &[&str] = &[
+    "figure", "fig.", "table", "chart", "exhibit", "graph", "source:", "sources:", "note:", "notes:",
+];
+
+/// What holds across the pages of a document.
+struct Context {
+    /// Character-weighted median font size.
+    body_size: f32,
+    /// Whether pages are slides, whose top heading is the slide title.
+    slides: bool,
+    /// Margin text seen on several pages, with digits masked.
+    running: HashSet<String>,
+}
+
+impl Context {
+    fn new(content: &ExtractedContent) -> Self {
+        let mut sizes: Vec<(f32, usize)> = content
+            .pages
+            .iter()
+            .flat_map(|p| p.spans.iter())
+            .map(|s| (s.font_size, s.text.chars().count()))
+            .collect();
+        sizes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let total: usize = sizes.iter().map(|s| s.1).sum();
+        let mut seen = 0;
+        let body_size = sizes
+            .iter()
+            .find(|(_, chars)| {
+                seen += chars;
+                seen * 2 >= total
+            })
+            .map_or(12.0, |s| s.0)
+            .max(1.0);
+
+        let slides = is_slides(content);
+
+        let mut pages_with: HashMap<String, usize> = HashMap::new();
+        for page in &content.pages {
+            let height = page_height(page);
+            let texts: HashSet<String> = page
+                .spans
+                .iter()
+                .filter(|s| in_margin(&s.bbox, height))
+                .map(|s| mask(&s.text))
+                .collect();
+            for text in texts {
+                *pages_with.entry(text).or_default() += 1;
+            }
+        }
+        let running = pages_with.into_iter().filter(|(_, n)| *n >= 2).map(|(t, _)| t).collect();
+
+        Self { body_size, slides, running }
+    }
+}
+
+/// Whether a document's pages are slides: a PowerPoint deck, or a PDF
+/// whose pages are mostly landscape, as exported decks are.
+pub fn is_slides(content: &ExtractedContent) -> bool {
+    let sized: Vec<&PageContent> = content.pages.iter().filter(|p| p.width.is_some()).collect();
+    let landscape = sized.iter().filter(|p| p.width > p.height).count();
+    content.metadata.file_type == "pptx" || (!sized.is_empty() && landscape * 2 > sized.len())
+}
+
+/// Split every page into blocks and classify each as a title, heading,
+/// bullet, paragraph, caption, running header or footer, or page number.
+/// Laid-out pages are read from font size, weight and position; flow
+/// documents from paragraph styles; plain text from its line structure.
+pub fn classify(content: &ExtractedContent) -> Vec<TextBlock> {
+    let ctx = Context::new(content);
+    content
+        .pages
+        .iter()
+        .flat_map(|page| {
+            if !page.spans.is_empty() {
+                from_spans(page, &ctx)
+            } else if !page.paragraphs.is_empty() {
+                from_paragraphs(page)
+            } else {
+                from_text(page)
+            }
+        })
+        .collect()
+}
+
+/// Consecutive spans that continue one another.
+struct Group<'a> {
+    spans: Vec<&'a TextSpan>,
+    bbox: BoundingBox,
+}
+
+impl Group<'_> {
+    fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+
+    fn font_size(&self) -> f32 {
+        self.spans.iter().map(|s| s.font_size).fold(0.0, f32::max)
+    }
+
+    fn bold(&self) -> bool {
+        self.spans.iter().all(|s| s.bold)
+    }
+
+    /// Recognition confidence of the weakest span, 1 for native text.
+    fn recognition(&self) -> f32 {
+        self.spans.iter().filter_map(|s| s.confidence).fold(1.0, f32::min)
+    }
+}
+
+fn from_spans(page: &PageContent, ctx: &Context) -> Vec<TextBlock> {
+    let height = page_height(page);
+    let groups = group(page, height);
+
+    let figures: Vec<BoundingBox> = page
+        .images
+        .iter()
+        .filter_map(|i| i.bbox)
+        .chain(page.tables.iter().filter_map(|t| t.bbox))
+        .chain(page.charts.iter().filter_map(|c| c.bbox))
+        .collect();
+    // Nesting of marked bullets is read from their indent.
+    let bullet_x = groups
+        .iter()
+        .filter(|g| g.spans[0].list_level.is_none() && marker(&g.spans[0].text).is_some())
+        .map(|g| g.bbox.x)
+        .fold(f32::INFINITY, f32::min);
+
+    let mut blocks: Vec<TextBlock> = groups
+        .iter()
+        .map(|g| {
+            let mut block = classify_group(g, page, ctx, height, &figures, bullet_x);
+            block.confidence = (block.confidence * g.recognition()).clamp(0.0, 1.0);
+            block
+        })
+        .collect();
+
+    // The most prominent heading near the top is the slide title, or on a
+    // document's first page, its title when set large enough.
+    let body_size = ctx.body_size;
+    let title = blocks
+        .iter()
+        .zip(&groups)
+        .enumerate()
+        .filter(|(_, (b, g))| b.block_type == "heading" && g.bbox.y < height * TITLE_ZONE)
+        .filter(|(_, (_, g))| ctx.slides || (page.number == 1 && g.font_size() >= body_size * TOP_HEADING_RATIO))
+        .max_by(|(_, (_, a)), (_, (_, b))| a.font_size().total_cmp(&b.font_size()).then(b.bbox.y.total_cmp(&a.bbox.y)))
+        .map(|(i, _)| i);
+    if let Some(i) = title {
+        blocks[i].block_type = "title".to_string();
+        blocks[i].level = Some(1);
+    }
+    blocks
+}
+
+/// Group spans, already in reading order, into blocks: a block ends at a
+/// change of font size or weight, a gap wider than a line, a new list
+/// item, or the edge of the page margin.
+fn group(page: &PageContent, height: f32) -> Vec<Group<'_>> {
+    let mut groups: Vec<Group> = Vec::new();
+    for span in &page.spans {
+        let joins = groups.last().is_some_and(|g| {
+            let p = g.spans[g.spans.len() - 1];
+            let gap = span.bbox.y - p.bbox.bottom();
+            let overlaps = span.bbox.x < p.bbox.right() && p.bbox.x < span.bbox.right();
+            (span.font_size - p.font_size).abs() < 0.5
+                && span.bold == p.bold
+                && gap < p.font_size * 0.6
+                && gap > -p.font_size
+                && overlaps
+                && span.list_level.is_none()
+                && p.list_level.is_none()
+                && marker(&span.text).is_none()
+                && in_margin(&span.bbox, height) == in_margin(&p.bbox, height)
+        });
+        match groups.last_mut() {
+            Some(g) if joins => {
+                g.spans.push(span);
+                g.bbox = g.bbox.union(&span.bbox);
+            }
+            _ => groups.push(Group { spans: vec![span], bbox: span.bbox }),
+        }
+    }
+    groups
+}
+
+fn classify_group(
+    group: &Group,
+    page: &PageContent,
+    ctx: &Context,
+    height: f32,
+    figures: &[BoundingBox],
+    bullet_x: f32,
+) -> TextBlock {
+    let text = group.text();
+    let words = text.split_whitespace().count();
+    let size = group.font_size();
+    let ratio = size / ctx.body_size;
+    let bold = group.bold();
+    let bbox = group.bbox;
+    let block = |block_type: &str, content: &str, confidence: f32, level: Option<u32>| TextBlock {
+        content: content.to_string(),
+        block_type: block_type.to_string(),
+        confidence,
+        page: page.number,
+        bbox: Some(bbox),
+        level,
+    };
+
+    let top = bbox.bottom() <= height * MARGIN_ZONE;
+    let bottom = bbox.y >= height * (1.0 - MARGIN_ZONE);
+    if top || bottom {
+        if let Some(number) = page_number(&text) {
+            let confidence = if number == page.number { 0.95 } else { 0.8 };
+            return block("page_number", &text, confidence, None);
+        }
+        if ratio <= 1.05 {
+            let running = ctx.running.contains(&mask(&text));
+            if bottom {
+                return block("footer", &text, if running { 0.95 } else { 0.7 }, None);
+            }
+            if running {
+                return block("header", &text, 0.9, None);
+            }
+        }
+    }
+
+    if let Some(level) = group.spans[0].list_level {
+        let content = marker(&text).map_or(text.as_str(), |(rest, _)| rest);
+        return block("bullet", content, 0.95, Some(level));
+    }
+    if let Some((rest, numbered)) = marker(&text) {
+        if !(numbered && ratio >= HEADING_RATIO) {
+            let indent = size.max(1.0) * 1.2;
+            let level = ((bbox.x - bullet_x).max(0.0) / indent).round().min(5.0) as u32;
+            return block("bullet", rest, if numbered { 0.85 } else { 0.9 }, Some(level));
+        }
+    }
+
+    if words <= MAX_CAPTION_WORDS {
+        if caption_prefix(&text) {
+            return block("caption", &text, 0.9, None);
+        }
+        let adjacent = figures.iter().any(|f| {
+            let gap = (bbox.y - f.bottom()).max(f.y - bbox.bottom());
+            gap >= -1.0 && gap < size * 2.0 && bbox.x < f.right() && f.x < bbox.right()
+        });
+        if adjacent && ratio < 0.95 {
+            return block("caption", &text, 0.7, None);
+        }
+    }
+
+    let sentence_end = text.trim_end().ends_with(['.', ',', ';', ':']);
+    let short = words <= MAX_HEADING_WORDS && group.spans.len() <= 3 && !sentence_end;
+    if short && (ratio >= HEADING_RATIO || (bold && words <= 10 && ratio >= 0.95)) {
+        let strength = ((ratio - 1.0) / (TOP_HEADING_RATIO - 1.0)).clamp(0.0, 1.0);
+        let mut confidence = 0.6 + 0.25 * strength;
+        if bold {
+            confidence += 0.1;
+        }
+        if bbox.y < height * TITLE_ZONE {
+            confidence += 0.05;
+        }
+        let level = if ratio >= TOP_HEADING_RATIO {
+            1
+        } else if ratio >= 1.3 {
+            2
+        } else {
+            3
+        };
+        return block("heading", &text, confidence.min(0.98), Some(level));
+    }
+
+    // A lone short line could be an unstyled label as easily as prose.
+    let prose = group.spans.len() >= 2 || words >= 12 || text.trim_end().ends_with(['.', '!', '?']);
+    block("paragraph", &text, if prose { 0.9 } else { 0.7 }, None)
+}
+
+/// Blocks of a flow document, read from its paragraph styles.
+fn from_paragraphs(page: &PageContent) -> Vec<TextBlock> {
+    page.paragraphs
+        .iter()
+        .map(|p| {
+            let style = p.style.as_deref().unwrap_or_default().to_lowercase();
+            let heading_level = style
+                .strip_prefix("heading")
+                .and_then(|n| n.trim().parse::<u32>().ok());
+            let (block_type, confidence, level) = match (style.as_str(), heading_level) {
+                ("title", _) => ("title", 0.98, Some(1)),
+                ("subtitle", _) => ("heading", 0.9, Some(2)),
+                (_, Some(n)) => ("heading", 0.98, Some(n)),
+                ("caption", _) => ("caption", 0.98, None),
+                ("footer", _) => ("footer", 0.98, None),
+                ("header", _) => ("header", 0.98, None),
+                _ if p.list_level.is_some() || style.contains("list") => {
+                    ("bullet", 0.95, Some(p.list_level.unwrap_or(0)))
+                }
+                _ if marker(&p.text).is_some() => ("bullet", 0.85, Some(0)),
+                _ if caption_prefix(&p.text) && p.text.split_whitespace().count() <= MAX_CAPTION_WORDS => {
+                    ("caption", 0.75, None)
+                }
+                _ => ("paragraph", 0.9, None),
+            };
+            let content = match (block_type, marker(&p.text)) {
+                ("bullet", Some((rest, _))) => rest.to_string(),
+                _ => p.text.trim().to_string(),
+            };
+            TextBlock {
+                content,
+                block_type: block_type.to_string(),
+                confidence,
+                page: page.number,
+                bbox: None,
+                level,
+            }
+        })
+        .collect()
+}
+
+/// Blocks of text without layout: list items line by line, and otherwise
+/// runs of lines separated by blank lines.
+fn from_text(page: &PageContent) -> Vec<TextBlock> {
+    let mut blocks: Vec<TextBlock> = Vec::new();
+    let mut open = false;
+    for line in page.text.lines().map(str::trim) {
+        if line.is_empty() {
+            open = false;
+            continue;
+        }
+        if let Some((rest, numbered)) = marker(line) {
+            blocks.push(text_block(page, "bullet", rest, if numbered { 0.8 } else { 0.85 }, Some(0)));
+            open = false;
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) if open => {
+                block.content.push('\n');
+                block.content.push_str(line);
+            }
+            _ => {
+                blocks.push(text_block(page, "paragraph", line, 0.8, None));
+                open = true;
+            }
+        }
+    }
+    blocks
+}
+
+fn text_block(page: &PageContent, block_type: &str, content: &str, confidence: f32, level: Option<u32>) -> TextBlock {
+    TextBlock {
+        content: content.to_string(),
+        block_type: block_type.to_string(),
+        confidence,
+        page: page.number,
+        bbox: None,
+        level,
+    }
+}
+
+/// The text after a list marker, and whether the marker is a number or
+/// letter (`1.`, `2)`, `a.`) rather than a bullet glyph.
+fn marker(text: &str) -> Option<(&str, bool)> {
+    let text = text.trim_start();
+    let first = text.chars().next()?;
+    if BULLETS.contains(&first) {
+        let rest = &text[first.len_utf8()..];
+        let needs_space = matches!(first, '-' | '–' | '—' | '*');
+        if needs_space && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        return (!rest.is_empty()).then_some((rest, false));
+    }
+
+    let label_end = text.find(['.', ')'])?;
+    let label = &text[..label_end];
+    let numbered = (1..=2).contains(&label.len()) && label.chars().all(|c| c.is_ascii_digit())
+        || label.len() == 1 && label.chars().all(|c| c.is_ascii_lowercase());
+    let rest = &text[label_end + 1..];
+    if !numbered || !rest.starts_with(char::is_whitespace) || rest.trim().is_empty() {
+        return None;
+    }
+    Some((rest.trim_start(), true))
+}
+
+fn caption_prefix(text: &str) -> bool {
+    let lower = text.trim_start().to_lowercase();
+    CAPTION_PREFIXES.iter().any(|p| {
+        lower.strip_prefix(p).is_some_and(|rest| {
+            p.ends_with(':') || rest.starts_with(|c: char| c.is_whitespace() || c.is_ascii_digit() || c == ':')
+        })
+    })
+}
+
+/// The number in `7`, `Page 7`, `7 / 12` or `Slide 7 of 12`.
+fn page_number(text: &str) -> Option<u32> {
+    let lower = text.trim().to_lowercase();
+    let rest = ["page", "slide", "p."]
+        .iter()
+        .find_map(|p| lower.strip_prefix(p))
+        .unwrap_or(&lower)
+        .trim();
+    let (number, total) = match rest.split_once(['/', '|']).or_else(|| rest.split_once(" of ")) {
+        Some((n, t)) => (n.trim(), Some(t.trim())),
+        None => (rest, None),
+    };
+    let is_number = |s: &str| (1..=3).contains(&s.len()) && s.chars().all(|c| c.is_ascii_digit());
+    if !is_number(number) || total.is_some_and(|t| !is_number(t)) {
+        return None;
+    }
+    number.parse().ok()
+}
+
+fn in_margin(bbox: &BoundingBox, height: f32) -> bool {
+    bbox.bottom() <= height * MARGIN_ZONE || bbox.y >= height * (1.0 - MARGIN_ZONE)
+}
+
+/// Text with digits masked, so running footers match across pages.
+fn mask(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect()
+}
+
+fn page_height(page: &PageContent) -> f32 {
+    page.height
+        .unwrap_or_else(|| page.spans.iter().map(|s| s.bbox.bottom()).fold(0.0, f32::max))
+        .max(1.0)
+}
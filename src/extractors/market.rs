@@ -0,0 +1,334 @@
+// Market-size claim extraction: TAM, SAM, SOM and market size statements
+use super::metrics::{self, Quantity};
+use crate::language::NumberFormat;
+use crate::models::*;
+
+/// Phrases naming a market size, longest matched first. The acronyms
+/// `SAM` and `SOM` are also names, so they match only in capitals.
+const KEYWORDS: &[(MarketSizeKind, &[&str])] = &[
+    (MarketSizeKind::Tam, &["tam", "total addressable market", "total available market"]),
+    (MarketSizeKind::Sam, &["SAM", "serviceable addressable market", "serviceable available market"]),
+    (MarketSizeKind::Som, &["SOM", "serviceable obtainable market", "share of market"]),
+    (MarketSizeKind::Market, &["market", "market size", "market opportunity", "industry"]),
+];
+
+/// Regions and countries, with the name they are reported under. Patterns
+/// with capitals match case-sensitively, so `US` is a country and `us` is
+/// not.
+const GEOGRAPHIES: &[(&str, &str)] = &[
+    ("global", "Global"), ("globally", "Global"), ("worldwide", "Global"), ("world", "Global"),
+    ("north america", "North America"), ("latin america", "Latin America"), ("latam", "Latin America"),
+    ("south america", "South America"), ("united states", "United States"), ("US", "United States"),
+    ("U.S", "United States"), ("USA", "United States"), ("canada", "Canada"), ("mexico", "Mexico"),
+    ("brazil", "Brazil"), ("europe", "Europe"), ("european", "Europe"), ("EU", "Europe"),
+    ("united kingdom", "United Kingdom"), ("UK", "United Kingdom"), ("germany", "Germany"),
+    ("france", "France"), ("spain", "Spain"), ("dach", "DACH"), ("emea", "EMEA"),
+    ("middle east", "Middle East"), ("mena", "MENA"), ("africa", "Africa"), ("nigeria", "Nigeria"),
+    ("asia-pacific", "Asia-Pacific"), ("asia pacific", "Asia-Pacific"), ("apac", "Asia-Pacific"),
+    ("southeast asia", "Southeast Asia"), ("asia", "Asia"), ("india", "India"), ("china", "China"),
+    ("japan", "Japan"), ("indonesia", "Indonesia"), ("australia", "Australia"),
+];
+
+/// Most words allowed between a market-size name and its value.
+const MAX_GAP: usize = 6;
+
+/// Smallest figure read as an unlabelled market size; below it `market`
+/// is more likely part of a plan, as in "$2M to go to market".
+const MIN_MARKET_SIZE: f64 = 1e8;
+
+/// Most words in a lone label or figure paired with its neighbour.
+const MAX_LONE_WORDS: usize = 8;
+
+/// Market-size claims in text blocks, with the year, geography and growth
+/// rate stated alongside, and the source they cite: in the sentence, in a
+/// footnote its value points to, or in a `Source:` line on the same page.
+pub fn extract(blocks: &[TextBlock], format: Option<NumberFormat>) -> Vec<MarketClaim> {
+    let mut sentences: Vec<Sentence> = Vec::new();
+    for block in blocks {
+        if matches!(block.block_type.as_str(), "header" | "page_number") {
+            continue;
+        }
+        for text in metrics::sentences(&block.content) {
+            let tokens = metrics::tokens(text);
+            let words: Vec<String> = tokens.iter().map(|t| metrics::normalize_word(t)).collect();
+            let names = metrics::find_phrases(&tokens, &words, KEYWORDS);
+            let quantities = metrics::quantities(&tokens, &words, format);
+            sentences.push(Sentence { text, block, tokens, words, names, quantities });
+        }
+    }
+
+    // (claim, footnote marker on its figure)
+    let mut claims: Vec<(MarketClaim, Option<String>)> = Vec::new();
+    for sentence in &sentences {
+        // Pair names with the nearest fitting figure, closest pairs first.
+        let mut pairs: Vec<(usize, usize, usize)> = Vec::new();
+        for (n, (kind, start, end)) in sentence.names.iter().enumerate() {
+            for q in sentence.sizes() {
+                let quantity = &sentence.quantities[q];
+                let (gap, after) = if quantity.end <= *start {
+                    (start - quantity.end, false)
+                } else if quantity.start >= *end {
+                    (quantity.start - end, true)
+                } else {
+                    continue;
+                };
+                let plain = *kind == MarketSizeKind::Market;
+                if gap <= MAX_GAP && (!plain || (after && quantity.value >= MIN_MARKET_SIZE)) {
+                    pairs.push((gap, n, q));
+                }
+            }
+        }
+        pairs.sort();
+        let mut used_names = vec![false; sentence.names.len()];
+        let mut used_quantities = vec![false; sentence.quantities.len()];
+        for (gap, n, q) in pairs {
+            if used_names[n] || used_quantities[q] {
+                continue;
+            }
+            used_names[n] = true;
+            used_quantities[q] = true;
+            let kind = sentence.names[n].0;
+            let base = if kind == MarketSizeKind::Market { 0.6 } else { 0.9 };
+            claims.push(claim(kind, sentence, q, base - 0.04 * gap as f32));
+        }
+    }
+
+    // Slides often set a label such as `TAM` and its figure in separate
+    // text boxes; pair a lone label with a lone figure next to it.
+    let mut i = 0;
+    while i + 1 < sentences.len() {
+        let (a, b) = (&sentences[i], &sentences[i + 1]);
+        let pair = match (a.label(), b.label()) {
+            _ if a.block.page != b.block.page => None,
+            (Some(kind), None) if b.lone_figure() => Some((kind, b, a)),
+            (None, Some(kind)) if a.lone_figure() => Some((kind, a, b)),
+            _ => None,
+        };
+        let Some((kind, figure, label)) = pair else {
+            i += 1;
+            continue;
+        };
+        let (claim, marker) = claim(kind, figure, figure.sizes()[0], 0.75);
+        let text = if std::ptr::eq(label, a) {
+            format!("{} {}", label.text.trim(), claim.text)
+        } else {
+            format!("{} {}", claim.text, label.text.trim())
+        };
+        claims.push((MarketClaim { text, ..claim }, marker));
+        i += 2;
+    }
+
+    for (claim, marker) in &mut claims {
+        if claim.source.is_none() {
+            let page: Vec<&TextBlock> = blocks.iter().filter(|b| b.page == claim.page).collect();
+            claim.source = marker
+                .as_deref()
+                .and_then(|m| page.iter().find_map(|b| footnote_text(&b.content, m)))
+                .or_else(|| page_source(&page));
+        }
+    }
+
+    let mut unique: Vec<MarketClaim> = Vec::new();
+    for (claim, _) in claims {
+        let same = unique.iter_mut().find(|c| {
+            c.kind == claim.kind && c.year == claim.year && (c.value - claim.value).abs() <= c.value.abs() * 1e-6
+        });
+        match same {
+            Some(c) if c.confidence >= claim.confidence => {}
+            Some(c) => *c = claim,
+            None => unique.push(claim),
+        }
+    }
+    unique
+}
+
+/// A sentence of a block, read into names and figures.
+struct Sentence<'a> {
+    text: &'a str,
+    block: &'a TextBlock,
+    tokens: Vec<&'a str>,
+    words: Vec<String>,
+    names: Vec<(MarketSizeKind, usize, usize)>,
+    quantities: Vec<Quantity>,
+}
+
+impl Sentence<'_> {
+    /// Indices of the figures that can be market sizes.
+    fn sizes(&self) -> Vec<usize> {
+        (0..self.quantities.len()).filter(|q| is_size(&self.quantities[*q])).collect()
+    }
+
+    /// The kind a short sentence names without giving a figure, like
+    /// `Total Addressable Market (TAM)`.
+    fn label(&self) -> Option<MarketSizeKind> {
+        let (kind, _, _) = *self.names.first()?;
+        let lone = self.tokens.len() <= MAX_LONE_WORDS && self.sizes().is_empty() && kind != MarketSizeKind::Market;
+        (lone && self.names.iter().all(|n| n.0 == kind)).then_some(kind)
+    }
+
+    /// Whether the sentence is a short figure naming no market.
+    fn lone_figure(&self) -> bool {
+        self.tokens.len() <= MAX_LONE_WORDS && self.names.is_empty() && !self.sizes().is_empty()
+    }
+}
+
+/// Whether a figure can be a market size: an amount of money, or a large
+/// enough number, not a percentage, duration or year.
+fn is_size(quantity: &Quantity) -> bool {
+    !quantity.percent
+        && quantity.months.is_none()
+        && !quantity.year_like
+        && (quantity.unit.is_some() || quantity.value >= 1e6)
+}
+
+/// A claim for figure `q` of `sentence`, and the footnote marker on it.
+fn claim(kind: MarketSizeKind, sentence: &Sentence, q: usize, confidence: f32) -> (MarketClaim, Option<String>) {
+    let quantity = &sentence.quantities[q];
+    let confidence = if quantity.unit.is_some() { confidence } else { confidence - 0.2 };
+    let block = sentence.block;
+    // Years and places in a cited source describe the source, not the claim.
+    let stated = sentence
+        .tokens
+        .iter()
+        .position(|t| t.trim_start_matches('(').to_lowercase().starts_with("source"))
+        .unwrap_or(sentence.tokens.len());
+    let tokens = &sentence.tokens[..stated];
+    let claim = MarketClaim {
+        kind,
+        value: quantity.value,
+        currency: quantity.unit.clone(),
+        year: metrics::period(tokens, quantity.start).and_then(|p| p.get(..4)?.parse().ok()),
+        geography: geography(tokens).or_else(|| geography(&metrics::tokens(&block.content))),
+        cagr: cagr(&sentence.words, &sentence.quantities),
+        source: cited(sentence.text),
+        text: sentence.text.trim().to_string(),
+        page: block.page,
+        confidence: (confidence * block.confidence.max(0.5)).clamp(0.1, 0.95),
+    };
+    (claim, marker(&sentence.tokens, quantity))
+}
+
+/// The growth rate nearest a mention of CAGR or growth, in percent.
+fn cagr(words: &[String], quantities: &[Quantity]) -> Option<f64> {
+    let marks: Vec<usize> = words
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.as_str() == "cagr" || w.as_str() == "compound" || w.starts_with("grow"))
+        .map(|(i, _)| i)
+        .collect();
+    quantities
+        .iter()
+        .filter(|q| q.percent)
+        .filter_map(|q| {
+            let distance = marks.iter().map(|m| if *m < q.start { q.start - m } else { m.saturating_sub(q.end) }).min()?;
+            (distance <= MAX_GAP).then_some((distance, q.value))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, value)| value)
+}
+
+/// The first region or country named in `tokens`, longest name first.
+fn geography(tokens: &[&str]) -> Option<String> {
+    let clean: Vec<&str> = tokens
+        .iter()
+        .map(|t| t.trim_matches(|c: char| !c.is_alphanumeric() && c != '.' && c != '-').trim_end_matches('.'))
+        .collect();
+    let lower: Vec<String> = clean.iter().map(|t| t.to_lowercase()).collect();
+    let mut names: Vec<(Vec<&str>, &str)> = GEOGRAPHIES.iter().map(|(p, name)| (p.split(' ').collect(), *name)).collect();
+    names.sort_by_key(|(p, _)| std::cmp::Reverse(p.len()));
+
+    (0..clean.len()).find_map(|start| {
+        names.iter().find_map(|(phrase, name)| {
+            let words = clean.get(start..start + phrase.len())?;
+            let matched = words.iter().zip(&lower[start..]).zip(phrase).all(|((word, lower), p)| {
+                if p.chars().any(|c| c.is_uppercase()) {
+                    word == p
+                } else {
+                    lower == p
+                }
+            });
+            matched.then(|| name.to_string())
+        })
+    })
+}
+
+/// A source cited in the sentence itself: `Source: Gartner, 2024` or
+/// `according to Gartner`.
+fn cited(sentence: &str) -> Option<String> {
+    let text = if let Some(at) = find_ignore_case(sentence, "source:").or_else(|| find_ignore_case(sentence, "sources:")) {
+        let rest = &sentence[at..];
+        &rest[rest.find(':')? + 1..]
+    } else if let Some(at) = find_ignore_case(sentence, "according to ") {
+        let rest = &sentence[at + "according to ".len()..];
+        rest.split([',', ')']).next()?
+    } else {
+        return None;
+    };
+    let text = text.trim().trim_end_matches(['.', ')']).trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Byte offset of `needle` in `haystack`, ignoring ASCII case. Offsets
+/// index `haystack` itself, which lowercasing could shift.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| haystack[i..].get(..needle.len()).is_some_and(|h| h.eq_ignore_ascii_case(needle)))
+}
+
+/// The footnote marker on a figure, glued to it as in `$50B¹`, standing
+/// right after it as in `$50B [1]`, or closing its sentence.
+fn marker(tokens: &[&str], quantity: &Quantity) -> Option<String> {
+    let clean = |t: &str| t.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '"' | '.')).to_string();
+    let last = clean(tokens[quantity.end - 1]);
+    if let (_, Some(marker)) = metrics::footnote(&last) {
+        return Some(marker);
+    }
+    if let Some(next) = tokens.get(quantity.end) {
+        if let (head, Some(marker)) = metrics::footnote(&clean(next)) {
+            if head.is_empty() {
+                return Some(marker);
+            }
+        }
+    }
+    metrics::footnote(&clean(tokens.last()?)).1
+}
+
+/// The footnote for `marker` in `text`: a line starting `¹`, `[1]`, `1.`,
+/// `1)` or `*`.
+fn footnote_text(text: &str, marker: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let line = line.trim();
+        let superscripts = line.chars().take_while(|c| metrics::superscript(*c).is_some());
+        let at = superscripts.clone().map(char::len_utf8).sum::<usize>();
+        let rest = if at > 0 {
+            superscripts.filter_map(metrics::superscript).eq(marker.chars()).then(|| &line[at..])
+        } else if marker.chars().all(|c| c.is_ascii_digit()) {
+            line.strip_prefix(&format!("[{}]", marker))
+                .or_else(|| line.strip_prefix(marker).and_then(|r| r.strip_prefix(['.', ')'])))
+        } else {
+            line.strip_prefix(marker)
+        }?;
+        let rest = rest.trim();
+        let rest = rest.strip_prefix("Source:").or_else(|| rest.strip_prefix("Sources:")).unwrap_or(rest).trim();
+        (!rest.is_empty()).then(|| rest.to_string())
+    })
+}
+
+/// A `Source:` line among the blocks of a page, which the page's figures
+/// cite unless they name their own.
+fn page_source(page: &[&TextBlock]) -> Option<String> {
+    page.iter()
+        .flat_map(|b| b.content.lines())
+        .find_map(|line| {
+            let line = line.trim();
+            let lower = line.to_lowercase();
+            if !(lower.starts_with("source") || lower.starts_with("data source")) {
+                return None;
+            }
+            let (_, rest) = line.split_once(':')?;
+            let rest = rest.trim().trim_end_matches('.').trim();
+            (!rest.is_empty()).then(|| rest.to_string())
+        })
+}
@@ -0,0 +1,471 @@
+// Startup KPI extraction from text blocks and tables
+use crate::language::NumberFormat;
+use crate::models::*;
+use crate::normalize::{self, Unit};
+use crate::parsers::cell_value;
+
+/// Phrases naming each metric. Longer phrases are matched first, so
+/// `annual recurring revenue` is ARR rather than revenue.
+const KEYWORDS: &[(MetricKind, &[&str])] = &[
+    (MetricKind::Arr, &["arr", "annual recurring revenue", "annualized recurring revenue", "run rate", "run-rate"]),
+    (MetricKind::Mrr, &["mrr", "monthly recurring revenue"]),
+    (MetricKind::Revenue, &["revenue", "revenues", "sales", "turnover"]),
+    (MetricKind::Gmv, &["gmv", "gross merchandise value", "gross merchandise volume"]),
+    (MetricKind::GrossMargin, &["gross margin", "gross margins"]),
+    (MetricKind::Cac, &["cac", "customer acquisition cost", "acquisition cost"]),
+    (MetricKind::Ltv, &["ltv", "clv", "lifetime value", "customer lifetime value"]),
+    (MetricKind::Churn, &["churn", "churn rate", "logo churn", "revenue churn"]),
+    (MetricKind::Nrr, &["nrr", "ndr", "net revenue retention", "net dollar retention", "net retention"]),
+    (MetricKind::Burn, &["burn", "burn rate", "net burn", "cash burn"]),
+    (MetricKind::Runway, &["runway"]),
+    (MetricKind::Headcount, &["headcount", "employees", "ftes", "fte", "team of", "staff"]),
+    (MetricKind::Users, &["users", "mau", "dau", "active users", "monthly active users", "daily active users", "subscribers"]),
+];
+
+/// Most words allowed between a metric's name and its value.
+const MAX_GAP: usize = 4;
+
+const MONTHLY: &[&str] = &["monthly", "month", "mo", "mom", "pm"];
+const ANNUAL: &[&str] = &["annual", "annually", "yearly", "year", "yr", "yoy", "pa"];
+
+/// What a metric's value must look like.
+#[derive(PartialEq)]
+enum Expects {
+    Money,
+    Percent,
+    Months,
+    Count,
+}
+
+impl MetricKind {
+    fn expects(&self) -> Expects {
+        match self {
+            MetricKind::GrossMargin | MetricKind::Churn | MetricKind::Nrr => Expects::Percent,
+            MetricKind::Runway => Expects::Months,
+            MetricKind::Headcount | MetricKind::Users => Expects::Count,
+            _ => Expects::Money,
+        }
+    }
+}
+
+/// Metrics stated in text blocks and tables, one per metric, value and
+/// period, keeping the most confident reading. Figures in text and
+/// tables are read in `format` when the document's locale is known.
+pub fn extract(blocks: &[TextBlock], tables: &[TableData], format: Option<NumberFormat>) -> Vec<Metric> {
+    let mut metrics: Vec<Metric> = Vec::new();
+    for block in blocks {
+        if matches!(block.block_type.as_str(), "header" | "footer" | "page_number") {
+            continue;
+        }
+        for sentence in sentences(&block.content) {
+            metrics.extend(from_sentence(sentence, block.page, block.confidence, format));
+        }
+    }
+    for table in tables {
+        metrics.extend(from_table(table, format));
+    }
+
+    let mut unique: Vec<Metric> = Vec::new();
+    for metric in metrics {
+        let same = unique.iter_mut().find(|m| {
+            m.kind == metric.kind && m.period == metric.period && (m.value - metric.value).abs() <= m.value.abs() * 1e-6
+        });
+        match same {
+            Some(m) if m.confidence >= metric.confidence => {}
+            Some(m) => *m = metric,
+            None => unique.push(metric),
+        }
+    }
+    unique
+}
+
+/// A figure in a sentence, spanning tokens `start..end`.
+#[derive(Debug)]
+pub(super) struct Quantity {
+    pub(super) value: f64,
+    pub(super) unit: Option<String>,
+    pub(super) percent: bool,
+    /// A bare four-digit number such as 2000, more likely a year unless
+    /// it directly precedes what it counts.
+    pub(super) year_like: bool,
+    /// Months, when followed by a duration word.
+    pub(super) months: Option<f64>,
+    pub(super) start: usize,
+    pub(super) end: usize,
+}
+
+fn from_sentence(sentence: &str, page: u32, block_confidence: f32, format: Option<NumberFormat>) -> Vec<Metric> {
+    let tokens = tokens(sentence);
+    let words: Vec<String> = tokens.iter().map(|t| normalize_word(t)).collect();
+    let names = find_phrases(&tokens, &words, KEYWORDS);
+    let quantities = quantities(&tokens, &words, format);
+
+    // Pair names with the nearest fitting quantity, closest pairs first;
+    // at equal distance a figure beats a bare year ("2024 revenue $1.0M").
+    let mut pairs: Vec<(usize, bool, usize, usize)> = Vec::new();
+    for (n, (kind, start, end)) in names.iter().enumerate() {
+        for (q, quantity) in quantities.iter().enumerate() {
+            let gap = if quantity.end <= *start {
+                start - quantity.end
+            } else if quantity.start >= *end {
+                quantity.start - end
+            } else {
+                continue;
+            };
+            if gap <= MAX_GAP && fits(kind, quantity, gap, quantity.start >= *end) {
+                pairs.push((gap, quantity.year_like, n, q));
+            }
+        }
+    }
+    pairs.sort();
+
+    let mut used_names = vec![false; names.len()];
+    let mut used_quantities = vec![false; quantities.len()];
+    let mut metrics = Vec::new();
+    for (gap, _, n, q) in pairs {
+        if used_names[n] || used_quantities[q] {
+            continue;
+        }
+        used_names[n] = true;
+        used_quantities[q] = true;
+        let (kind, start, _) = names[n];
+        let quantity = &quantities[q];
+
+        let (value, unit) = match kind.expects() {
+            Expects::Money => (quantity.value, quantity.unit.clone()),
+            Expects::Percent => (quantity.value, Some("%".to_string())),
+            Expects::Months => (quantity.months.unwrap_or(quantity.value), Some("months".to_string())),
+            Expects::Count if kind == MetricKind::Users => (quantity.value, Some("users".to_string())),
+            Expects::Count => (quantity.value, Some("people".to_string())),
+        };
+        let mut confidence = 0.9 - 0.08 * gap as f32;
+        if kind.expects() == Expects::Money && quantity.unit.is_none() {
+            confidence -= 0.2;
+        }
+        metrics.push(Metric {
+            kind,
+            value,
+            unit,
+            period: period(&tokens, start),
+            cadence: cadence(&words, kind),
+            text: sentence.trim().to_string(),
+            page,
+            source: "text".to_string(),
+            confidence: (confidence * block_confidence.max(0.5)).clamp(0.1, 0.95),
+        });
+    }
+    metrics
+}
+
+/// Whether `quantity` can be the value of `kind`, `gap` words from its
+/// name, on the `after` side.
+fn fits(kind: &MetricKind, quantity: &Quantity, gap: usize, after: bool) -> bool {
+    if quantity.year_like && (after || gap > 0) {
+        return false;
+    }
+    match kind.expects() {
+        Expects::Money => !quantity.percent && quantity.months.is_none(),
+        Expects::Percent => quantity.percent,
+        Expects::Months => quantity.months.is_some(),
+        Expects::Count => {
+            !quantity.percent && quantity.unit.is_none() && quantity.months.is_none() && quantity.value >= 1.0
+        }
+    }
+}
+
+/// Names from `keywords` in a sentence's normalized words, as `(kind,
+/// start, end)` word ranges. Longer phrases win over the words in them.
+/// Phrases with capitals match the tokens as written, so `SAM` is a
+/// market and `Sam` is not.
+pub(super) fn find_phrases<K: Copy>(
+    tokens: &[&str],
+    words: &[String],
+    keywords: &[(K, &[&str])],
+) -> Vec<(K, usize, usize)> {
+    let mut phrases: Vec<(K, Vec<&str>)> = keywords
+        .iter()
+        .flat_map(|(kind, phrases)| phrases.iter().map(move |p| (*kind, p.split(' ').collect())))
+        .collect();
+    phrases.sort_by_key(|(_, p)| std::cmp::Reverse(p.len()));
+
+    let mut taken = vec![false; words.len()];
+    let mut names = Vec::new();
+    for (kind, phrase) in &phrases {
+        for start in 0..words.len().saturating_sub(phrase.len() - 1) {
+            let end = start + phrase.len();
+            if taken[start..end].iter().any(|t| *t) {
+                continue;
+            }
+            let matched = tokens[start..end].iter().zip(&words[start..end]).zip(phrase).all(|((t, w), p)| {
+                if p.chars().any(|c| c.is_uppercase()) {
+                    t.trim_matches(|c: char| !c.is_alphanumeric()) == *p
+                } else {
+                    w == p
+                }
+            });
+            if matched {
+                taken[start..end].iter_mut().for_each(|t| *t = true);
+                names.push((*kind, start, end));
+            }
+        }
+    }
+    names
+}
+
+/// Figures in a sentence. A magnitude word or currency code next to a
+/// number belongs to it, as in `2.4 million` or `USD 2.4M`; a duration
+/// word after it makes it a number of months.
+pub(super) fn quantities(tokens: &[&str], words: &[String], format: Option<NumberFormat>) -> Vec<Quantity> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '"'));
+        let token = token.strip_suffix('.').unwrap_or(token);
+        let (token, _) = footnote(token);
+        let (token, duration) = match token.split_once('-') {
+            Some((n, unit)) if n.chars().any(|c| c.is_ascii_digit()) && duration_months(unit).is_some() => {
+                (n, duration_months(unit))
+            }
+            _ => (token, None),
+        };
+        let Some(quantity) = normalize::parse_quantity(token, format) else {
+            i += 1;
+            continue;
+        };
+        // Multiples like 3x are ratios, not metric values.
+        if quantity.unit == Some(Unit::Multiple) {
+            i += 1;
+            continue;
+        }
+        let year_like = token.len() == 4 && token.chars().all(|c| c.is_ascii_digit()) && (1900.0..=2100.0).contains(&quantity.value);
+        let mut value = quantity.value;
+        let mut unit = quantity.currency().map(str::to_string);
+        let percent = quantity.unit == Some(Unit::Percent);
+
+        let start = i;
+        let mut end = i + 1;
+        let mut percent = percent;
+        let currency = |j: usize| tokens.get(j).and_then(|t| normalize::currency(t.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '"'))));
+        if let Some(code) = i.checked_sub(1).and_then(currency) {
+            if unit.is_none() {
+                unit = Some(code.to_string());
+            }
+        }
+        if let Some(multiplier) = words.get(end).and_then(|w| normalize::magnitude(w)) {
+            value *= multiplier;
+            end += 1;
+        }
+        if let Some(code) = currency(end) {
+            if unit.is_none() {
+                unit = Some(code.to_string());
+                end += 1;
+            }
+        }
+        if words.get(end).is_some_and(|w| w == "%" || w == "percent") {
+            percent = true;
+            end += 1;
+        }
+        let mut months = duration.map(|m| value * m);
+        if months.is_none() {
+            if let Some(m) = words.get(end).and_then(|w| duration_months(w)) {
+                months = Some(value * m);
+                end += 1;
+            }
+        }
+
+        out.push(Quantity {
+            value,
+            unit,
+            percent,
+            year_like: year_like && months.is_none(),
+            months,
+            start,
+            end,
+        });
+        i = end;
+    }
+    out
+}
+
+/// The period a metric is stated for: the date or period nearest its
+/// name in the sentence, e.g. `as of Q3 2025` or `in FY2024`.
+pub(super) fn period(tokens: &[&str], at: usize) -> Option<String> {
+    let clean = |t: &str| t.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '.' | '"')).to_string();
+    let mut found: Vec<(usize, String)> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let pair = tokens.get(i + 1).map(|next| format!("{} {}", clean(tokens[i]), clean(next)));
+        if let Some(CellValue::Date { value }) = pair.as_deref().map(|p| cell_value::parse(p, None)) {
+            found.push((i, value));
+            i += 2;
+            continue;
+        }
+        let single = clean(tokens[i]);
+        // A lone year counts only when it reads like one: 2025, FY25, 2025E.
+        if single.chars().filter(|c| c.is_ascii_digit()).count() >= 4 || single.to_lowercase().starts_with("fy") {
+            if let CellValue::Date { value } = cell_value::parse(&single, None) {
+                found.push((i, value));
+            }
+        }
+        i += 1;
+    }
+    found
+        .into_iter()
+        .min_by_key(|(i, _)| i.abs_diff(at))
+        .map(|(_, value)| value)
+}
+
+fn cadence(words: &[String], kind: MetricKind) -> Option<String> {
+    if !matches!(kind, MetricKind::Burn | MetricKind::Churn | MetricKind::Revenue | MetricKind::Users) {
+        return None;
+    }
+    if words.iter().any(|w| MONTHLY.contains(&w.as_str())) {
+        Some("monthly".to_string())
+    } else if words.iter().any(|w| ANNUAL.contains(&w.as_str())) {
+        Some("annual".to_string())
+    } else {
+        None
+    }
+}
+
+/// Metrics in a table, named either by row labels, with periods as column
+/// headings, or by column headings, with periods as row labels. A unit in
+/// a heading, like `Revenue ($M)`, applies to every figure under it.
+/// Figures are read in `format` when the document's locale is known.
+fn from_table(table: &TableData, format: Option<NumberFormat>) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    let metric = |kind: MetricKind, label: &str, text: &str, period: Option<String>, row: &[String]| {
+        let (currency, multiplier) = scale(label);
+        let value = cell_value::parse(text, format);
+        let (value, unit, confidence) = match (kind.expects(), value) {
+            (Expects::Money, CellValue::Currency { value, currency }) => (value, Some(currency), 0.9),
+            (Expects::Money, CellValue::Number { value }) => (value * multiplier, currency.clone(), if currency.is_some() { 0.9 } else { 0.7 }),
+            (Expects::Percent, CellValue::Percentage { value }) => (value, Some("%".to_string()), 0.9),
+            (Expects::Percent, CellValue::Number { value }) if label.contains('%') => (value, Some("%".to_string()), 0.85),
+            (Expects::Months, CellValue::Number { value }) => (value, Some("months".to_string()), 0.8),
+            (Expects::Count, CellValue::Number { value }) if kind == MetricKind::Users => {
+                (value * multiplier, Some("users".to_string()), 0.85)
+            }
+            (Expects::Count, CellValue::Number { value }) => (value, Some("people".to_string()), 0.85),
+            _ => return None,
+        };
+        Some(Metric {
+            kind,
+            value,
+            unit,
+            period,
+            cadence: cadence(&tokens(label).iter().map(|t| normalize_word(t)).collect::<Vec<_>>(), kind),
+            text: row.join(" | "),
+            page: table.page,
+            source: "table".to_string(),
+            confidence,
+        })
+    };
+    let kind_of = |label: &str| {
+        let tokens = tokens(label);
+        let words: Vec<String> = tokens.iter().map(|t| normalize_word(t)).collect();
+        find_phrases(&tokens, &words, KEYWORDS).first().map(|(kind, _, _)| *kind)
+    };
+    let date = |text: &str| match cell_value::parse(text, None) {
+        CellValue::Date { value } => Some(value),
+        _ => None,
+    };
+
+    for row in &table.rows {
+        let Some(kind) = row.first().and_then(|label| kind_of(label)) else {
+            continue;
+        };
+        for (col, text) in row.iter().enumerate().skip(1) {
+            let period = table.headers.get(col).and_then(|h| date(h));
+            metrics.extend(metric(kind, &row[0], text, period, row));
+        }
+    }
+    for (col, heading) in table.headers.iter().enumerate().skip(1) {
+        let Some(kind) = kind_of(heading) else {
+            continue;
+        };
+        for row in &table.rows {
+            let Some(text) = row.get(col) else {
+                continue;
+            };
+            let period = row.first().and_then(|label| date(label));
+            metrics.extend(metric(kind, heading, text, period, row));
+        }
+    }
+    metrics
+}
+
+/// Currency and multiplier stated in a heading, e.g. `($M)`, `(EUR k)` or
+/// `in millions`.
+fn scale(label: &str) -> (Option<String>, f64) {
+    let lower = label.to_lowercase();
+    let currency = [("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("usd", "USD"), ("eur", "EUR"), ("gbp", "GBP")]
+        .iter()
+        .find(|(symbol, _)| lower.contains(symbol))
+        .map(|(_, code)| code.to_string());
+    let multiplier = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter_map(normalize::magnitude)
+        .next()
+        .or_else(|| lower.contains("'000").then_some(1e3))
+        .unwrap_or(1.0);
+    (currency, multiplier)
+}
+
+/// Months in one unit of a duration word.
+fn duration_months(word: &str) -> Option<f64> {
+    match word.to_lowercase().as_str() {
+        "month" | "months" | "mo" | "mos" => Some(1.0),
+        "year" | "years" | "yr" | "yrs" => Some(12.0),
+        _ => None,
+    }
+}
+
+/// Clauses of a block: lines, and sentences and `;`-separated parts of them.
+pub(crate) fn sentences(text: &str) -> Vec<&str> {
+    text.split(['\n', ';', '\u{2022}'])
+        .flat_map(|line| line.split(". "))
+        .filter(|s| !s.trim().is_empty())
+        .collect()
+}
+
+/// Whitespace-separated tokens, with `/` splitting rates like `$150K/mo`.
+pub(crate) fn tokens(sentence: &str) -> Vec<&str> {
+    sentence
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+pub(crate) fn normalize_word(token: &str) -> String {
+    token
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '%' && c != '-')
+        .trim_matches('-')
+        .to_lowercase()
+}
+
+/// A token without a footnote marker at its end, like the `¹` in `$50B¹`
+/// or the `[2]` in `$50B[2]`, and the marker: its number, or the symbol
+/// for `*` and `†`.
+pub(super) fn footnote(token: &str) -> (&str, Option<String>) {
+    if let Some((head, n)) = token.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+        if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) {
+            return (head, Some(n.to_string()));
+        }
+    }
+    let head = token.trim_end_matches(|c: char| superscript(c).is_some() || matches!(c, '*' | '\u{2020}' | '\u{2021}'));
+    if head.len() == token.len() {
+        return (token, None);
+    }
+    let marker = &token[head.len()..];
+    let digits: String = marker.chars().filter_map(superscript).collect();
+    (head, Some(if digits.is_empty() { marker.to_string() } else { digits }))
+}
+
+/// The digit a superscript digit stands for.
+pub(super) fn superscript(c: char) -> Option<char> {
+    "\u{2070}\u{b9}\u{b2}\u{b3}\u{2074}\u{2075}\u{2076}\u{2077}\u{2078}\u{2079}"
+        .chars()
+        .position(|s| s == c)
+        .and_then(|d| char::from_digit(d as u32, 10))
+}
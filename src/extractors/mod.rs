@@ -1,22 +1,121 @@
-use crate::{models::*, error::*};
+use crate::{models::*, error::*, language::{detect_language, NumberFormat}, pipeline::*};
 
+mod blocks;
+mod market;
+mod metrics;
+mod sections;
+
+pub(crate) use metrics::{normalize_word, sentences, tokens};
+
+/// Text blocks of every page in reading order, classified as titles,
+/// headings, bullets with their nesting level, paragraphs, captions,
+/// running headers and footers, and page numbers.
 pub fn extract_text(content: &ExtractedContent) -> Result<Vec<TextBlock>> {
-    // Extract and structure text
-    let text_blocks = vec![
-        TextBlock {
-            content: content.text.clone(),
-            block_type: "paragraph".to_string(),
-            confidence: 0.95,
-        },
-    ];
-    Ok(text_blocks)
+    Ok(blocks::classify(content))
 }
 
+/// Document outline: its sections, each mapped where possible to a
+/// standard pitch-deck section, and the language of the document and of
+/// each page.
 pub fn extract_structure(content: &ExtractedContent) -> Result<DocumentStructure> {
-    // Analyze document structure
-    Ok(DocumentStructure {
-        sections: vec![],
+    Ok(structure(content, &blocks::classify(content)))
+}
+
+/// Business metrics such as ARR, gross margin, burn and headcount, read
+/// from the document's text and tables. Numbers are read the way the
+/// document's language writes them.
+pub fn extract_metrics(content: &ExtractedContent) -> Result<Vec<Metric>> {
+    let tables: Vec<TableData> = content.tables().cloned().collect();
+    let format = detect_language(&content.text).map(|g| NumberFormat::for_language(&g.language));
+    Ok(metrics::extract(&blocks::classify(content), &tables, format))
+}
+
+/// TAM, SAM, SOM and other market-size claims, with the year, geography,
+/// growth rate and source stated for each.
+pub fn extract_market_claims(content: &ExtractedContent) -> Result<Vec<MarketClaim>> {
+    let format = detect_language(&content.text).map(|g| NumberFormat::for_language(&g.language));
+    Ok(market::extract(&blocks::classify(content), format))
+}
+
+fn structure(content: &ExtractedContent, blocks: &[TextBlock]) -> DocumentStructure {
+    let page_languages = content
+        .pages
+        .iter()
+        .filter_map(|page| {
+            let guess = detect_language(&page.text)?;
+            Some(PageLanguage {
+                page: page.number,
+                language: guess.language,
+                confidence: guess.confidence,
+            })
+        })
+        .collect();
+    DocumentStructure {
+        sections: sections::detect(content, blocks),
         total_pages: content.metadata.pages.unwrap_or(1),
-        language: None,
-    })
+        language: detect_language(&content.text).map(|g| g.language),
+        page_languages,
+    }
+}
+
+/// Classifies the text of every page into `ProcessedData::text_blocks`.
+pub struct TextBlockStage;
+
+impl Stage for TextBlockStage {
+    fn name(&self) -> &str {
+        "text_blocks"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        ctx.processed.text_blocks = extract_text(&ctx.extracted)?;
+        Ok(())
+    }
+}
+
+/// Fills `ProcessedData::structure` from the text blocks found before it.
+pub struct StructureStage;
+
+impl Stage for StructureStage {
+    fn name(&self) -> &str {
+        "structure"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        ctx.processed.structure = structure(&ctx.extracted, &ctx.processed.text_blocks);
+        Ok(())
+    }
+}
+
+/// Reads metrics from the text blocks found before it and from tables
+/// into `ProcessedData::metrics`, in the number format of the language
+/// the structure stage detected.
+pub struct MetricStage;
+
+impl Stage for MetricStage {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        let tables: Vec<TableData> = ctx.extracted.tables().cloned().collect();
+        let format = ctx.processed.structure.language.as_deref().map(NumberFormat::for_language);
+        ctx.processed.metrics = metrics::extract(&ctx.processed.text_blocks, &tables, format);
+        Ok(())
+    }
+}
+
+/// Reads market-size claims from the text blocks found before it into
+/// `ProcessedData::market_claims`.
+pub struct MarketStage;
+
+impl Stage for MarketStage {
+    fn name(&self) -> &str {
+        "market"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        let format = ctx.processed.structure.language.as_deref().map(NumberFormat::for_language);
+        ctx.processed.market_claims = market::extract(&ctx.processed.text_blocks, format);
+        Ok(())
+    }
 }
@@ -0,0 +1,197 @@
+// Pitch-deck section detection
+use crate::models::*;
+
+/// Phrases that name each section, as they appear in slide titles and
+/// body text. Longer phrases outweigh shorter ones, so `go-to-market`
+/// reads as a business model rather than a market slide.
+const KEYWORDS: &[(SectionKind, &[&str])] = &[
+    (SectionKind::Problem, &[
+        "problem", "problems", "pain point", "pain points", "the challenge", "challenges", "status quo",
+        "what's broken", "the issue",
+    ]),
+    (SectionKind::Solution, &[
+        "solution", "our solution", "our approach", "how it works", "value proposition", "introducing",
+    ]),
+    (SectionKind::Market, &[
+        "market", "market size", "market opportunity", "opportunity", "tam", "sam", "som",
+        "addressable market", "why now", "industry", "market landscape",
+    ]),
+    (SectionKind::Product, &[
+        "product", "products", "demo", "features", "technology", "platform", "product roadmap", "roadmap",
+        "how we do it",
+    ]),
+    (SectionKind::BusinessModel, &[
+        "business model", "revenue model", "pricing", "monetization", "unit economics", "go to market",
+        "gtm", "sales strategy", "distribution", "how we make money",
+    ]),
+    (SectionKind::Traction, &[
+        "traction", "milestones", "growth", "key metrics", "momentum", "progress", "customers",
+        "our customers", "case study", "pilots", "arr", "mrr",
+    ]),
+    (SectionKind::Competition, &[
+        "competition", "competitive landscape", "competitors", "competitive advantage", "alternatives",
+        "differentiation", "why we win", "moat",
+    ]),
+    (SectionKind::Team, &[
+        "team", "our team", "founders", "founding team", "leadership", "management team", "advisors",
+        "advisory board", "who we are",
+    ]),
+    (SectionKind::Financials, &[
+        "financials", "financial projections", "projections", "forecast", "financial plan",
+        "revenue projections", "financial model", "p&l", "burn", "runway", "ebitda",
+    ]),
+    (SectionKind::Ask, &[
+        "the ask", "ask", "use of funds", "use of proceeds", "funding", "raising", "we are raising",
+        "investment", "fundraise", "fundraising", "round", "seed round", "series a", "allocation of funds",
+    ]),
+];
+
+/// Weighted body-text matches needed to name an untitled section.
+const MIN_CONTENT_SCORE: usize = 3;
+
+/// Sections of a document with the canonical pitch section each maps to.
+/// Slides are read one at a time; other documents split at top-level
+/// headings. Each part is mapped by the phrases in its title, falling back
+/// to its body text, and consecutive parts mapped to the same section are
+/// merged, so a two-slide traction story is one `Traction` section.
+pub fn detect(content: &ExtractedContent, blocks: &[TextBlock]) -> Vec<Section> {
+    let slides = super::blocks::is_slides(content);
+    let mut sections: Vec<Section> = Vec::new();
+
+    for part in parts(blocks, slides) {
+        let (kind, confidence) = classify(&part);
+        let section = Section {
+            title: part.title.clone(),
+            content_blocks: part.body.len(),
+            kind,
+            confidence,
+            pages: part.pages.clone(),
+        };
+        match sections.last_mut() {
+            Some(last) if kind.is_some() && last.kind == kind => {
+                last.content_blocks += section.content_blocks;
+                last.confidence = last.confidence.max(confidence);
+                for page in section.pages {
+                    if !last.pages.contains(&page) {
+                        last.pages.push(page);
+                    }
+                }
+            }
+            _ => sections.push(section),
+        }
+    }
+    sections
+}
+
+/// A slide, or the run of blocks under one heading.
+struct Part<'a> {
+    title: String,
+    body: Vec<&'a TextBlock>,
+    pages: Vec<u32>,
+}
+
+fn parts(blocks: &[TextBlock], slides: bool) -> Vec<Part<'_>> {
+    let mut parts: Vec<Part> = Vec::new();
+    for block in blocks {
+        if matches!(block.block_type.as_str(), "header" | "footer" | "page_number") {
+            continue;
+        }
+        let starts = match parts.last() {
+            None => true,
+            Some(part) if slides => !part.pages.contains(&block.page),
+            Some(_) => {
+                block.block_type == "title" || (block.block_type == "heading" && block.level.is_some_and(|l| l <= 2))
+            }
+        };
+        if starts {
+            parts.push(Part { title: String::new(), body: vec![], pages: vec![block.page] });
+        }
+        let part = parts.last_mut().expect("a part was just started");
+        if !part.pages.contains(&block.page) {
+            part.pages.push(block.page);
+        }
+        part.body.push(block);
+    }
+
+    // A slide is titled by its title block, or failing that its first
+    // heading, or its first line.
+    for part in &mut parts {
+        let title = ["title", "heading"]
+            .iter()
+            .find_map(|t| part.body.iter().position(|b| b.block_type == *t))
+            .unwrap_or(0);
+        let block = part.body.remove(title);
+        part.title = block.content.lines().collect::<Vec<_>>().join(" ");
+    }
+    parts
+}
+
+/// The section a part maps to and the confidence in that mapping.
+fn classify(part: &Part) -> (Option<SectionKind>, f32) {
+    let title = words(&part.title);
+    let title_scores = scores(&title, true);
+    let body: Vec<String> = part.body.iter().flat_map(|b| words(&b.content)).collect();
+    let body_scores = scores(&body, false);
+
+    let body_best = best(&body_scores);
+    if let Some((kind, _, unique)) = best(&title_scores) {
+        let mut confidence = if unique { 0.9 } else { 0.6 };
+        if body_best.is_some_and(|(k, _, _)| k == kind) {
+            confidence += 0.05;
+        }
+        return (Some(kind), confidence);
+    }
+
+    // No telling title: go by what the body talks about most, trusting it
+    // by how clearly one section leads.
+    let Some((kind, score, _)) = body_best.filter(|(_, s, _)| *s >= MIN_CONTENT_SCORE) else {
+        return (None, 0.0);
+    };
+    let runner_up = body_scores.iter().map(|(_, s)| *s).filter(|s| *s < score).max().unwrap_or(0);
+    let lead = (score - runner_up) as f32 / score as f32;
+    let confidence = 0.3 + 0.45 * lead;
+    if confidence < 0.4 {
+        return (None, 0.0);
+    }
+    (Some(kind), confidence)
+}
+
+/// Weighted phrase matches per section. In a title each phrase counts
+/// once, by its length in words; in body text every occurrence counts.
+fn scores(words: &[String], title: bool) -> Vec<(SectionKind, usize)> {
+    KEYWORDS
+        .iter()
+        .map(|(kind, phrases)| {
+            let score = phrases
+                .iter()
+                .map(|phrase| {
+                    let phrase: Vec<&str> = phrase.split(' ').collect();
+                    let hits = words
+                        .windows(phrase.len())
+                        .filter(|w| w.iter().zip(&phrase).all(|(a, b)| a == b))
+                        .count();
+                    let hits = if title { hits.min(1) } else { hits };
+                    hits * phrase.len()
+                })
+                .sum();
+            (*kind, score)
+        })
+        .collect()
+}
+
+/// The highest-scoring section, its score, and whether it leads alone.
+fn best(scores: &[(SectionKind, usize)]) -> Option<(SectionKind, usize, bool)> {
+    let top = scores.iter().map(|(_, s)| *s).max().filter(|s| *s > 0)?;
+    let mut leaders = scores.iter().filter(|(_, s)| *s == top);
+    let (kind, _) = leaders.next()?;
+    Some((*kind, top, leaders.next().is_none()))
+}
+
+/// Lower-case words, split at anything but letters, digits, `&` and `'`.
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '&' || c == '\'' || c == '\u{2019}'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.replace('\u{2019}', "'"))
+        .collect()
+}
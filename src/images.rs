@@ -0,0 +1,125 @@
+// Image byte storage: inline, on disk, or by content hash
+use crate::{error::*, models::*, pipeline::*};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+/// Where the bytes of extracted images end up once a document is processed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ImageStorage {
+    /// Keep the bytes in `ImageData::data`; serialized as base64.
+    #[default]
+    Inline,
+    /// Write each image once to `<dir>/<sha256>.<ext>` and reference it
+    /// from `ImageData::path`. The extension is the image format reduced
+    /// to lowercase ASCII letters and digits.
+    Directory(PathBuf),
+    /// Drop the bytes; images are identified by `ImageData::sha256` alone.
+    HashOnly,
+}
+
+/// Hex SHA-256 of image bytes, the key images are stored under.
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Move the bytes of every image in `content` to `storage`. Images drawn
+/// several times, like a logo on every slide, are written once.
+pub fn store_images(content: &mut ExtractedContent, storage: &ImageStorage) -> Result<()> {
+    if let ImageStorage::Directory(dir) = storage {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    for image in content.pages.iter_mut().flat_map(|p| p.images.iter_mut()) {
+        match storage {
+            ImageStorage::Inline => {}
+            ImageStorage::Directory(dir) => {
+                if image.data.is_empty() {
+                    continue;
+                }
+                let file = dir.join(format!("{}.{}", image.sha256, extension(&image.format)));
+                if !file.exists() {
+                    std::fs::write(&file, &image.data)?;
+                }
+                image.path = Some(file.to_string_lossy().into_owned());
+                image.data = Vec::new();
+            }
+            ImageStorage::HashOnly => image.data = Vec::new(),
+        }
+    }
+    Ok(())
+}
+
+/// File extension for an image `format`: only `[a-z0-9]` survive, so a
+/// format read from a document cannot name a path outside the directory.
+fn extension(format: &str) -> String {
+    let ext: String = format
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if ext.is_empty() {
+        "bin".to_string()
+    } else {
+        ext
+    }
+}
+
+/// Applies an `ImageStorage` to the extracted images. Add it last, after
+/// every stage that reads image bytes, e.g.
+/// `Pipeline::default().with_stage(ImageStorageStage::new(storage))`.
+pub struct ImageStorageStage {
+    storage: ImageStorage,
+}
+
+impl ImageStorageStage {
+    pub fn new(storage: ImageStorage) -> Self {
+        Self { storage }
+    }
+}
+
+impl Stage for ImageStorageStage {
+    fn name(&self) -> &str {
+        "image_storage"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        store_images(&mut ctx.extracted, &self.storage)
+    }
+}
+
+impl ImageData {
+    /// Encoded bytes of the image, read from `path` when they were moved
+    /// to a side directory.
+    pub fn bytes(&self) -> Result<Cow<'_, [u8]>> {
+        if !self.data.is_empty() {
+            return Ok(Cow::Borrowed(&self.data));
+        }
+        match &self.path {
+            Some(path) => Ok(Cow::Owned(std::fs::read(path)?)),
+            None => Err(TraceDeckError::ImageError(format!(
+                "bytes of {} are not stored (sha256 {})",
+                self.id, self.sha256
+            ))),
+        }
+    }
+}
+
+/// Serde adapter writing byte buffers as base64 strings rather than
+/// arrays of numbers.
+pub(crate) mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,139 @@
+// Offline language identification and per-language number conventions
+use serde::{Deserialize, Serialize};
+
+/// Frequent function words of each language. Words shared between
+/// languages count for each; the distinctive ones decide.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &[
+        "the", "and", "of", "to", "in", "is", "for", "that", "with", "on", "are", "we", "our", "by",
+        "this", "from", "it", "as", "be", "at", "or", "an", "have", "has", "will", "you", "your", "their",
+        "which", "more", "than", "per", "into", "over",
+    ]),
+    ("es", &[
+        "el", "la", "los", "las", "de", "del", "y", "en", "que", "por", "para", "con", "una", "un", "es",
+        "son", "al", "se", "su", "sus", "nuestro", "nuestra", "nuestros", "más", "como", "pero", "este",
+        "esta", "entre", "también", "hasta", "desde", "año", "años",
+    ]),
+    ("pt", &[
+        "o", "os", "as", "de", "do", "da", "dos", "das", "e", "em", "no", "na", "nos", "nas", "que",
+        "para", "com", "uma", "um", "é", "são", "ao", "pelo", "pela", "seu", "sua", "nosso", "nossa",
+        "mais", "como", "mas", "não", "também", "até", "ano", "anos",
+    ]),
+    ("de", &[
+        "der", "die", "das", "und", "den", "dem", "des", "ist", "sind", "mit", "für", "von", "zu", "auf",
+        "ein", "eine", "einen", "nicht", "wir", "unser", "unsere", "auch", "bei", "im", "zum", "zur",
+        "über", "durch", "oder", "als", "wie", "jahr", "pro",
+    ]),
+    ("fr", &[
+        "le", "la", "les", "de", "des", "du", "et", "en", "un", "une", "est", "sont", "pour", "avec",
+        "que", "qui", "dans", "sur", "par", "au", "aux", "nous", "notre", "nos", "plus", "pas", "ce",
+        "cette", "ces", "mais", "ou", "leur", "année",
+    ]),
+    ("it", &[
+        "il", "lo", "la", "gli", "le", "di", "del", "della", "dei", "e", "che", "per", "con", "un", "una",
+        "è", "sono", "nel", "nella", "al", "alla", "non", "più", "come", "ma", "anche", "nostro",
+        "nostra", "questo", "questa", "anno", "anni",
+    ]),
+    ("nl", &[
+        "de", "het", "een", "en", "van", "in", "is", "zijn", "voor", "met", "op", "dat", "die", "te",
+        "niet", "ook", "wij", "we", "ons", "onze", "aan", "bij", "om", "door", "naar", "dan", "jaar",
+        "per", "meer",
+    ]),
+];
+
+/// Letters found in few of the languages above, with the languages they
+/// point to.
+const LETTERS: &[(char, &[&str])] = &[
+    ('ñ', &["es"]),
+    ('¿', &["es"]),
+    ('¡', &["es"]),
+    ('ã', &["pt"]),
+    ('õ', &["pt"]),
+    ('ç', &["pt", "fr"]),
+    ('ß', &["de"]),
+    ('ä', &["de"]),
+    ('ö', &["de"]),
+    ('ü', &["de"]),
+    ('è', &["fr", "it"]),
+    ('ê', &["fr", "pt"]),
+    ('œ', &["fr"]),
+    ('ì', &["it"]),
+    ('ò', &["it"]),
+];
+
+/// Stopword hits below which text is too short to call.
+const MIN_HITS: usize = 3;
+
+/// Stopword hits at which a clear lead is fully trusted.
+const FULL_EVIDENCE: usize = 12;
+
+/// The language a text is written in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageGuess {
+    pub language: String,  // ISO 639-1 code, e.g. "es"
+    pub confidence: f32,
+}
+
+/// Identify the language of `text` among English, Spanish, Portuguese,
+/// German, French, Italian and Dutch from its function words and
+/// distinctive letters. Returns `None` when there is too little text to
+/// tell.
+pub fn detect_language(text: &str) -> Option<LanguageGuess> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let mut scores: Vec<(&str, f32)> = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|w| stopwords.contains(w)).count();
+            (*language, hits as f32)
+        })
+        .collect();
+    let hits = scores.iter().map(|s| s.1).fold(0.0, f32::max) as usize;
+    if hits < MIN_HITS {
+        return None;
+    }
+    for c in lower.chars() {
+        if let Some((_, languages)) = LETTERS.iter().find(|(l, _)| *l == c) {
+            for (language, score) in &mut scores {
+                if languages.contains(language) {
+                    *score += 0.5;
+                }
+            }
+        }
+    }
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (language, best) = scores[0];
+    let lead = (best - scores[1].1) / best;
+    let evidence = (hits as f32 / FULL_EVIDENCE as f32).min(1.0);
+    let confidence = (0.5 + 0.5 * lead) * (0.5 + 0.5 * evidence);
+    Some(LanguageGuess {
+        language: language.to_string(),
+        confidence,
+    })
+}
+
+/// How numbers are written in a language: `1,200.50` in English,
+/// `1.200,50` in Spanish or German, `1 200,50` in French.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    pub grouping: char,
+}
+
+impl NumberFormat {
+    pub const ENGLISH: NumberFormat = NumberFormat { decimal: '.', grouping: ',' };
+
+    /// Conventions for an ISO 639-1 code, English for unknown languages.
+    pub fn for_language(language: &str) -> Self {
+        match language {
+            "es" | "pt" | "de" | "it" | "nl" | "id" | "tr" => NumberFormat { decimal: ',', grouping: '.' },
+            "fr" | "ru" | "pl" | "sv" | "no" | "fi" | "cs" => NumberFormat { decimal: ',', grouping: ' ' },
+            _ => Self::ENGLISH,
+        }
+    }
+}
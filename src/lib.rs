@@ -4,26 +4,102 @@ pub mod extractors;
 pub mod processors;
 pub mod models;
 pub mod error;
+pub mod images;
+pub mod language;
+pub mod normalize;
+pub mod pipeline;
+pub mod verify;
+pub mod risk;
+pub mod ocr;
 pub mod ui;
 
 pub use models::*;
 pub use error::*;
+pub use images::*;
+pub use language::*;
+pub use normalize::{parse_quantity, FxRates, Period, Quantity, Unit};
+pub use pipeline::*;
+pub use verify::*;
+pub use risk::*;
+pub use ocr::*;
 pub use parsers::*;
 pub use extractors::*;
 pub use processors::*;
 pub use ui::*;
 
-/// Process any document (PDF, DOCX, Images)
+/// Process any document (PDF, DOCX, PPTX, Images)
 pub async fn process_document(path: &str) -> Result<DocumentResult> {
+    process_document_with(path, &Pipeline::default()).await
+}
+
+/// Process a document through `pipeline`, e.g. the default pipeline with
+/// extra analysis stages added.
+pub async fn process_document_with(path: &str, pipeline: &Pipeline) -> Result<DocumentResult> {
     let start = std::time::Instant::now();
     let parser = parsers::DocumentParser::new(path)?;
+    run(parser, pipeline, start).await
+}
+
+/// Process a document held in memory; `filename` is an optional hint used
+/// like a file extension.
+pub async fn process_bytes(bytes: impl Into<Vec<u8>>, filename: Option<&str>) -> Result<DocumentResult> {
+    process_bytes_with(bytes, filename, &Pipeline::default()).await
+}
+
+/// Process a document held in memory through `pipeline`.
+pub async fn process_bytes_with(
+    bytes: impl Into<Vec<u8>>,
+    filename: Option<&str>,
+    pipeline: &Pipeline,
+) -> Result<DocumentResult> {
+    let start = std::time::Instant::now();
+    let parser = parsers::DocumentParser::from_bytes(bytes, filename)?;
+    run(parser, pipeline, start).await
+}
+
+/// Process a document read to the end from `reader`, e.g. stdin or an
+/// upload body.
+pub async fn process_reader<R>(reader: R, filename: Option<&str>) -> Result<DocumentResult>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    process_reader_with(reader, filename, &Pipeline::default()).await
+}
+
+/// Process a document read from `reader` through `pipeline`.
+pub async fn process_reader_with<R>(reader: R, filename: Option<&str>, pipeline: &Pipeline) -> Result<DocumentResult>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let start = std::time::Instant::now();
+    let parser = parsers::DocumentParser::from_reader(reader, filename).await?;
+    run(parser, pipeline, start).await
+}
+
+/// Process a document already opened as `parser`, e.g. one whose detected
+/// type has been inspected or whose OCR engine has been configured.
+pub async fn process_parser(parser: parsers::DocumentParser, pipeline: &Pipeline) -> Result<DocumentResult> {
+    run(parser, pipeline, std::time::Instant::now()).await
+}
+
+async fn run(
+    parser: parsers::DocumentParser,
+    pipeline: &Pipeline,
+    start: std::time::Instant,
+) -> Result<DocumentResult> {
     let extracted = parser.extract().await?;
-    let processed = processors::process(&extracted)?;
+    // Stages may block, e.g. on a data provider's requests.
+    let pipeline = pipeline.clone();
+    let ctx = tokio::task::spawn_blocking(move || pipeline.run(extracted))
+        .await
+        .map_err(std::io::Error::from)??;
+    let DocumentContext { extracted, processed, risk_flags } = ctx;
     let processing_time_ms = start.elapsed().as_millis();
     
     Ok(DocumentResult {
         extracted,
         processed,
+        risk_flags,
         processing_time_ms,
     })
 }
@@ -30,6 +30,12 @@ enum Commands {
         timing: bool,
         #[arg(short, long)]
         verbose: bool,
+        /// Write images to DIR and reference them by path instead of embedding them
+        #[arg(long, value_name = "DIR")]
+        image_dir: Option<String>,
+        /// Reference images by content hash only, leaving their bytes out
+        #[arg(long, conflicts_with = "image_dir")]
+        image_hashes: bool,
     },
 
     /// Extract text content from document
@@ -80,8 +86,13 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Process { file, format, timing, verbose }) => {
-            process_document_cmd(&file, &format, timing, verbose).await?;
+        Some(Commands::Process { file, format, timing, verbose, image_dir, image_hashes }) => {
+            let storage = match (image_dir, image_hashes) {
+                (Some(dir), _) => ImageStorage::Directory(dir.into()),
+                (None, true) => ImageStorage::HashOnly,
+                (None, false) => ImageStorage::Inline,
+            };
+            process_document_cmd(&file, &format, timing, verbose, &storage).await?;
         }
         Some(Commands::Extract { file, text_only }) => {
             extract_text_cmd(&file, text_only).await?;
@@ -96,7 +107,7 @@ async fn main() -> Result<()> {
         Some(Commands::Export { file, output }) => export_results(&file, &output).await?,
         None => {
             if let Some(file) = cli.file {
-                process_document_cmd(&file, "pretty", false, false).await?;
+                process_document_cmd(&file, "pretty", false, false, &ImageStorage::Inline).await?;
             }
         }
     }
@@ -104,8 +115,16 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn process_document_cmd(file: &str, format: &str, timing: bool, verbose: bool) -> Result<()> {
-    if !Path::new(file).exists() {
+async fn process_document_cmd(
+    file: &str,
+    format: &str,
+    timing: bool,
+    verbose: bool,
+    storage: &ImageStorage,
+) -> Result<()> {
+    // `-` reads the document from stdin.
+    let from_stdin = file == "-";
+    if !from_stdin && !Path::new(file).exists() {
         ui::error(&format!("File not found: {}", file));
         std::process::exit(1);
     }
@@ -114,20 +133,48 @@ async fn process_document_cmd(file: &str, format: &str, timing: bool, verbose: b
     ui::status_line("info", "Processing document...");
     
     ui::subsection("Input Details");
-    let metadata = std::fs::metadata(file)?;
-    ui::pair("Path", file);
-    ui::pair("Size", &ui::format_size(metadata.len()));
+    let data = if from_stdin {
+        let mut data = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut tokio::io::stdin(), &mut data).await?;
+        data
+    } else {
+        std::fs::read(file)?
+    };
+    ui::pair("Path", if from_stdin { "<stdin>" } else { file });
+    ui::pair("Size", &ui::format_size(data.len() as u64));
     
+    let name = (!from_stdin).then_some(file);
     let ext = Path::new(file)
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("unknown");
-    ui::pair("Type", ext);
+    let parser = DocumentParser::from_bytes(data, name).map(|parser| {
+        let engine = TesseractEngine::default();
+        if engine.is_available() {
+            parser.with_ocr(engine)
+        } else {
+            parser
+        }
+    });
+    match &parser {
+        Ok(parser) if name.is_some() && !parser.file_type().eq_ignore_ascii_case(ext) => {
+            ui::pair("Type", &format!("{} (extension says {})", parser.file_type(), ext))
+        }
+        Ok(parser) => ui::pair("Type", parser.file_type()),
+        Err(_) => ui::pair("Type", ext),
+    }
     
     println!();
 
     let start = Instant::now();
-    match process_document(file).await {
+    let result = match parser {
+        Ok(parser) => {
+            let pipeline = Pipeline::default().with_stage(ImageStorageStage::new(storage.clone()));
+            process_parser(parser, &pipeline).await
+        }
+        Err(e) => Err(e),
+    };
+    match result {
         Ok(result) => {
             let elapsed = start.elapsed();
             
@@ -140,6 +187,30 @@ async fn process_document_cmd(file: &str, format: &str, timing: bool, verbose: b
                 _ => println!("{}", serde_json::to_string_pretty(&result)?),
             }
 
+            // Machine-readable output stays parseable; the flags are in the JSON.
+            if format != "json" {
+                ui::rule();
+                ui::subsection("Risk Flags");
+                if result.risk_flags.is_empty() {
+                    ui::success("No risk flags");
+                }
+                for flag in &result.risk_flags {
+                    let status = match flag.severity {
+                        Severity::High => "err",
+                        Severity::Medium => "warn",
+                        Severity::Low => "info",
+                    };
+                    let pages = match flag.pages.as_slice() {
+                        [] => String::new(),
+                        [page] => format!(" (page {})", page),
+                        pages => format!(" (pages {})", pages.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ")),
+                    };
+                    let category = serde_json::to_value(flag.category)?;
+                    ui::status_line(status, &format!("[{}] {}{}", category.as_str().unwrap_or_default(), flag.explanation, pages));
+                }
+                println!();
+            }
+
             if timing {
                 ui::rule();
                 ui::subsection("Performance Metrics");
@@ -153,6 +224,13 @@ async fn process_document_cmd(file: &str, format: &str, timing: bool, verbose: b
                 ui::subsection("Document Statistics");
                 ui::pair("Text Blocks", &result.processed.text_blocks.len().to_string());
                 ui::pair("Visual Elements", &result.processed.visual_elements.len().to_string());
+                ui::pair("Sections", &result.processed.structure.sections.len().to_string());
+                ui::pair("Metrics", &result.processed.metrics.len().to_string());
+                ui::pair("Market claims", &result.processed.market_claims.len().to_string());
+                ui::pair("Risk flags", &result.risk_flags.len().to_string());
+                if let Some(language) = &result.processed.structure.language {
+                    ui::pair("Language", language);
+                }
                 ui::pair("Pages", &result.extracted.metadata.pages.unwrap_or(1).to_string());
                 ui::pair("Analyzed Size", &ui::format_size(result.extracted.metadata.file_size));
                 println!();
@@ -186,22 +264,46 @@ async fn extract_text_cmd(file: &str, text_only: bool) -> Result<()> {
                 println!("{}", result.extracted.text);
             } else {
                 ui::section("Text Content");
-                println!("{}", result.extracted.text);
+                for page in &result.extracted.pages {
+                    ui::subsection(&format!("Page {}", page.number));
+                    println!("{}", page.text);
+                }
                 
                 ui::section("Document Metadata");
                 let mut table = ui::Table::new(vec!["Property", "Value"]);
                 table.add_row(vec!["Type", &result.extracted.metadata.file_type]);
-                table.add_row(vec!["Size", &ui::format_size(result.extracted.metadata.file_size as u64)]);
+                table.add_row(vec!["Size", &ui::format_size(result.extracted.metadata.file_size)]);
                 if let Some(pages) = result.extracted.metadata.pages {
                     table.add_row(vec!["Pages", &pages.to_string()]);
                 }
                 if let Some(title) = &result.extracted.metadata.title {
                     table.add_row(vec!["Title", title]);
                 }
+                let metadata = &result.extracted.metadata;
+                for (label, value) in [
+                    ("Author", &metadata.author),
+                    ("Created With", &metadata.creator_tool),
+                    ("Producer", &metadata.producer),
+                    ("Created", &metadata.created_at),
+                    ("Modified", &metadata.modified_at),
+                ] {
+                    if let Some(value) = value {
+                        table.add_row(vec![label, value]);
+                    }
+                }
+                if !metadata.keywords.is_empty() {
+                    table.add_row(vec!["Keywords", &metadata.keywords.join(", ")]);
+                }
                 table.print();
 
-                if !result.extracted.images.is_empty() {
-                    ui::status_line("info", &format!("Found {} images", result.extracted.images.len()));
+                let image_count = result.extracted.images().count();
+                if image_count > 0 {
+                    ui::status_line("info", &format!("Found {} images", image_count));
+                }
+
+                let chart_count = result.extracted.charts().count();
+                if chart_count > 0 {
+                    ui::status_line("info", &format!("Found {} charts", chart_count));
                 }
             }
         }
@@ -306,6 +408,7 @@ fn show_formats() {
     let mut table = ui::Table::new(vec!["Format", "Extension", "Description"]);
     table.add_row(vec!["PDF", ".pdf", "Portable Document Format"]);
     table.add_row(vec!["Word", ".docx", "Microsoft Word Document"]);
+    table.add_row(vec!["PowerPoint", ".pptx", "Microsoft PowerPoint Deck"]);
     table.add_row(vec!["PNG", ".png", "Portable Network Graphics"]);
     table.add_row(vec!["JPEG", ".jpg, .jpeg", "Joint Photographic Experts"]);
     table.add_row(vec!["GIF", ".gif", "Graphics Interchange Format"]);
@@ -325,9 +428,9 @@ fn show_info() {
 
     ui::section("Capabilities");
     let mut table = ui::Table::new(vec!["Category", "Features"]);
-    table.add_row(vec!["Input", "PDF, DOCX, PNG, JPG, GIF, WebP"]);
+    table.add_row(vec!["Input", "PDF, DOCX, PPTX, PNG, JPG, GIF, WebP"]);
     table.add_row(vec!["Output", "JSON (structured)"]);
-    table.add_row(vec!["Processing", "Text, metadata, images, tables"]);
+    table.add_row(vec!["Processing", "Text, metadata, images, tables, charts"]);
     table.add_row(vec!["Performance", "Optimized for large documents"]);
     table.print();
     
@@ -353,6 +456,11 @@ fn check_system() {
     ui::status_line("ok", "Async executor active");
     ui::status_line("ok", "Document parsing available");
     ui::status_line("ok", "JSON serialization ready");
+    if TesseractEngine::default().is_available() {
+        ui::status_line("ok", "OCR engine available (tesseract)");
+    } else {
+        ui::status_line("warn", "OCR engine not found: image-only pages will have no text");
+    }
     println!();
 
     ui::section("System Configuration");
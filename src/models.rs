@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentMetadata {
@@ -8,14 +9,145 @@ pub struct DocumentMetadata {
     pub title: Option<String>,
     pub author: Option<String>,
     pub created_at: Option<String>,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    #[serde(default)]
+    pub creator_tool: Option<String>,  // authoring application, e.g. Keynote
+    #[serde(default)]
+    pub producer: Option<String>,      // tool that wrote the file, e.g. a PDF library
+    #[serde(default)]
+    pub keywords: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedContent {
+    pub text: String,           // all pages, separated by blank lines
+    pub pages: Vec<PageContent>,
+    pub metadata: DocumentMetadata,
+}
+
+impl ExtractedContent {
+    /// Images from every page, in page order.
+    pub fn images(&self) -> impl Iterator<Item = &ImageData> {
+        self.pages.iter().flat_map(|p| p.images.iter())
+    }
+
+    /// Tables from every page, in page order.
+    pub fn tables(&self) -> impl Iterator<Item = &TableData> {
+        self.pages.iter().flat_map(|p| p.tables.iter())
+    }
+
+    /// Charts from every page, in page order.
+    pub fn charts(&self) -> impl Iterator<Item = &ChartData> {
+        self.pages.iter().flat_map(|p| p.charts.iter())
+    }
+
+    /// Look up a page by its 1-based number.
+    pub fn page(&self, number: u32) -> Option<&PageContent> {
+        self.pages.iter().find(|p| p.number == number)
+    }
+}
+
+/// One page of a PDF, one slide of a deck, or the single page of an image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageContent {
+    pub number: u32,            // 1-based
     pub text: String,
+    pub spans: Vec<TextSpan>,   // in reading order
+    pub paragraphs: Vec<StyledParagraph>,  // flow-layout documents only
     pub images: Vec<ImageData>,
     pub tables: Vec<TableData>,
-    pub metadata: DocumentMetadata,
+    pub charts: Vec<ChartData>,
+    pub notes: Option<String>,  // speaker notes
+    #[serde(skip)]
+    pub paths: Vec<VectorPath>, // painted vector graphics, PDF only
+    pub width: Option<f32>,     // points for PDF/PPTX, pixels for images
+    pub height: Option<f32>,
+}
+
+impl PageContent {
+    pub fn new(number: u32) -> Self {
+        Self {
+            number,
+            text: String::new(),
+            spans: vec![],
+            paragraphs: vec![],
+            images: vec![],
+            tables: vec![],
+            charts: vec![],
+            notes: None,
+            paths: vec![],
+            width: None,
+            height: None,
+        }
+    }
+}
+
+/// Axis-aligned rectangle in page coordinates: origin at the top-left
+/// corner, y growing downwards, in the page's own units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl BoundingBox {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Smallest box containing both `self` and `other`.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        BoundingBox {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+}
+
+/// A painted path: polylines in page coordinates, curves reduced to their
+/// end points. Closed subpaths end on their first point.
+#[derive(Debug, Clone, Default)]
+pub struct VectorPath {
+    pub subpaths: Vec<Vec<(f32, f32)>>,
+    pub fill: Option<String>,    // fill colour as #rrggbb, when filled
+    pub stroke: Option<String>,  // stroke colour as #rrggbb, when stroked
+    pub line_width: f32,
+}
+
+/// A line fragment of text with its position on the page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSpan {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub font_size: f32,
+    pub confidence: Option<f32>,  // recognition confidence, for OCR text
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub list_level: Option<u32>,  // 0-based bullet level, where the source marks list items
+}
+
+/// A paragraph from a flow-layout document, with its named style.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyledParagraph {
+    pub text: String,
+    pub style: Option<String>,    // style id, e.g. Heading1, ListParagraph
+    pub list_level: Option<u32>,  // 0-based numbering level for list items
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,52 +156,385 @@ pub struct ImageData {
     pub format: String,
     pub width: u32,
     pub height: u32,
+    /// Encoded bytes, as stored in the source where possible. Empty once
+    /// moved elsewhere by `store_images`; `bytes()` reads them back.
+    #[serde(with = "crate::images::base64_bytes", default, skip_serializing_if = "Vec::is_empty")]
     pub data: Vec<u8>,
+    pub sha256: String,             // hex digest of the encoded bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,       // file holding the bytes, in directory storage
+    pub bbox: Option<BoundingBox>,  // placement on the page, when known
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableData {
-    pub rows: Vec<Vec<String>>,
-    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,     // body rows as text, merged cells in their first position
+    pub headers: Vec<String>,       // one per column, stacked header rows joined
+    #[serde(default)]
+    pub cells: Vec<TableCell>,      // every cell with its span and typed value, row by row
+    #[serde(default)]
+    pub header_rows: usize,
+    #[serde(default)]
+    pub page: u32,
+    #[serde(default)]
+    pub bbox: Option<BoundingBox>,
+    #[serde(default)]
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCell {
+    pub row: usize,       // 0-based, header rows included
+    pub col: usize,
+    pub row_span: usize,
+    pub col_span: usize,
+    pub header: bool,
+    pub text: String,
+    pub value: CellValue,
+}
+
+/// What a table cell states, parsed from its text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CellValue {
+    Empty,
+    Text,
+    Number { value: f64 },
+    Percentage { value: f64 },                  // in percent: 12.5% is 12.5
+    Currency { value: f64, currency: String },  // ISO 4217 code, e.g. USD
+    Date { value: String },                     // ISO 8601: 2025, 2025-03, 2025-03-14, 2025-Q3
+}
+
+/// Data behind a chart: one value per category for each series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartData {
+    pub chart_type: String,       // bar, column, line, pie, area, scatter, combo, ...
+    pub title: Option<String>,
+    pub categories: Vec<String>,
+    pub series: Vec<ChartSeries>,
+    pub value_axis: Option<ChartAxis>,
+    pub bbox: Option<BoundingBox>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSeries {
+    pub name: String,
+    pub values: Vec<Option<f64>>,  // aligned with `categories`; None for gaps
+    pub confidence: Vec<f32>,      // per value: 1.0 when read from chart data
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartAxis {
+    pub title: Option<String>,
+    pub number_format: Option<String>,  // e.g. "$#,##0", "0%"
+    pub unit: Option<String>,           // currency symbol or "%", from the number format
+    pub scale: Option<String>,          // display unit, e.g. thousands, millions; "relative" when values are fractions of the largest mark
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProcessedData {
     pub text_blocks: Vec<TextBlock>,
     pub visual_elements: Vec<VisualElement>,
+    pub charts: Vec<ChartElement>,
     pub structure: DocumentStructure,
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub market_claims: Vec<MarketClaim>,
+    #[serde(default)]
+    pub verifications: Vec<Verification>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, serde_json::Value>,  // results of custom pipeline stages, by stage name
+}
+
+/// A business metric stated in the document, e.g. "$2.4M ARR as of Q3 2025".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    pub kind: MetricKind,
+    pub value: f64,               // magnitudes applied: $2.4M is 2400000
+    pub unit: Option<String>,     // ISO currency code, "%", "months", "people" or "users"
+    pub period: Option<String>,   // ISO 8601 date or period the value is for, e.g. 2025-Q3
+    pub cadence: Option<String>,  // monthly or annual, for rates such as burn and churn
+    pub text: String,             // the sentence or table row it was read from
+    pub page: u32,
+    pub source: String,           // text or table
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    Arr,
+    Mrr,
+    Revenue,
+    Gmv,
+    GrossMargin,
+    Cac,
+    Ltv,
+    Churn,
+    Nrr,
+    Burn,
+    Runway,
+    Headcount,
+    Users,
+}
+
+/// A market-size claim, e.g. "a $50B global TAM growing at 12% CAGR".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketClaim {
+    pub kind: MarketSizeKind,
+    pub value: f64,                  // magnitudes applied: $50B is 50000000000
+    pub currency: Option<String>,    // ISO 4217 code
+    pub year: Option<u32>,           // the year the size is stated for
+    pub geography: Option<String>,   // e.g. Global, United States, Europe
+    pub cagr: Option<f64>,           // growth rate in percent a year
+    pub source: Option<String>,      // cited source or footnote text
+    pub text: String,                // the sentence it was read from
+    pub page: u32,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSizeKind {
+    Tam,
+    Sam,
+    Som,
+    Market,  // a market size not labelled TAM, SAM or SOM
+}
+
+/// A statement to check against reference data: a market size, a fact
+/// about a company, or a funding round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claim {
+    pub kind: ClaimKind,
+    pub subject: Option<String>,     // the market or company it is about, when known
+    pub attribute: String,           // e.g. tam, founded, headquarters, series_a
+    pub value: ClaimValue,
+    pub year: Option<u32>,
+    pub geography: Option<String>,
+    pub text: String,                // as stated in the document
+    pub page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimKind {
+    MarketSize,
+    CompanyFact,
+    Funding,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimValue {
+    Number { value: f64, unit: Option<String> },  // unit: ISO currency code, "%", ...
+    Text { value: String },
+}
+
+impl Claim {
+    pub fn new(kind: ClaimKind, attribute: &str, value: ClaimValue) -> Self {
+        Self {
+            kind,
+            subject: None,
+            attribute: attribute.to_string(),
+            value,
+            year: None,
+            geography: None,
+            text: String::new(),
+            page: None,
+        }
+    }
+
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    pub fn with_year(mut self, year: u32) -> Self {
+        self.year = Some(year);
+        self
+    }
+
+    pub fn with_geography(mut self, geography: &str) -> Self {
+        self.geography = Some(geography.to_string());
+        self
+    }
+}
+
+impl From<&MarketClaim> for Claim {
+    fn from(claim: &MarketClaim) -> Self {
+        let attribute = match claim.kind {
+            MarketSizeKind::Tam => "tam",
+            MarketSizeKind::Sam => "sam",
+            MarketSizeKind::Som => "som",
+            MarketSizeKind::Market => "market_size",
+        };
+        Self {
+            kind: ClaimKind::MarketSize,
+            subject: None,
+            attribute: attribute.to_string(),
+            value: ClaimValue::Number { value: claim.value, unit: claim.currency.clone() },
+            year: claim.year,
+            geography: claim.geography.clone(),
+            text: claim.text.clone(),
+            page: Some(claim.page),
+        }
+    }
+}
+
+/// A reference value a data provider holds for a claim's attribute.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evidence {
+    pub subject: Option<String>,
+    pub attribute: String,
+    pub value: ClaimValue,
+    pub year: Option<u32>,
+    pub geography: Option<String>,
+    pub source: String,  // the dataset or publication it comes from
+    pub provider: String,
+}
+
+/// The outcome of checking a claim.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Verification {
+    pub claim: Claim,
+    pub verdict: Verdict,
+    pub evidence: Vec<Evidence>,  // matching reference values, closest first
+    pub delta: Option<f64>,       // claimed / closest reference - 1: 0.25 is 25% above
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Supported,
+    Contradicted,
+    Unverifiable,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextBlock {
     pub content: String,
-    pub block_type: String,  // heading, paragraph, bullet, etc.
+    pub block_type: String,  // title, heading, bullet, paragraph, caption, header, footer, page_number
     pub confidence: f32,
+    pub page: u32,
+    pub bbox: Option<BoundingBox>,
+    #[serde(default)]
+    pub level: Option<u32>,  // heading level (1 = top) or bullet nesting (0 = outermost)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisualElement {
     pub element_type: String,  // image, chart, diagram
-    pub position: (u32, u32),
-    pub size: (u32, u32),
+    pub page: u32,
+    pub bbox: Option<BoundingBox>,
 }
 
+/// A chart found on a page, either embedded with its data or recovered
+/// from the drawing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartElement {
+    pub page: u32,
+    pub source: String,  // embedded, vector, raster
+    pub chart: ChartData,
+    pub confidence: f32, // mean of the per-value confidences
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DocumentStructure {
     pub sections: Vec<Section>,
     pub total_pages: u32,
-    pub language: Option<String>,
+    pub language: Option<String>,  // ISO 639-1 code of the main language, e.g. "es"
+    #[serde(default)]
+    pub page_languages: Vec<PageLanguage>,  // pages with enough text to tell
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLanguage {
+    pub page: u32,
+    pub language: String,
+    pub confidence: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Section {
-    pub title: String,
+    pub title: String,           // heading as written in the document
     pub content_blocks: usize,
+    #[serde(default)]
+    pub kind: Option<SectionKind>,  // canonical pitch section, when recognized
+    #[serde(default)]
+    pub confidence: f32,         // confidence in `kind`
+    #[serde(default)]
+    pub pages: Vec<u32>,
+}
+
+/// The standard sections of a pitch deck.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    Problem,
+    Solution,
+    Market,
+    Product,
+    BusinessModel,
+    Traction,
+    Competition,
+    Team,
+    Financials,
+    Ask,  // the raise and use of funds
+}
+
+impl SectionKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SectionKind::Problem => "Problem",
+            SectionKind::Solution => "Solution",
+            SectionKind::Market => "Market",
+            SectionKind::Product => "Product",
+            SectionKind::BusinessModel => "Business Model",
+            SectionKind::Traction => "Traction",
+            SectionKind::Competition => "Competition",
+            SectionKind::Team => "Team",
+            SectionKind::Financials => "Financials",
+            SectionKind::Ask => "Ask / Use of Funds",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentResult {
     pub extracted: ExtractedContent,
     pub processed: ProcessedData,
+    #[serde(default)]
+    pub risk_flags: Vec<RiskFlag>,  // most severe first
     pub processing_time_ms: u128,
 }
+
+/// A risk an investor should look into, raised by a rule over the
+/// processed document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskFlag {
+    pub rule: String,  // the rule that raised it, e.g. short_runway
+    pub severity: Severity,
+    pub category: RiskCategory,
+    pub explanation: String,
+    pub pages: Vec<u32>,  // where the evidence is; empty when something is missing
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskCategory {
+    Financial,
+    Market,
+    Team,
+    Legal,
+    Consistency,
+}
@@ -0,0 +1,346 @@
+// Normalization of written amounts: currencies, magnitudes and periods
+use crate::error::*;
+use crate::language::NumberFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Currency markers written before or after an amount, longest first so
+/// `US$` wins over `$`.
+const CURRENCIES: &[(&str, &str)] = &[
+    ("US$", "USD"), ("R$", "BRL"), ("A$", "AUD"), ("C$", "CAD"), ("S$", "SGD"),
+    ("HK$", "HKD"), ("MX$", "MXN"), ("NZ$", "NZD"), ("Rs.", "INR"), ("Rs", "INR"),
+    ("RMB", "CNY"), ("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY"),
+    ("₹", "INR"), ("₩", "KRW"), ("₦", "NGN"), ("₱", "PHP"), ("USD", "USD"),
+    ("EUR", "EUR"), ("GBP", "GBP"), ("JPY", "JPY"), ("INR", "INR"), ("BRL", "BRL"),
+    ("CHF", "CHF"), ("CAD", "CAD"), ("AUD", "AUD"), ("MXN", "MXN"), ("SGD", "SGD"),
+    ("HKD", "HKD"), ("NZD", "NZD"), ("CNY", "CNY"), ("KRW", "KRW"), ("SEK", "SEK"),
+    ("NOK", "NOK"), ("DKK", "DKK"), ("ZAR", "ZAR"), ("NGN", "NGN"), ("KES", "KES"),
+    ("IDR", "IDR"), ("PHP", "PHP"),
+];
+
+/// Magnitude suffixes, longest first. Besides the English ones this
+/// covers German `Mio.`/`Mrd.`/`Tsd.` and Indian lakh and crore.
+const MAGNITUDES: &[(&str, f64)] = &[
+    ("thousand", 1e3), ("million", 1e6), ("billion", 1e9), ("trillion", 1e12),
+    ("crore", 1e7), ("lakh", 1e5), ("Mio.", 1e6), ("Mrd.", 1e9), ("Tsd.", 1e3),
+    ("Mio", 1e6), ("Mrd", 1e9), ("Tsd", 1e3), ("lac", 1e5), ("MM", 1e6), ("mm", 1e6),
+    ("mn", 1e6), ("Mn", 1e6), ("bn", 1e9), ("Bn", 1e9), ("BN", 1e9), ("tn", 1e12),
+    ("Cr", 1e7), ("cr", 1e7), ("K", 1e3), ("k", 1e3), ("M", 1e6), ("m", 1e6),
+    ("B", 1e9), ("T", 1e12),
+];
+
+/// Words marking an amount as approximate, as in `~40%` or `c. $2M`.
+const APPROXIMATE: &[&str] = &[
+    "~", "\u{2248}", "c.", "ca.", "circa", "approx.", "approximately", "approx", "about",
+    "around", "roughly", "nearly", "almost",
+];
+
+/// Rate and growth markers written after an amount, lower-case.
+const PERIODS: &[(&str, Period)] = &[
+    ("yoy", Period::YearOverYear), ("y/y", Period::YearOverYear),
+    ("qoq", Period::QuarterOverQuarter), ("q/q", Period::QuarterOverQuarter),
+    ("mom", Period::MonthOverMonth), ("m/m", Period::MonthOverMonth),
+    ("wow", Period::WeekOverWeek), ("w/w", Period::WeekOverWeek),
+    ("per year", Period::Annual), ("a year", Period::Annual), ("/year", Period::Annual),
+    ("/yr", Period::Annual), ("p.a.", Period::Annual), ("annually", Period::Annual),
+    ("yearly", Period::Annual), ("per quarter", Period::Quarterly), ("/quarter", Period::Quarterly),
+    ("/qtr", Period::Quarterly), ("quarterly", Period::Quarterly), ("per month", Period::Monthly),
+    ("a month", Period::Monthly), ("/month", Period::Monthly), ("/mo", Period::Monthly),
+    ("p.m.", Period::Monthly), ("monthly", Period::Monthly), ("per week", Period::Weekly),
+    ("/week", Period::Weekly), ("/wk", Period::Weekly), ("weekly", Period::Weekly),
+    ("per day", Period::Daily), ("/day", Period::Daily), ("daily", Period::Daily),
+];
+
+/// Words allowed after a number as what it counts, as in `12k MAU`.
+const MAX_LABEL_WORDS: usize = 3;
+
+/// An amount as written in a document, normalized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: f64,              // scale applied: $1.2M is 1,200,000
+    pub unit: Option<Unit>,
+    pub scale: f64,              // the magnitude written: 1e6 for M, 1e7 for Cr, else 1
+    pub approximate: bool,       // ~40%, c. 200, 10k+
+    pub period: Option<Period>,  // what the amount is per or compared over
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Currency(String),  // ISO 4217 code, e.g. USD
+    Percent,           // 12.5% is 12.5
+    Multiple,          // 3x is 3
+    Other(String),     // what is counted, as written: MAU, users
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annual,
+    WeekOverWeek,
+    MonthOverMonth,
+    QuarterOverQuarter,
+    YearOverYear,
+}
+
+impl Quantity {
+    /// ISO 4217 code of the currency the amount is in.
+    pub fn currency(&self) -> Option<&str> {
+        match &self.unit {
+            Some(Unit::Currency(code)) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// The amount in currency `to`, when it is a currency amount and
+    /// `rates` covers both currencies.
+    pub fn in_currency(&self, rates: &FxRates, to: &str) -> Option<Quantity> {
+        let value = rates.convert(self.value, self.currency()?, to)?;
+        Some(Quantity {
+            value,
+            unit: Some(Unit::Currency(to.to_string())),
+            ..self.clone()
+        })
+    }
+}
+
+/// Parse an amount such as `$1.2M`, `1,2 Mio. €`, `₹50 Cr`, `12k MAU`,
+/// `3x YoY` or `~40%`. With a number format, separators are read the way
+/// that locale writes them, so `1.200` is 1200 in German; without one they
+/// are guessed from their positions.
+pub fn parse_quantity(text: &str, format: Option<NumberFormat>) -> Option<Quantity> {
+    let mut s = text.trim();
+    let mut approximate = false;
+    while let Some(rest) = APPROXIMATE.iter().find_map(|marker| strip_word_prefix(s, marker)) {
+        approximate = true;
+        s = rest;
+    }
+    let mut period = None;
+    if let Some((rest, p)) = PERIODS.iter().find_map(|(marker, p)| strip_word_suffix(s, marker).map(|r| (r, *p))) {
+        period = Some(p);
+        s = rest;
+    }
+
+    // Peel off what is counted, word by word from the end, until what is
+    // left reads as an amount.
+    let mut label: Vec<&str> = Vec::new();
+    loop {
+        let (head, plus) = match s.strip_suffix('+') {
+            Some(head) => (head.trim_end(), true),
+            None => (s, false),
+        };
+        if let Some((value, scale, unit)) = amount(head, format) {
+            let unit = unit.or_else(|| (!label.is_empty()).then(|| Unit::Other(label.join(" "))));
+            return Some(Quantity {
+                value,
+                unit,
+                scale,
+                approximate: approximate || plus,
+                period,
+            });
+        }
+        let (head, word) = s.rsplit_once(char::is_whitespace)?;
+        if label.len() == MAX_LABEL_WORDS || !word.chars().all(|c| c.is_alphabetic() || c == '-') {
+            return None;
+        }
+        label.insert(0, word);
+        s = head.trim_end();
+    }
+}
+
+/// Value, magnitude and unit of a signed amount with optional currency,
+/// percent or multiple marker.
+fn amount(text: &str, format: Option<NumberFormat>) -> Option<(f64, f64, Option<Unit>)> {
+    let mut s = text.trim();
+    s = s.strip_prefix('+').map_or(s, str::trim_start);
+    let mut negative = false;
+    if let Some(inner) = s.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        negative = true;
+        s = inner.trim();
+    }
+    if let Some(rest) = s.strip_prefix('-').or_else(|| s.strip_prefix('\u{2212}')) {
+        negative = !negative;
+        s = rest.trim_start();
+    }
+
+    let mut unit = None;
+    for (marker, code) in CURRENCIES {
+        if let Some(rest) = s.strip_prefix(marker) {
+            unit = Some(Unit::Currency(code.to_string()));
+            s = rest.trim_start();
+            break;
+        }
+        if let Some(rest) = s.strip_suffix(marker) {
+            unit = Some(Unit::Currency(code.to_string()));
+            s = rest.trim_end();
+            break;
+        }
+    }
+    // A sign may also follow the currency symbol: $-40.
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = !negative;
+        s = rest.trim_start();
+    }
+
+    if let Some(rest) = s.strip_suffix('%') {
+        unit = Some(Unit::Percent);
+        s = rest.trim_end();
+    } else if let Some(rest) = s.strip_suffix(['x', '\u{d7}']) {
+        unit = Some(Unit::Multiple);
+        s = rest.trim_end();
+    }
+
+    let (digits, scale) = MAGNITUDES
+        .iter()
+        .find_map(|(suffix, m)| s.strip_suffix(suffix).map(|d| (d.trim_end(), *m)))
+        .unwrap_or((s, 1.0));
+    let value = number(digits, format)? * scale;
+    Some((if negative { -value } else { value }, scale, unit))
+}
+
+/// Parse digits with grouping separators. When both `,` and `.` appear
+/// the last is the decimal mark. Otherwise a locale's decimal mark is one
+/// unless it repeats, and its grouping mark groups only by three or when
+/// it repeats; with no locale, a lone comma followed by three digits
+/// groups thousands and a lone period is a decimal point.
+pub(crate) fn number(digits: &str, format: Option<NumberFormat>) -> Option<f64> {
+    let digits: String = digits.chars().filter(|c| !matches!(c, ' ' | '\u{a0}' | '\u{202f}')).collect();
+    if !digits.starts_with(|c: char| c.is_ascii_digit())
+        || !digits.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '.'))
+    {
+        return None;
+    }
+    let decimal = match (digits.rfind(','), digits.rfind('.')) {
+        (Some(c), Some(d)) => Some(if c > d { ',' } else { '.' }),
+        (None, None) => None,
+        (comma, _) => {
+            let (mark, at) = match comma {
+                Some(c) => (',', c),
+                None => ('.', digits.rfind('.')?),
+            };
+            let repeated = digits.matches(mark).count() > 1;
+            let by_three = digits.len() - at - 1 == 3;
+            let grouped = match format {
+                Some(format) if mark == format.decimal => repeated,
+                Some(_) => repeated || by_three,
+                None if mark == ',' => repeated || by_three,
+                None => repeated,
+            };
+            (!grouped).then_some(mark)
+        }
+    };
+
+    let normalized: String = digits
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_ascii_digit() => Some(c),
+            c if Some(c) == decimal => Some('.'),
+            _ => None,
+        })
+        .collect();
+    normalized.parse().ok()
+}
+
+/// Multiplier named by a magnitude word on its own, e.g. `million`, `bn`,
+/// `Mio.` or `crore`. Case is ignored.
+pub fn magnitude(word: &str) -> Option<f64> {
+    match word.trim_end_matches('.').to_lowercase().as_str() {
+        "k" | "thousand" | "thousands" | "tsd" => Some(1e3),
+        "lakh" | "lakhs" | "lac" | "lacs" => Some(1e5),
+        "m" | "mm" | "mn" | "mio" | "million" | "millions" => Some(1e6),
+        "cr" | "crore" | "crores" => Some(1e7),
+        "b" | "bn" | "mrd" | "billion" | "billions" => Some(1e9),
+        "t" | "tn" | "trillion" | "trillions" => Some(1e12),
+        _ => None,
+    }
+}
+
+/// ISO 4217 code for a currency symbol or code written on its own, e.g.
+/// `€`, `US$` or `usd`.
+pub fn currency(marker: &str) -> Option<&'static str> {
+    CURRENCIES
+        .iter()
+        .find(|(m, _)| *m == marker || (m.len() == 3 && m.chars().all(|c| c.is_ascii_uppercase()) && m.eq_ignore_ascii_case(marker)))
+        .map(|(_, code)| *code)
+}
+
+fn strip_word_prefix<'a>(s: &'a str, marker: &str) -> Option<&'a str> {
+    let head = s.get(..marker.len())?;
+    if !head.eq_ignore_ascii_case(marker) {
+        return None;
+    }
+    let rest = &s[marker.len()..];
+    // Word markers must stand apart from the number: `about 40`, not `aboutness`.
+    if marker.ends_with(char::is_alphabetic) && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim_start())
+}
+
+fn strip_word_suffix<'a>(s: &'a str, marker: &str) -> Option<&'a str> {
+    let at = s.len().checked_sub(marker.len())?;
+    if !s.is_char_boundary(at) || !s[at..].eq_ignore_ascii_case(marker) {
+        return None;
+    }
+    let rest = &s[..at];
+    if !marker.starts_with('/') && !rest.ends_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim_end())
+}
+
+/// Exchange rates for converting amounts between currencies: the value of
+/// one unit of each currency in the `base` currency.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FxRates {
+    pub base: String,
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl FxRates {
+    pub fn new(base: &str) -> Self {
+        Self {
+            base: base.to_uppercase(),
+            rates: BTreeMap::new(),
+        }
+    }
+
+    /// Add the value of one unit of `currency` in the base currency.
+    pub fn with_rate(mut self, currency: &str, rate: f64) -> Self {
+        self.rates.insert(currency.to_uppercase(), rate);
+        self
+    }
+
+    /// Read rates from a JSON file such as
+    /// `{"base": "USD", "rates": {"EUR": 1.08, "INR": 0.012}}`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let rates: FxRates = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if let Some((code, rate)) = rates.rates.iter().find(|(_, r)| !(**r > 0.0 && r.is_finite())) {
+            return Err(TraceDeckError::ParseError(format!("invalid FX rate for {}: {}", code, rate)));
+        }
+        Ok(Self::new(&rates.base).with_rates(rates.rates))
+    }
+
+    fn with_rates(self, rates: BTreeMap<String, f64>) -> Self {
+        rates.into_iter().fold(self, |fx, (code, rate)| fx.with_rate(&code, rate))
+    }
+
+    /// Value of one unit of `currency` in the base currency.
+    pub fn rate(&self, currency: &str) -> Option<f64> {
+        let currency = currency.to_uppercase();
+        if currency == self.base {
+            return Some(1.0);
+        }
+        self.rates.get(&currency).copied()
+    }
+
+    /// Convert `value` from currency `from` to currency `to`.
+    pub fn convert(&self, value: f64, from: &str, to: &str) -> Option<f64> {
+        Some(value * self.rate(from)? / self.rate(to)?)
+    }
+}
@@ -0,0 +1,136 @@
+// Optical character recognition backends
+use crate::{error::*, models::*};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A word recognized in an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrWord {
+    pub text: String,
+    pub bbox: BoundingBox,  // pixels, top-left origin
+    pub confidence: f32,    // 0.0 - 1.0
+}
+
+/// Recognizes text in encoded images (PNG, JPEG, ...).
+pub trait OcrEngine: Send + Sync {
+    fn recognize(&self, image: &[u8]) -> Result<Vec<OcrWord>>;
+}
+
+/// Shells out to a locally installed Tesseract binary, passing the image on
+/// stdin and reading word boxes from its TSV output.
+#[derive(Debug, Clone)]
+pub struct TesseractEngine {
+    binary: String,
+    language: String,
+}
+
+impl Default for TesseractEngine {
+    fn default() -> Self {
+        Self {
+            binary: "tesseract".to_string(),
+            language: "eng".to_string(),
+        }
+    }
+}
+
+impl TesseractEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path or name of the executable, `tesseract` by default.
+    pub fn with_binary(mut self, binary: &str) -> Self {
+        self.binary = binary.to_string();
+        self
+    }
+
+    /// Tesseract language codes, e.g. `eng` or `eng+deu`.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    /// Whether the binary can be run.
+    pub fn is_available(&self) -> bool {
+        Command::new(&self.binary)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+}
+
+impl OcrEngine for TesseractEngine {
+    fn recognize(&self, image: &[u8]) -> Result<Vec<OcrWord>> {
+        let mut child = Command::new(&self.binary)
+            .args(["stdin", "stdout", "-l", &self.language, "tsv"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| TraceDeckError::OcrError(format!("cannot run {}: {}", self.binary, e)))?;
+
+        // Feed stdin from another thread so a full stdout pipe cannot deadlock.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = image.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+        let output = child.wait_with_output()?;
+        let written = writer
+            .join()
+            .map_err(|_| TraceDeckError::OcrError("stdin writer panicked".into()))?;
+
+        // A rejected image ends Tesseract early, breaking the pipe; its
+        // stderr says why.
+        if !output.status.success() {
+            return Err(TraceDeckError::OcrError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        written?;
+        Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Word rows (level 5) of Tesseract's TSV output.
+fn parse_tsv(tsv: &str) -> Vec<OcrWord> {
+    tsv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.splitn(12, '\t').collect();
+            if cols.len() < 12 || cols[0] != "5" {
+                return None;
+            }
+            let num = |i: usize| cols[i].trim().parse::<f32>().ok();
+            let text = cols[11].trim();
+            let confidence = num(10)?;
+            if text.is_empty() || confidence < 0.0 {
+                return None;
+            }
+            Some(OcrWord {
+                text: text.to_string(),
+                bbox: BoundingBox::new(num(6)?, num(7)?, num(8)?, num(9)?),
+                confidence: confidence / 100.0,
+            })
+        })
+        .collect()
+}
+
+/// Returns a fixed set of words for every image, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct MockEngine {
+    words: Vec<OcrWord>,
+}
+
+impl MockEngine {
+    pub fn new(words: Vec<OcrWord>) -> Self {
+        Self { words }
+    }
+}
+
+impl OcrEngine for MockEngine {
+    fn recognize(&self, _image: &[u8]) -> Result<Vec<OcrWord>> {
+        Ok(self.words.clone())
+    }
+}
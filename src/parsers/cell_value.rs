@@ -0,0 +1,139 @@
+// Typed values of table cells
+use crate::language::NumberFormat;
+use crate::models::*;
+use crate::normalize::{self, Unit};
+
+/// Names and abbreviations of each month, as whole lowercase words.
+const MONTHS: &[&[&str]] = &[
+    &["jan", "january"], &["feb", "february"], &["mar", "march"], &["apr", "april"],
+    &["may"], &["jun", "june"], &["jul", "july"], &["aug", "august"],
+    &["sep", "sept", "september"], &["oct", "october"], &["nov", "november"], &["dec", "december"],
+];
+
+/// Read the value a cell states: an amount with its currency, a
+/// percentage, a plain number, a date or period, or otherwise text.
+/// Magnitude suffixes are applied, so `$1.2M` is 1,200,000 USD, and
+/// separators are read in `format` when the document's locale is known.
+pub fn parse(text: &str, format: Option<NumberFormat>) -> CellValue {
+    let text = text.trim();
+    if text.is_empty() || matches!(text, "-" | "\u{2013}" | "\u{2014}" | "n/a" | "N/A") {
+        return CellValue::Empty;
+    }
+    if let Some(value) = date(text) {
+        return CellValue::Date { value };
+    }
+    figure(text, format).unwrap_or(CellValue::Text)
+}
+
+/// A figure on its own. Figures followed by words, like `2024 Plan`,
+/// are text.
+fn figure(text: &str, format: Option<NumberFormat>) -> Option<CellValue> {
+    let quantity = normalize::parse_quantity(text, format)?;
+    let value = quantity.value;
+    Some(match quantity.unit {
+        Some(Unit::Percent) => CellValue::Percentage { value },
+        Some(Unit::Currency(currency)) => CellValue::Currency { value, currency },
+        Some(Unit::Multiple) | None => CellValue::Number { value },
+        Some(Unit::Other(_)) => return None,
+    })
+}
+
+/// ISO 8601 form of a year, quarter, month or day: `2025`, `FY25`,
+/// `Q3 2025`, `Mar 2024`, `2024-03-14`, `03/2024` or `14/03/2024`.
+/// Slashed days read month first unless the first field exceeds 12.
+fn date(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    let s = lower.trim();
+
+    let year = |y: &str| -> Option<u32> {
+        let y = y.trim_start_matches(['\'', '\u{2019}']);
+        let n: u32 = y.parse().ok().filter(|_| y.chars().all(|c| c.is_ascii_digit()))?;
+        match y.len() {
+            4 if (1900..=2100).contains(&n) => Some(n),
+            2 => Some(2000 + n),
+            _ => None,
+        }
+    };
+    let four_digit_year = |y: &str| year(y).filter(|_| y.len() == 4);
+
+    // 2025, 2025E, FY2025, FY25
+    let bare = s.trim_end_matches(['e', 'f', 'a', 'p']);
+    if let Some(y) = four_digit_year(bare) {
+        return Some(y.to_string());
+    }
+    if let Some(y) = s.strip_prefix("fy").and_then(|y| year(y.trim())) {
+        return Some(y.to_string());
+    }
+
+    let words: Vec<&str> = s
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '\'' || c == '\u{2019}')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Q3 2025, Q3'25, 2025 Q3, 3Q25, Q3 FY25
+    let quarter = |w: &str| -> Option<u32> {
+        let q = w.strip_prefix('q').or_else(|| w.strip_suffix('q'))?;
+        q.parse().ok().filter(|q| (1..=4).contains(q))
+    };
+    match words[..] {
+        [a, b] => {
+            if let (Some(q), Some(y)) = (quarter(a), year(b.trim_start_matches("fy"))) {
+                return Some(format!("{}-Q{}", y, q));
+            }
+            if let (Some(y), Some(q)) = (four_digit_year(a), quarter(b)) {
+                return Some(format!("{}-Q{}", y, q));
+            }
+            // Mar 2024, Mar. 2024, March 2024, Mar-24; not Marketing 2024.
+            let month = MONTHS.iter().position(|names| names.contains(&a.trim_end_matches('.')));
+            if let (Some(m), Some(y)) = (month, year(b)) {
+                return Some(format!("{}-{:02}", y, m + 1));
+            }
+        }
+        [w] if w.len() == 4 || w.len() == 5 => {
+            // 3Q25
+            if let Some((q, y)) = w.split_once('q') {
+                if let (Ok(q), Some(y)) = (q.parse::<u32>(), year(y)) {
+                    if (1..=4).contains(&q) {
+                        return Some(format!("{}-Q{}", y, q));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // 2024-03-14, 2024-03
+    let parts: Vec<&str> = s.split('-').collect();
+    if parts.len() >= 2 && parts.len() <= 3 && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit())) {
+        let y = four_digit_year(parts[0])?;
+        let m: u32 = parts[1].parse().ok().filter(|m| (1..=12).contains(m))?;
+        return match parts.get(2) {
+            Some(d) => {
+                let d: u32 = d.parse().ok().filter(|d| (1..=31).contains(d))?;
+                Some(format!("{}-{:02}-{:02}", y, m, d))
+            }
+            None => Some(format!("{}-{:02}", y, m)),
+        };
+    }
+
+    // 03/2024, 14/03/2024, 3/14/2024
+    let parts: Vec<&str> = s.split('/').collect();
+    if !parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    match parts[..] {
+        [m, y] => {
+            let m: u32 = m.parse().ok().filter(|m| (1..=12).contains(m))?;
+            Some(format!("{}-{:02}", four_digit_year(y)?, m))
+        }
+        [a, b, y] => {
+            let (a, b): (u32, u32) = (a.parse().ok()?, b.parse().ok()?);
+            let (m, d) = if a > 12 { (b, a) } else { (a, b) };
+            if !(1..=12).contains(&m) || !(1..=31).contains(&d) {
+                return None;
+            }
+            Some(format!("{}-{:02}-{:02}", four_digit_year(y)?, m, d))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,203 @@
+// Embedded Office chart parts (DrawingML chartN.xml)
+use super::ooxml::XmlNode;
+use crate::models::*;
+
+/// Read the cached series data of a chart part. Office keeps a copy of
+/// the numbers next to the embedded workbook, so no spreadsheet parsing is
+/// needed.
+pub fn parse_chart(chart_space: &XmlNode, bbox: Option<BoundingBox>) -> Option<ChartData> {
+    let chart = chart_space.child("chart")?;
+    let plot_area = chart.child("plotArea")?;
+
+    let mut chart_types: Vec<String> = Vec::new();
+    let mut categories: Vec<String> = Vec::new();
+    let mut series: Vec<ChartSeries> = Vec::new();
+    let mut source_format: Option<String> = None;
+
+    for plot in plot_area.elements().filter(|p| p.name.ends_with("Chart")) {
+        let kind = chart_type(plot);
+        if !chart_types.contains(&kind) {
+            chart_types.push(kind);
+        }
+
+        for ser in plot.children("ser") {
+            let name = ser
+                .child("tx")
+                .and_then(|tx| {
+                    cached_strings(tx)
+                        .into_iter()
+                        .next()
+                        .or_else(|| tx.child("v").map(|v| v.text()))
+                })
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| format!("Series {}", series.len() + 1));
+
+            if categories.is_empty() {
+                if let Some(cats) = ser.child("cat").or_else(|| ser.child("xVal")) {
+                    categories = cached_strings(cats);
+                }
+            }
+
+            let values = ser.child("val").or_else(|| ser.child("yVal"));
+            if source_format.is_none() {
+                source_format = values
+                    .and_then(|v| v.descendants("formatCode").into_iter().next())
+                    .map(|f| f.text())
+                    .filter(|f| f != "General");
+            }
+
+            series.push(ChartSeries {
+                name,
+                values: values.map(cached_numbers).unwrap_or_default(),
+                confidence: vec![],
+            });
+        }
+    }
+
+    if series.is_empty() {
+        return None;
+    }
+
+    // Pad so every series lines up with the category labels.
+    let len = series.iter().map(|s| s.values.len()).max().unwrap_or(0).max(categories.len());
+    categories.resize(len, String::new());
+    for s in &mut series {
+        s.values.resize(len, None);
+        s.confidence = vec![1.0; len];
+    }
+
+    let chart_type = match chart_types.len() {
+        1 => chart_types.remove(0),
+        _ => "combo".to_string(),
+    };
+
+    Some(ChartData {
+        chart_type,
+        title: chart.child("title").and_then(title_text),
+        categories,
+        series,
+        value_axis: value_axis(plot_area, source_format),
+        bbox,
+    })
+}
+
+/// `c:barChart` → `bar` (or `column` for vertical bars), `c:line3DChart` → `line`.
+fn chart_type(plot: &XmlNode) -> String {
+    let kind = plot.name.trim_end_matches("Chart").trim_end_matches("3D");
+    match kind {
+        "bar" if plot.child("barDir").and_then(|d| d.attr("val")) == Some("col") => "column".to_string(),
+        "ofPie" => "pie".to_string(),
+        _ => kind.to_string(),
+    }
+}
+
+/// The axis carrying the values. Scatter charts have two value axes; the
+/// vertical one is the measure.
+fn value_axis(plot_area: &XmlNode, source_format: Option<String>) -> Option<ChartAxis> {
+    let axes: Vec<&XmlNode> = plot_area.children("valAx").collect();
+    let axis = axes
+        .iter()
+        .find(|a| matches!(a.child("axPos").and_then(|p| p.attr("val")), Some("l" | "r")))
+        .or_else(|| axes.first())?;
+
+    // A source-linked format defers to the number format of the cells.
+    let number_format = axis
+        .child("numFmt")
+        .filter(|f| f.attr("sourceLinked") != Some("1"))
+        .and_then(|f| f.attr("formatCode"))
+        .map(str::to_string)
+        .filter(|f| f != "General")
+        .or(source_format);
+
+    let scale = axis.child("dispUnits").and_then(|d| {
+        d.child("builtInUnit")
+            .or_else(|| d.child("custUnit"))
+            .and_then(|u| u.attr("val"))
+            .map(str::to_string)
+    });
+
+    Some(ChartAxis {
+        title: axis.child("title").and_then(title_text),
+        unit: number_format.as_deref().and_then(format_unit),
+        number_format,
+        scale,
+    })
+}
+
+/// Text of a chart or axis title, either rich text or a cell reference.
+fn title_text(title: &XmlNode) -> Option<String> {
+    let tx = title.child("tx")?;
+    let text = match tx.child("rich") {
+        Some(rich) => rich
+            .descendants("p")
+            .iter()
+            .map(|p| p.text().trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => cached_strings(tx).join(" "),
+    };
+    Some(text).filter(|t| !t.trim().is_empty())
+}
+
+/// Unit implied by a number format: a currency symbol (bare, quoted as in
+/// `"$"#,##0`, or in a locale block like `[$€-407]`) or `%`.
+fn format_unit(format: &str) -> Option<String> {
+    // Bracketed blocks are locales, colors and conditions; only a `[$…-…]`
+    // block names a symbol, and its `$` is syntax, not a dollar sign.
+    let mut bare = String::new();
+    for (i, part) in format.split('[').enumerate() {
+        let (block, rest) = if i == 0 { ("", part) } else { part.split_once(']').unwrap_or((part, "")) };
+        if let Some(symbol) = block.strip_prefix('$').and_then(|b| b.split('-').next()).filter(|s| !s.is_empty()) {
+            return Some(symbol.to_string());
+        }
+        bare.push_str(rest);
+    }
+    if let Some(symbol) = bare.chars().find(|c| "$€£¥₹".contains(*c)) {
+        return Some(symbol.to_string());
+    }
+    bare.contains('%').then(|| "%".to_string())
+}
+
+/// Point values from a `strCache`/`numCache`/`multiLvlStrCache`, placed by
+/// their `idx` so gaps stay aligned across series.
+fn cached_points(node: &XmlNode) -> Vec<Option<String>> {
+    let Some(cache) = ["strCache", "numCache", "multiLvlStrCache", "strLit", "numLit"]
+        .iter()
+        .find_map(|name| node.descendants(name).into_iter().next())
+    else {
+        return vec![];
+    };
+
+    let count = cache
+        .child("ptCount")
+        .and_then(|c| c.attr("val")?.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut points = vec![None; count];
+
+    // Multi-level category caches repeat `pt` per level; the first level is
+    // the innermost label, which is the one shown on the axis.
+    let pts = match cache.child("lvl") {
+        Some(level) => level.children("pt").collect::<Vec<_>>(),
+        None => cache.children("pt").collect(),
+    };
+    for pt in pts {
+        let idx = pt.attr("idx").and_then(|i| i.parse::<usize>().ok()).unwrap_or(points.len());
+        if idx >= points.len() {
+            points.resize(idx + 1, None);
+        }
+        points[idx] = pt.child("v").map(|v| v.text());
+    }
+    points
+}
+
+fn cached_strings(node: &XmlNode) -> Vec<String> {
+    cached_points(node).into_iter().map(Option::unwrap_or_default).collect()
+}
+
+fn cached_numbers(node: &XmlNode) -> Vec<Option<f64>> {
+    cached_points(node)
+        .into_iter()
+        .map(|p| p.and_then(|v| v.trim().parse().ok()))
+        .collect()
+}
@@ -0,0 +1,306 @@
+// Word document parsing on top of docx-rs
+use super::chart;
+use super::ooxml::{CoreProperties, Package, XmlNode};
+use super::table::{self, Cell};
+use crate::{error::*, images::content_hash, models::*};
+use docx_rs::{
+    DocumentChild, InsertChild, MoveToChild, Paragraph, ParagraphChild, Run, RunChild,
+    StructuredDataTagChild, Table, TableCellContent, TableChild, TableRowChild,
+};
+use std::io::Cursor;
+
+/// Body content of a `.docx` file.
+pub struct DocxDocument {
+    pub paragraphs: Vec<StyledParagraph>,
+    pub tables: Vec<TableData>,
+    pub images: Vec<ImageData>,
+    pub charts: Vec<ChartData>,
+    pub properties: CoreProperties,
+    /// Whether the last block read was a table, or a caption paragraph.
+    table_last: bool,
+    caption_before: bool,
+}
+
+impl DocxDocument {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let docx = docx_rs::read_docx(bytes).map_err(|e| TraceDeckError::DocxError(e.to_string()))?;
+        let mut package = Package::new(bytes)?;
+        let properties = package.core_properties();
+        let body = main_document(&mut package);
+        let charts = body
+            .as_ref()
+            .map(|(path, xml)| charts(&mut package, path, xml))
+            .unwrap_or_default();
+        // docx-rs keeps cell merges in private fields, so they are read
+        // from the XML, table by table in the same order.
+        let mut layouts = body
+            .as_ref()
+            .map(|(_, xml)| table_layouts(xml))
+            .unwrap_or_default()
+            .into_iter();
+
+        let mut document = Self {
+            paragraphs: vec![],
+            tables: vec![],
+            images: vec![],
+            charts,
+            properties,
+            table_last: false,
+            caption_before: false,
+        };
+
+        for child in &docx.document.children {
+            match child {
+                DocumentChild::Paragraph(p) => document.add_paragraph(p),
+                DocumentChild::Table(t) => document.add_table(t, &layouts.next().unwrap_or_default()),
+                DocumentChild::StructuredDataTag(sdt) => {
+                    for child in &sdt.children {
+                        match child {
+                            StructuredDataTagChild::Paragraph(p) => document.add_paragraph(p),
+                            StructuredDataTagChild::Table(t) => {
+                                document.add_table(t, &layouts.next().unwrap_or_default())
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        document.images = docx
+            .images
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, path, image, _))| {
+                let (width, height) = image::ImageReader::new(Cursor::new(&image.0))
+                    .with_guessed_format()
+                    .ok()
+                    .and_then(|r| r.into_dimensions().ok())
+                    .unwrap_or((0, 0));
+                ImageData {
+                    id: format!("img_{}", idx),
+                    format: path.rsplit('.').next().unwrap_or_default().to_lowercase(),
+                    width,
+                    height,
+                    data: image.0.clone(),
+                    sha256: content_hash(&image.0),
+                    path: None,
+                    bbox: None,
+                }
+            })
+            .collect();
+
+        Ok(document)
+    }
+
+    pub fn text(&self) -> String {
+        self.paragraphs
+            .iter()
+            .map(|p| p.text.as_str())
+            .filter(|t| !t.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn add_paragraph(&mut self, paragraph: &Paragraph) {
+        let text = paragraph_text(paragraph);
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let property = &paragraph.property;
+        let style = property.style.as_ref().map(|s| s.val.clone());
+        // A caption belongs to the table it directly precedes or follows.
+        let caption = is_caption(style.as_deref());
+        if caption && self.table_last {
+            if let Some(table) = self.tables.last_mut().filter(|t| t.caption.is_none()) {
+                table.caption = Some(text.trim().to_string());
+            }
+        }
+        self.caption_before = caption;
+        self.table_last = false;
+
+        self.paragraphs.push(StyledParagraph {
+            text,
+            style,
+            list_level: property
+                .numbering_property
+                .as_ref()
+                .and_then(|n| n.level.as_ref())
+                .map(|l| l.val as u32),
+        });
+    }
+
+    /// Add a table, with `layout` giving the merges of each row's cells.
+    /// Horizontally merged cells carry a `gridSpan`; vertical merges start
+    /// at a `restart` cell and continue through the cells below, which are
+    /// folded into the first.
+    fn add_table(&mut self, table: &Table, layout: &[Vec<CellLayout>]) {
+        let mut cells: Vec<Cell> = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+        for (row, TableChild::TableRow(tr)) in table.rows.iter().enumerate() {
+            rows = row + 1;
+            let mut col = 0;
+            for (idx, TableRowChild::TableCell(tc)) in tr.cells.iter().enumerate() {
+                let cell = layout.get(row).and_then(|r| r.get(idx)).copied().unwrap_or_default();
+                let col_span = cell.col_span;
+                if cell.continued {
+                    if let Some(above) = cells.iter_mut().rev().find(|c| c.col == col && c.row < row) {
+                        above.row_span = row + 1 - above.row;
+                    }
+                } else {
+                    let text = tc
+                        .children
+                        .iter()
+                        .filter_map(|c| match c {
+                            TableCellContent::Paragraph(p) => Some(paragraph_text(p)),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    cells.push(Cell { row, col, row_span: 1, col_span, text });
+                }
+                col += col_span;
+            }
+            cols = cols.max(col);
+        }
+
+        if rows == 0 {
+            return;
+        }
+        let caption = self
+            .paragraphs
+            .last()
+            .filter(|_| self.caption_before)
+            .map(|p| p.text.trim().to_string());
+        self.tables.push(TableData {
+            caption,
+            ..table::table_data(rows, cols, cells)
+        });
+        self.table_last = true;
+        self.caption_before = false;
+    }
+}
+
+fn is_caption(style: Option<&str>) -> bool {
+    style.is_some_and(|s| s.eq_ignore_ascii_case("caption"))
+}
+
+/// Merge properties of a table cell, from its `w:tcPr`.
+#[derive(Debug, Clone, Copy)]
+struct CellLayout {
+    col_span: usize,
+    /// Continues a vertical merge from the cell above.
+    continued: bool,
+}
+
+impl Default for CellLayout {
+    fn default() -> Self {
+        Self { col_span: 1, continued: false }
+    }
+}
+
+/// Path and XML of the main document part, usually `word/document.xml`.
+fn main_document(package: &mut Package<'_>) -> Option<(String, XmlNode)> {
+    let path = package
+        .relationships("")
+        .into_values()
+        .find(|r| r.kind == "officeDocument")
+        .map(|r| r.target)
+        .unwrap_or_else(|| "word/document.xml".to_string());
+    let xml = package.xml(&path)?;
+    Some((path, xml))
+}
+
+/// Cell layouts of the tables docx-rs reads, in the same order: tables in
+/// the body and in its content controls, rows by cells.
+fn table_layouts(document: &XmlNode) -> Vec<Vec<Vec<CellLayout>>> {
+    let Some(body) = document.child("body") else {
+        return vec![];
+    };
+    body.elements()
+        .flat_map(|e| match e.name.as_str() {
+            "tbl" => vec![e],
+            "sdt" => e.children("sdtContent").flat_map(|c| c.children("tbl")).collect(),
+            _ => vec![],
+        })
+        .map(|tbl| {
+            tbl.children("tr")
+                .map(|tr| {
+                    tr.children("tc")
+                        .map(|tc| {
+                            let property = tc.child("tcPr");
+                            let col_span = property
+                                .and_then(|p| p.child("gridSpan")?.attr("w:val")?.parse().ok())
+                                .unwrap_or(1usize)
+                                .max(1);
+                            // A bare `w:vMerge` continues; only `restart` starts one.
+                            let continued = property
+                                .and_then(|p| p.child("vMerge"))
+                                .is_some_and(|m| m.attr("w:val").unwrap_or("continue") == "continue");
+                            CellLayout { col_span, continued }
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Charts referenced from the document body, in document order. docx-rs
+/// does not model chart parts, so they are read from the package directly.
+fn charts(package: &mut Package<'_>, document_path: &str, document: &XmlNode) -> Vec<ChartData> {
+    let rels = package.relationships(document_path);
+
+    document
+        .descendants("chart")
+        .into_iter()
+        .filter_map(|c| rels.get(c.attr("r:id")?))
+        .filter_map(|r| package.xml(&r.target))
+        .filter_map(|c| chart::parse_chart(&c, None))
+        .collect()
+}
+
+fn paragraph_text(paragraph: &Paragraph) -> String {
+    let mut out = String::new();
+    push_children(&paragraph.children, &mut out);
+    out
+}
+
+fn push_children(children: &[ParagraphChild], out: &mut String) {
+    for child in children {
+        match child {
+            ParagraphChild::Run(run) => push_run(run, out),
+            ParagraphChild::Insert(insert) => {
+                for c in &insert.children {
+                    if let InsertChild::Run(run) = c {
+                        push_run(run, out);
+                    }
+                }
+            }
+            ParagraphChild::MoveTo(move_to) => {
+                for c in &move_to.children {
+                    if let MoveToChild::Run(run) = c {
+                        push_run(run, out);
+                    }
+                }
+            }
+            ParagraphChild::Hyperlink(link) => push_children(&link.children, out),
+            // Deleted and moved-from text is revision history, not content.
+            _ => {}
+        }
+    }
+}
+
+fn push_run(run: &Run, out: &mut String) {
+    for child in &run.children {
+        match child {
+            RunChild::Text(t) => out.push_str(&t.text),
+            RunChild::Tab(_) | RunChild::PTab(_) => out.push('\t'),
+            RunChild::Break(_) | RunChild::CarriageReturn(_) => out.push('\n'),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// Reading order reconstruction for positioned text
+use crate::models::*;
+
+/// Gutter width, in multiples of the median font size, that separates columns.
+const COLUMN_GAP_EM: f32 = 1.0;
+
+/// Order spans the way a reader would take them in, using recursive XY-cut:
+/// split on the widest column gutter first, otherwise on the widest
+/// horizontal gap, and fall back to top-to-bottom, left-to-right.
+pub fn reading_order(mut spans: Vec<TextSpan>) -> Vec<TextSpan> {
+    if spans.len() < 2 {
+        return spans;
+    }
+
+    let em = median_font_size(&spans);
+    let mut indices: Vec<usize> = (0..spans.len()).collect();
+    let mut order = Vec::with_capacity(spans.len());
+    xy_cut(&spans, &mut indices, em, &mut order);
+
+    let mut slots: Vec<Option<TextSpan>> = spans.drain(..).map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+/// Join ordered spans into text, one visual line per output line.
+pub fn spans_to_text(spans: &[TextSpan]) -> String {
+    let mut out = String::new();
+    let mut prev: Option<&TextSpan> = None;
+
+    for span in spans {
+        if let Some(p) = prev {
+            if same_line(p, span) {
+                out.push(' ');
+            } else {
+                out.push('\n');
+            }
+        }
+        out.push_str(span.text.trim());
+        prev = Some(span);
+    }
+
+    out
+}
+
+/// Join recognized words into line spans. A word continues a line when it
+/// overlaps it vertically and starts within a line height of its end.
+pub fn words_to_lines(mut words: Vec<TextSpan>) -> Vec<TextSpan> {
+    words.sort_by(|a, b| a.bbox.x.total_cmp(&b.bbox.x));
+
+    let mut lines: Vec<(TextSpan, usize)> = Vec::new();
+    for word in words {
+        let line = lines.iter_mut().find(|(line, _)| {
+            let height = line.bbox.height.min(word.bbox.height);
+            let gap = word.bbox.x - line.bbox.right();
+            same_line(line, &word) && gap > -height * 0.5 && gap < height * 1.5
+        });
+        match line {
+            Some((line, count)) => {
+                line.text.push(' ');
+                line.text.push_str(&word.text);
+                line.bbox = line.bbox.union(&word.bbox);
+                line.font_size = line.font_size.max(word.font_size);
+                line.confidence = match (line.confidence, word.confidence) {
+                    (Some(a), Some(b)) => Some((a * *count as f32 + b) / (*count + 1) as f32),
+                    (a, b) => a.or(b),
+                };
+                *count += 1;
+            }
+            None => lines.push((word, 1)),
+        }
+    }
+
+    lines.into_iter().map(|(line, _)| line).collect()
+}
+
+fn same_line(a: &TextSpan, b: &TextSpan) -> bool {
+    let overlap = a.bbox.bottom().min(b.bbox.bottom()) - a.bbox.y.max(b.bbox.y);
+    overlap > a.bbox.height.min(b.bbox.height) * 0.5 && b.bbox.x >= a.bbox.x
+}
+
+fn median_font_size(spans: &[TextSpan]) -> f32 {
+    let mut sizes: Vec<f32> = spans.iter().map(|s| s.font_size).collect();
+    sizes.sort_by(f32::total_cmp);
+    sizes[sizes.len() / 2].max(1.0)
+}
+
+fn xy_cut(spans: &[TextSpan], indices: &mut [usize], em: f32, order: &mut Vec<usize>) {
+    if indices.len() < 2 {
+        order.extend_from_slice(indices);
+        return;
+    }
+
+    let vertical = widest_gap(spans, indices, |b| (b.x, b.right()));
+    if let Some((at, gap)) = vertical {
+        if gap >= em * COLUMN_GAP_EM {
+            let split = partition(spans, indices, |b| b.x < at);
+            let (left, right) = indices.split_at_mut(split);
+            xy_cut(spans, left, em, order);
+            xy_cut(spans, right, em, order);
+            return;
+        }
+    }
+
+    if let Some((at, _)) = widest_gap(spans, indices, |b| (b.y, b.bottom())) {
+        let split = partition(spans, indices, |b| b.y < at);
+        let (top, bottom) = indices.split_at_mut(split);
+        xy_cut(spans, top, em, order);
+        xy_cut(spans, bottom, em, order);
+        return;
+    }
+
+    // Overlapping lines: plain top-to-bottom, left-to-right.
+    indices.sort_by(|&a, &b| {
+        let (a, b) = (&spans[a].bbox, &spans[b].bbox);
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+    order.extend_from_slice(indices);
+}
+
+/// Find the widest empty band along one axis, returning its midpoint and width.
+fn widest_gap(
+    spans: &[TextSpan],
+    indices: &[usize],
+    extent: impl Fn(&BoundingBox) -> (f32, f32),
+) -> Option<(f32, f32)> {
+    let mut ranges: Vec<(f32, f32)> = indices.iter().map(|&i| extent(&spans[i].bbox)).collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut best: Option<(f32, f32)> = None;
+    let mut reach = ranges[0].1;
+    for &(start, end) in &ranges[1..] {
+        let gap = start - reach;
+        if gap > 0.0 && best.is_none_or(|(_, w)| gap > w) {
+            best = Some((reach + gap / 2.0, gap));
+        }
+        reach = reach.max(end);
+    }
+    best
+}
+
+/// Stable partition of `indices` so spans matching `first` come first.
+fn partition(
+    spans: &[TextSpan],
+    indices: &mut [usize],
+    first: impl Fn(&BoundingBox) -> bool,
+) -> usize {
+    let (mut a, b): (Vec<usize>, Vec<usize>) =
+        indices.iter().partition(|&&i| first(&spans[i].bbox));
+    let split = a.len();
+    a.extend(b);
+    indices.copy_from_slice(&a);
+    split
+}
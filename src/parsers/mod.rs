@@ -1,75 +1,255 @@
-use crate::{models::*, error::*};
+use crate::{models::*, error::*, images::content_hash, ocr::*};
+use std::io::Cursor;
 use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use image::{ImageReader, GenericImageView};
 
+pub(crate) mod cell_value;
+mod chart;
+mod docx;
+mod layout;
+mod ooxml;
+mod pdf;
+mod pptx;
+mod sniff;
+mod table;
+mod xmp;
+
+/// File types `DocumentParser` can read.
+const SUPPORTED_FORMATS: &[&str] = &["pdf", "docx", "pptx", "png", "jpg", "jpeg", "webp", "gif"];
+
 pub struct DocumentParser {
-    file_path: String,
+    data: Vec<u8>,
     file_type: String,
+    claimed_type: Option<String>,  // from the file extension
+    ocr: Option<Arc<dyn OcrEngine>>,
 }
 
 impl DocumentParser {
+    /// Open `path`, identifying its format from the content. The extension
+    /// only settles what the bytes cannot, e.g. a zip without content types.
     pub fn new(path: &str) -> Result<Self> {
-        let ext = Path::new(path)
-            .extension()
+        Self::from_bytes(std::fs::read(path)?, Some(path))
+    }
+
+    /// Parse a document held in memory. `filename` is an optional hint,
+    /// used like the extension of a path.
+    pub fn from_bytes(data: impl Into<Vec<u8>>, filename: Option<&str>) -> Result<Self> {
+        let data = data.into();
+        let claimed_type = filename
+            .and_then(|name| Path::new(name).extension())
             .and_then(|e| e.to_str())
-            .ok_or_else(|| TraceDeckError::ParseError("No file extension".into()))?
-            .to_lowercase();
+            .map(|e| e.to_lowercase());
+        let detected = sniff::detect(Cursor::new(&data));
+
+        let file_type = match (detected, claimed_type.as_deref()) {
+            // Same format, different spelling.
+            (Some("jpg"), Some("jpeg")) => "jpeg",
+            (Some("zip"), Some(claimed @ ("docx" | "pptx"))) => claimed,
+            (Some(detected), _) => detected,
+            (None, Some(claimed)) => claimed,
+            (None, None) => "unknown",
+        };
+        if !SUPPORTED_FORMATS.contains(&file_type) {
+            return Err(TraceDeckError::UnsupportedFormat {
+                detected: detected.unwrap_or("unknown").to_string(),
+                claimed: claimed_type.unwrap_or_else(|| "none".to_string()),
+            });
+        }
 
         Ok(Self {
-            file_path: path.to_string(),
-            file_type: ext,
+            data,
+            file_type: file_type.to_string(),
+            claimed_type,
+            ocr: None,
         })
     }
 
+    /// Read a whole document from `reader`, e.g. an upload body or stdin.
+    pub async fn from_reader<R: AsyncRead + Unpin>(mut reader: R, filename: Option<&str>) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        Self::from_bytes(data, filename)
+    }
+
+    /// Format the document will be parsed as, e.g. `pdf` or `pptx`.
+    pub fn file_type(&self) -> &str {
+        &self.file_type
+    }
+
+    /// Use `engine` to read pages that carry no extractable text. OCR is off
+    /// unless an engine is given.
+    pub fn with_ocr(mut self, engine: impl OcrEngine + 'static) -> Self {
+        self.ocr = Some(Arc::new(engine));
+        self
+    }
+
+    /// Never run OCR; image-only pages stay empty.
+    pub fn without_ocr(mut self) -> Self {
+        self.ocr = None;
+        self
+    }
+
     pub async fn extract(&self) -> Result<ExtractedContent> {
-        match self.file_type.as_str() {
+        let mut content = match self.file_type.as_str() {
             "pdf" => self.parse_pdf().await,
             "docx" => self.parse_docx().await,
+            "pptx" => self.parse_pptx().await,
             "png" | "jpg" | "jpeg" | "webp" | "gif" => self.parse_image().await,
-            _ => Err(TraceDeckError::UnsupportedFormat(self.file_type.clone())),
+            _ => Err(TraceDeckError::UnsupportedFormat {
+                detected: self.file_type.clone(),
+                claimed: self.claimed_type.clone().unwrap_or_else(|| "none".to_string()),
+            }),
+        }?;
+
+        if let Some(engine) = self.ocr.clone() {
+            // Engines may shell out or otherwise block.
+            content = tokio::task::spawn_blocking(move || {
+                recognize_pages(engine.as_ref(), &mut content);
+                content
+            })
+            .await
+            .map_err(|e| TraceDeckError::OcrError(e.to_string()))?;
         }
+        for page in &mut content.pages {
+            for table in &mut page.tables {
+                table.page = page.number;
+            }
+        }
+        Ok(content)
     }
 
     async fn parse_pdf(&self) -> Result<ExtractedContent> {
-        // PDF parsing logic
+        let document = pdf::PdfDocument::parse(&self.data)?;
+
+        // Image ids are unique across the document, as for decks.
+        let mut image_count = 0;
+        let pages: Vec<PageContent> = document
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(idx, page)| {
+                let spans = layout::reading_order(page.spans());
+                let images = page.images(image_count);
+                image_count += images.len();
+                let paths = page.vector_paths();
+                PageContent {
+                    text: layout::spans_to_text(&spans),
+                    tables: table::detect(&spans, &paths),
+                    spans,
+                    images,
+                    paths,
+                    width: Some(page.width),
+                    height: Some(page.height),
+                    ..PageContent::new(idx as u32 + 1)
+                }
+            })
+            .collect();
+
         let metadata = DocumentMetadata {
             file_type: "pdf".to_string(),
-            file_size: std::fs::metadata(&self.file_path)?.len(),
-            pages: Some(1), // Extract actual page count
-            title: None,
-            author: None,
-            created_at: None,
+            file_size: self.data.len() as u64,
+            pages: Some(pages.len() as u32),
+            title: document.info.title,
+            author: document.info.author,
+            created_at: document.info.created,
+            modified_at: document.info.modified,
+            creator_tool: document.info.creator_tool,
+            producer: document.info.producer,
+            keywords: document.info.keywords,
         };
 
         Ok(ExtractedContent {
-            text: String::new(), // Extract text from PDF
-            images: vec![],      // Extract images from PDF
-            tables: vec![],      // Extract tables from PDF
+            text: join_pages(&pages),
+            pages,
             metadata,
         })
     }
 
     async fn parse_docx(&self) -> Result<ExtractedContent> {
-        // DOCX parsing logic
+        let file_size = self.data.len() as u64;
+        let document = docx::DocxDocument::parse(&self.data)?;
+
         let metadata = DocumentMetadata {
             file_type: "docx".to_string(),
-            file_size: std::fs::metadata(&self.file_path)?.len(),
+            file_size,
             pages: None,
-            title: None,
-            author: None,
-            created_at: None,
+            title: document.properties.title.clone(),
+            author: document.properties.creator.clone(),
+            created_at: document.properties.created.clone(),
+            modified_at: document.properties.modified.clone(),
+            creator_tool: document.properties.application.clone(),
+            producer: None,
+            keywords: document.properties.keywords.clone(),
+        };
+
+        // Word documents carry no fixed pagination, so the body is one page.
+        let page = PageContent {
+            text: document.text(),
+            paragraphs: document.paragraphs,
+            images: document.images,
+            tables: document.tables,
+            charts: document.charts,
+            ..PageContent::new(1)
         };
+        let pages = vec![page];
 
         Ok(ExtractedContent {
-            text: String::new(),
-            images: vec![],
-            tables: vec![],
+            text: join_pages(&pages),
+            pages,
+            metadata,
+        })
+    }
+
+    async fn parse_pptx(&self) -> Result<ExtractedContent> {
+        let file_size = self.data.len() as u64;
+        let deck = pptx::PptxDocument::parse(&self.data)?;
+
+        let pages: Vec<PageContent> = deck
+            .slides
+            .into_iter()
+            .enumerate()
+            .map(|(idx, slide)| {
+                let spans = layout::reading_order(slide.spans);
+                PageContent {
+                    text: layout::spans_to_text(&spans),
+                    spans,
+                    images: slide.images,
+                    tables: slide.tables,
+                    charts: slide.charts,
+                    notes: slide.notes,
+                    width: Some(deck.width),
+                    height: Some(deck.height),
+                    ..PageContent::new(idx as u32 + 1)
+                }
+            })
+            .collect();
+
+        let metadata = DocumentMetadata {
+            file_type: "pptx".to_string(),
+            file_size,
+            pages: Some(pages.len() as u32),
+            title: deck.properties.title,
+            author: deck.properties.creator,
+            created_at: deck.properties.created,
+            modified_at: deck.properties.modified,
+            creator_tool: deck.properties.application,
+            producer: None,
+            keywords: deck.properties.keywords,
+        };
+
+        Ok(ExtractedContent {
+            text: join_pages(&pages),
+            pages,
             metadata,
         })
     }
 
     async fn parse_image(&self) -> Result<ExtractedContent> {
-        let img = ImageReader::open(&self.file_path)?
+        let data = &self.data;
+        let img = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()?
             .decode()
             .map_err(|e| TraceDeckError::ImageError(e.to_string()))?;
 
@@ -77,11 +257,15 @@ impl DocumentParser {
 
         let metadata = DocumentMetadata {
             file_type: self.file_type.clone(),
-            file_size: std::fs::metadata(&self.file_path)?.len(),
+            file_size: data.len() as u64,
             pages: Some(1),
             title: None,
             author: None,
             created_at: None,
+            modified_at: None,
+            creator_tool: None,
+            producer: None,
+            keywords: Vec::new(),
         };
 
         let image_data = ImageData {
@@ -89,14 +273,92 @@ impl DocumentParser {
             format: self.file_type.clone(),
             width,
             height,
-            data: vec![], // Serialize image
+            sha256: content_hash(data),
+            data: data.clone(),
+            path: None,
+            bbox: Some(BoundingBox::new(0.0, 0.0, width as f32, height as f32)),
+        };
+
+        let page = PageContent {
+            images: vec![image_data],
+            width: Some(width as f32),
+            height: Some(height as f32),
+            ..PageContent::new(1)
         };
 
         Ok(ExtractedContent {
             text: String::new(),
-            images: vec![image_data],
-            tables: vec![],
+            pages: vec![page],
             metadata,
         })
     }
 }
+
+/// OCR the images of pages that have no text of their own, placing the
+/// recognized words where each image sits on the page. Recognition failures
+/// leave the page empty rather than failing the document.
+fn recognize_pages(engine: &dyn OcrEngine, content: &mut ExtractedContent) {
+    let mut changed = false;
+
+    for page in content.pages.iter_mut().filter(|p| p.text.trim().is_empty()) {
+        let mut words: Vec<TextSpan> = Vec::new();
+        for image in page.images.iter().filter(|i| !i.data.is_empty()) {
+            let recognized = match engine.recognize(&image.data) {
+                Ok(words) => words,
+                Err(e) => {
+                    tracing::warn!("OCR failed for {} on page {}: {}", image.id, page.number, e);
+                    continue;
+                }
+            };
+
+            let frame = image.bbox.unwrap_or(BoundingBox::new(
+                0.0,
+                0.0,
+                image.width as f32,
+                image.height as f32,
+            ));
+            let sx = if image.width > 0 { frame.width / image.width as f32 } else { 1.0 };
+            let sy = if image.height > 0 { frame.height / image.height as f32 } else { 1.0 };
+
+            words.extend(recognized.into_iter().map(|w| {
+                let bbox = BoundingBox::new(
+                    frame.x + w.bbox.x * sx,
+                    frame.y + w.bbox.y * sy,
+                    w.bbox.width * sx,
+                    w.bbox.height * sy,
+                );
+                TextSpan {
+                    text: w.text,
+                    font_size: bbox.height,
+                    bbox,
+                    confidence: Some(w.confidence),
+                    bold: false,
+                    list_level: None,
+                }
+            }));
+        }
+
+        if !words.is_empty() {
+            page.spans = layout::reading_order(layout::words_to_lines(words));
+            page.text = layout::spans_to_text(&page.spans);
+            if page.tables.is_empty() {
+                page.tables = table::detect(&page.spans, &page.paths);
+            }
+            changed = true;
+        }
+    }
+
+    if changed {
+        content.text = join_pages(&content.pages);
+    }
+}
+
+/// Flatten per-page text into the document-level `text` field.
+fn join_pages(pages: &[PageContent]) -> String {
+    pages
+        .iter()
+        .map(|p| p.text.as_str())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
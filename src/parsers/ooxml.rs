@@ -0,0 +1,295 @@
+// Shared helpers for Office Open XML packages (docx, pptx, xlsx)
+use crate::error::*;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// A zip-based OOXML package opened from memory.
+pub struct Package<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+}
+
+impl<'a> Package<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        let archive = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|e| TraceDeckError::ParseError(format!("invalid OOXML package: {}", e)))?;
+        Ok(Self { archive })
+    }
+
+    /// Raw bytes of a part, e.g. `word/media/image1.png`.
+    pub fn part(&mut self, name: &str) -> Option<Vec<u8>> {
+        let mut file = self.archive.by_name(name.trim_start_matches('/')).ok()?;
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data).ok()?;
+        Some(data)
+    }
+
+    /// Parse a part as XML.
+    pub fn xml(&mut self, name: &str) -> Option<XmlNode> {
+        let data = self.part(name)?;
+        XmlNode::parse(&String::from_utf8_lossy(&data))
+    }
+
+    /// Relationships of `part`, keyed by id, with targets resolved to
+    /// package paths. External targets are kept as-is.
+    pub fn relationships(&mut self, part: &str) -> HashMap<String, Relationship> {
+        let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+        let rels_path = if dir.is_empty() {
+            format!("_rels/{}.rels", file)
+        } else {
+            format!("{}/_rels/{}.rels", dir, file)
+        };
+
+        let Some(root) = self.xml(&rels_path) else {
+            return HashMap::new();
+        };
+
+        root.children("Relationship")
+            .filter_map(|rel| {
+                let id = rel.attr("Id")?.to_string();
+                let target = rel.attr("Target")?;
+                let external = rel.attr("TargetMode") == Some("External");
+                let target = if external {
+                    target.to_string()
+                } else {
+                    resolve_path(dir, target)
+                };
+                let kind = rel.attr("Type").unwrap_or_default();
+                let kind = kind.rsplit('/').next().unwrap_or(kind).to_string();
+                Some((id, Relationship { target, kind, external }))
+            })
+            .collect()
+    }
+
+    /// Dublin Core properties from `docProps/core.xml`, plus the authoring
+    /// application from `docProps/app.xml`.
+    pub fn core_properties(&mut self) -> CoreProperties {
+        let application = self
+            .xml("docProps/app.xml")
+            .and_then(|app| app.child("Application").map(|n| n.text().trim().to_string()))
+            .filter(|t| !t.is_empty());
+        let Some(root) = self.xml("docProps/core.xml") else {
+            return CoreProperties { application, ..Default::default() };
+        };
+        let field = |name: &str| {
+            root.child(name)
+                .map(|n| n.text())
+                .filter(|t| !t.trim().is_empty())
+        };
+
+        CoreProperties {
+            title: field("title"),
+            creator: field("creator"),
+            created: field("created"),
+            modified: field("modified"),
+            keywords: field("keywords").map(|k| split_keywords(&k)).unwrap_or_default(),
+            application,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Relationship {
+    pub target: String,
+    /// Last segment of the relationship type URI, e.g. `image` or `chart`.
+    pub kind: String,
+    pub external: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CoreProperties {
+    pub title: Option<String>,
+    pub creator: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub keywords: Vec<String>,
+    pub application: Option<String>,
+}
+
+/// Split a free-form keyword list on commas and semicolons.
+pub fn split_keywords(keywords: &str) -> Vec<String> {
+    keywords
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Resolve a relationship target relative to the directory of its source part.
+fn resolve_path(dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            "." | "" => {}
+            ".." => {
+                parts.pop();
+            }
+            s => parts.push(s),
+        }
+    }
+    parts.join("/")
+}
+
+/// Minimal XML element tree. Names are stored without namespace prefixes.
+#[derive(Debug, Clone, Default)]
+pub struct XmlNode {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    /// Child elements and character data, in document order.
+    pub nodes: Vec<XmlContent>,
+}
+
+/// A child of an element: another element or a run of character data.
+#[derive(Debug, Clone)]
+pub enum XmlContent {
+    Element(XmlNode),
+    Text(String),
+}
+
+impl XmlNode {
+    pub fn parse(xml: &str) -> Option<XmlNode> {
+        let mut reader = Reader::from_str(xml);
+        let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+
+        loop {
+            match reader.read_event().ok()? {
+                Event::Start(e) => stack.push(Self::element(&e, reader.decoder())),
+                Event::Empty(e) => {
+                    let node = Self::element(&e, reader.decoder());
+                    stack.last_mut()?.nodes.push(XmlContent::Element(node));
+                }
+                Event::End(_) => {
+                    let node = stack.pop()?;
+                    stack.last_mut()?.nodes.push(XmlContent::Element(node));
+                }
+                Event::Text(t) => {
+                    if let Ok(text) = t.decode() {
+                        stack.last_mut()?.push_text(&text);
+                    }
+                }
+                Event::CData(t) => {
+                    if let Ok(text) = t.decode() {
+                        stack.last_mut()?.push_text(&text);
+                    }
+                }
+                Event::GeneralRef(r) => {
+                    let resolved = match r.resolve_char_ref() {
+                        Ok(Some(c)) => Some(c),
+                        _ => r.decode().ok().and_then(|name| match name.as_ref() {
+                            "amp" => Some('&'),
+                            "lt" => Some('<'),
+                            "gt" => Some('>'),
+                            "quot" => Some('"'),
+                            "apos" => Some('\''),
+                            _ => None,
+                        }),
+                    };
+                    if let Some(c) = resolved {
+                        stack.last_mut()?.push_text(c.encode_utf8(&mut [0; 4]));
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        stack.pop()?.nodes.into_iter().find_map(|node| match node {
+            XmlContent::Element(element) => Some(element),
+            XmlContent::Text(_) => None,
+        })
+    }
+
+    /// Append character data, extending the text run it follows.
+    fn push_text(&mut self, text: &str) {
+        match self.nodes.last_mut() {
+            Some(XmlContent::Text(run)) => run.push_str(text),
+            _ => self.nodes.push(XmlContent::Text(text.to_string())),
+        }
+    }
+
+    fn element(e: &quick_xml::events::BytesStart, decoder: quick_xml::encoding::Decoder) -> XmlNode {
+        let attrs = e
+            .attributes()
+            .flatten()
+            .map(|a| {
+                let key = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+                let value = a
+                    .decoded_and_normalized_value(quick_xml::XmlVersion::Implicit1_0, decoder)
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&a.value).into_owned());
+                (key, value)
+            })
+            .collect();
+
+        XmlNode {
+            name: String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+            attrs,
+            ..Default::default()
+        }
+    }
+
+    /// Attribute value by qualified (`r:id`) or local (`val`) name.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name || k.rsplit(':').next() == Some(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Child elements, in document order.
+    pub fn elements(&self) -> impl Iterator<Item = &XmlNode> {
+        self.nodes.iter().filter_map(|node| match node {
+            XmlContent::Element(element) => Some(element),
+            XmlContent::Text(_) => None,
+        })
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlNode> {
+        self.elements().find(|c| c.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlNode> + 'a {
+        self.elements().filter(move |c| c.name == name)
+    }
+
+    /// Follow a path of child element names, e.g. `["spPr", "xfrm", "off"]`.
+    pub fn path(&self, names: &[&str]) -> Option<&XmlNode> {
+        names.iter().try_fold(self, |node, name| node.child(name))
+    }
+
+    /// All descendants named `name`, in document order.
+    pub fn descendants<'a>(&'a self, name: &'a str) -> Vec<&'a XmlNode> {
+        let mut out = Vec::new();
+        self.collect_descendants(name, &mut out);
+        out
+    }
+
+    fn collect_descendants<'a>(&'a self, name: &str, out: &mut Vec<&'a XmlNode>) {
+        for child in self.elements() {
+            if child.name == name {
+                out.push(child);
+            }
+            child.collect_descendants(name, out);
+        }
+    }
+
+    /// Concatenated character data of this element and its descendants,
+    /// in document order.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for node in &self.nodes {
+            match node {
+                XmlContent::Element(element) => out.push_str(&element.text()),
+                XmlContent::Text(text) => out.push_str(text),
+            }
+        }
+        out
+    }
+}
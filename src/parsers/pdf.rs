@@ -0,0 +1,1030 @@
+// PDF content stream interpretation
+use crate::{error::*, images::content_hash, models::*};
+use super::ooxml::split_keywords;
+use super::xmp::DocumentInfo;
+use ::pdf::content::{Color, FormXObject, Matrix, Op, TextDrawAdjusted};
+use ::pdf::primitive::{Date, PdfString, Primitive, TimeRel};
+use ::pdf::encoding::BaseEncoding;
+use ::pdf::file::FileOptions;
+use ::pdf::font::{Font, ToUnicodeMap, Widths};
+use ::pdf::enc::StreamFilter;
+use ::pdf::object::{ColorSpace, ImageXObject, Object, Resolve, Resources, XObject};
+use image::{GrayImage, ImageFormat, RgbImage};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// Maximum nesting of form XObjects we follow before giving up.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// Text and vector graphics recovered from a single PDF page.
+#[derive(Debug, Clone)]
+pub struct PdfPage {
+    pub runs: Vec<TextRun>,
+    pub paths: Vec<PdfPath>,
+    pub images: Vec<PdfImage>,
+    /// Visible page size in points, after applying `/Rotate`.
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A run of glyphs drawn by one text-showing operator.
+#[derive(Debug, Clone)]
+pub struct TextRun {
+    pub text: String,
+    /// Origin of the first glyph in page space.
+    pub x: f32,
+    pub y: f32,
+    /// Horizontal advance of the whole run in page space.
+    pub width: f32,
+    /// Rendered font size in page space.
+    pub font_size: f32,
+    /// Whether the font is a bold or heavier face.
+    pub bold: bool,
+}
+
+/// A painted path, in page space.
+#[derive(Debug, Clone)]
+pub struct PdfPath {
+    pub subpaths: Vec<Vec<(f32, f32)>>,
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
+    pub line_width: f32,
+}
+
+/// An image drawn on a page, re-encoded where the PDF stores raw samples.
+#[derive(Debug, Clone)]
+pub struct PdfImage {
+    pub format: String,  // jpeg and jp2 as stored, png for decoded samples
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    /// Corners of the unit square the image is painted into, in page space.
+    pub corners: [(f32, f32); 4],
+}
+
+impl PdfPage {
+    /// Drawn images in page coordinates (top-left origin), with ids
+    /// numbered from `first_id`.
+    pub fn images(&self, first_id: usize) -> Vec<ImageData> {
+        self.images
+            .iter()
+            .enumerate()
+            .map(|(idx, image)| {
+                let (xs, ys): (Vec<f32>, Vec<f32>) = image
+                    .corners
+                    .iter()
+                    .map(|&(x, y)| (x, self.height - y))
+                    .unzip();
+                let left = xs.iter().copied().fold(f32::INFINITY, f32::min);
+                let top = ys.iter().copied().fold(f32::INFINITY, f32::min);
+                let right = xs.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let bottom = ys.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+                ImageData {
+                    id: format!("img_{}", first_id + idx),
+                    format: image.format.clone(),
+                    width: image.width,
+                    height: image.height,
+                    data: image.data.clone(),
+                    sha256: content_hash(&image.data),
+                    path: None,
+                    bbox: Some(BoundingBox::new(left, top, right - left, bottom - top)),
+                }
+            })
+            .collect()
+    }
+
+    /// Painted paths in page coordinates (top-left origin).
+    pub fn vector_paths(&self) -> Vec<VectorPath> {
+        self.paths
+            .iter()
+            .map(|path| VectorPath {
+                subpaths: path
+                    .subpaths
+                    .iter()
+                    .map(|points| {
+                        points
+                            .iter()
+                            .map(|&(x, y)| (x, self.height - y))
+                            .collect()
+                    })
+                    .collect(),
+                fill: path.fill.clone(),
+                stroke: path.stroke.clone(),
+                line_width: path.line_width,
+            })
+            .collect()
+    }
+
+    /// Merge runs that continue each other on a baseline into spans, in
+    /// page coordinates (top-left origin).
+    pub fn spans(&self) -> Vec<TextSpan> {
+        let mut spans: Vec<TextSpan> = Vec::new();
+        let mut prev: Option<&TextRun> = None;
+
+        for run in &self.runs {
+            let size = run.font_size.max(1.0);
+            let bbox = BoundingBox::new(
+                run.x,
+                self.height - run.y - size * 0.8,
+                run.width,
+                size,
+            );
+
+            let continues = prev.is_some_and(|p| {
+                let gap = run.x - (p.x + p.width);
+                (p.y - run.y).abs() < size * 0.2 && gap > -size * 0.5 && gap < size
+            });
+
+            match spans.last_mut() {
+                Some(span) if continues => {
+                    let gap = run.x - prev.map_or(run.x, |p| p.x + p.width);
+                    if gap > size * 0.15 && !span.text.ends_with(' ') && !run.text.starts_with(' ') {
+                        span.text.push(' ');
+                    }
+                    span.text.push_str(&run.text);
+                    span.bbox = span.bbox.union(&bbox);
+                    span.font_size = span.font_size.max(run.font_size);
+                    span.bold &= run.bold;
+                }
+                _ => spans.push(TextSpan {
+                    text: run.text.clone(),
+                    bbox,
+                    font_size: run.font_size,
+                    confidence: None,
+                    bold: run.bold,
+                    list_level: None,
+                }),
+            }
+            prev = Some(run);
+        }
+
+        for span in &mut spans {
+            span.text = span.text.trim().to_string();
+        }
+        spans
+    }
+}
+
+/// Decoded contents of a PDF file.
+pub struct PdfDocument {
+    pub pages: Vec<PdfPage>,
+    pub info: DocumentInfo,
+}
+
+impl PdfDocument {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let file = FileOptions::cached()
+            .load(bytes)
+            .map_err(|e| TraceDeckError::PdfError(e.to_string()))?;
+        let resolver = file.resolver();
+
+        let mut pages = Vec::with_capacity(file.num_pages() as usize);
+        for page in file.pages() {
+            let page = page.map_err(|e| TraceDeckError::PdfError(e.to_string()))?;
+            let (width, height, page_space) = match page.crop_box() {
+                Ok(b) => page_space(b.left, b.bottom, b.right, b.top, page.rotate),
+                Err(_) => (0.0, 0.0, Mat::IDENTITY),
+            };
+            let mut interp = Interpreter::new(&resolver);
+            interp.state.ctm = page_space;
+
+            if let (Some(contents), Ok(resources)) = (&page.contents, page.resources()) {
+                let ops = contents
+                    .operations(&resolver)
+                    .map_err(|e| TraceDeckError::PdfError(e.to_string()))?;
+                interp.run(&ops, resources, 0);
+            }
+
+            pages.push(PdfPage {
+                runs: interp.runs,
+                paths: interp.paths,
+                images: interp.images,
+                width,
+                height,
+            });
+        }
+
+        // The Info dictionary is what most viewers show; XMP fills the gaps.
+        let info = file
+            .trailer
+            .info_dict
+            .as_ref()
+            .map(info_dict)
+            .unwrap_or_default();
+        let xmp = file
+            .get_root()
+            .metadata
+            .and_then(|stream| resolver.get(stream).ok())
+            .and_then(|stream| ::pdf::object::Stream::data(&stream, &resolver).ok())
+            .and_then(|data| DocumentInfo::from_xmp(&String::from_utf8_lossy(&data)))
+            .unwrap_or_default();
+
+        Ok(Self {
+            pages,
+            info: info.or(xmp),
+        })
+    }
+}
+
+/// Visible size of a page with crop box `left, bottom, right, top` and
+/// `/Rotate` of `rotate` degrees clockwise, and the matrix from user space
+/// to page space: points from the bottom-left corner of the page as
+/// displayed, y up.
+fn page_space(left: f32, bottom: f32, right: f32, top: f32, rotate: i32) -> (f32, f32, Mat) {
+    let (left, right) = (left.min(right), left.max(right));
+    let (bottom, top) = (bottom.min(top), bottom.max(top));
+    let (width, height) = (right - left, top - bottom);
+    match rotate.rem_euclid(360) {
+        90 => (height, width, Mat([0.0, -1.0, 1.0, 0.0, -bottom, right])),
+        180 => (width, height, Mat([-1.0, 0.0, 0.0, -1.0, right, top])),
+        270 => (height, width, Mat([0.0, 1.0, -1.0, 0.0, top, -left])),
+        _ => (width, height, Mat::translate(-left, -bottom)),
+    }
+}
+
+fn info_dict(info: &::pdf::object::InfoDict) -> DocumentInfo {
+    let text = |s: &Option<PdfString>| {
+        s.as_ref()
+            .map(|s| s.to_string_lossy().trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    DocumentInfo {
+        title: text(&info.title),
+        author: text(&info.author),
+        creator_tool: text(&info.creator),
+        producer: text(&info.producer),
+        created: info.creation_date.as_ref().map(iso_date),
+        modified: info.mod_date.as_ref().map(iso_date),
+        keywords: text(&info.keywords).map(|k| split_keywords(&k)).unwrap_or_default(),
+    }
+}
+
+/// Render a PDF date as ISO 8601, the form XMP and OOXML use.
+fn iso_date(date: &Date) -> String {
+    let zone = match date.rel {
+        TimeRel::Universal => "Z".to_string(),
+        TimeRel::Later => format!("+{:02}:{:02}", date.tz_hour, date.tz_minute),
+        TimeRel::Earlier => format!("-{:02}:{:02}", date.tz_hour, date.tz_minute),
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+        date.year, date.month, date.day, date.hour, date.minute, date.second, zone
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Mat([f32; 6]);
+
+impl Mat {
+    const IDENTITY: Mat = Mat([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(tx: f32, ty: f32) -> Mat {
+        Mat([1.0, 0.0, 0.0, 1.0, tx, ty])
+    }
+
+    /// `self × other` in PDF row-vector convention.
+    fn mul(&self, o: &Mat) -> Mat {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = o.0;
+        Mat([
+            a * a2 + b * c2,
+            a * b2 + b * d2,
+            c * a2 + d * c2,
+            c * b2 + d * d2,
+            e * a2 + f * c2 + e2,
+            e * b2 + f * d2 + f2,
+        ])
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    fn x_scale(&self) -> f32 {
+        (self.0[0] * self.0[0] + self.0[1] * self.0[1]).sqrt()
+    }
+
+    fn y_scale(&self) -> f32 {
+        (self.0[2] * self.0[2] + self.0[3] * self.0[3]).sqrt()
+    }
+}
+
+impl From<Matrix> for Mat {
+    fn from(m: Matrix) -> Self {
+        Mat([m.a, m.b, m.c, m.d, m.e, m.f])
+    }
+}
+
+#[derive(Clone)]
+struct GraphicsState {
+    ctm: Mat,
+    font: Option<String>,
+    font_size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horiz_scale: f32,
+    leading: f32,
+    rise: f32,
+    fill_color: Option<String>,
+    stroke_color: Option<String>,
+    line_width: f32,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            ctm: Mat::IDENTITY,
+            font: None,
+            font_size: 0.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horiz_scale: 1.0,
+            leading: 0.0,
+            rise: 0.0,
+            fill_color: Some("#000000".to_string()),
+            stroke_color: Some("#000000".to_string()),
+            line_width: 1.0,
+        }
+    }
+}
+
+struct Interpreter<'a, R: Resolve> {
+    resolver: &'a R,
+    state: GraphicsState,
+    stack: Vec<GraphicsState>,
+    text_matrix: Mat,
+    line_matrix: Mat,
+    fonts: HashMap<String, FontDecoder>,
+    runs: Vec<TextRun>,
+    /// Path under construction, already in page space.
+    path: Vec<Vec<(f32, f32)>>,
+    paths: Vec<PdfPath>,
+    images: Vec<PdfImage>,
+}
+
+impl<'a, R: Resolve> Interpreter<'a, R> {
+    fn new(resolver: &'a R) -> Self {
+        Self {
+            resolver,
+            state: GraphicsState::default(),
+            stack: Vec::new(),
+            text_matrix: Mat::IDENTITY,
+            line_matrix: Mat::IDENTITY,
+            fonts: HashMap::new(),
+            runs: Vec::new(),
+            path: Vec::new(),
+            paths: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    fn run(&mut self, ops: &[Op], resources: &Resources, depth: usize) {
+        // Font names are scoped to a resource dictionary, so forms get a fresh cache.
+        let saved_fonts = std::mem::take(&mut self.fonts);
+        for (name, font) in &resources.fonts {
+            if let Ok(font) = font.load(self.resolver) {
+                self.fonts
+                    .insert(name.to_string(), FontDecoder::new(&font, self.resolver));
+            }
+        }
+
+        for op in ops {
+            match op {
+                Op::Save => self.stack.push(self.state.clone()),
+                Op::Restore => {
+                    if let Some(state) = self.stack.pop() {
+                        self.state = state;
+                    }
+                }
+                Op::Transform { matrix } => {
+                    self.state.ctm = Mat::from(*matrix).mul(&self.state.ctm);
+                }
+                Op::LineWidth { width } => self.state.line_width = *width,
+                Op::FillColor { color } => self.state.fill_color = color_hex(color),
+                Op::StrokeColor { color } => self.state.stroke_color = color_hex(color),
+                Op::MoveTo { p } => {
+                    let point = self.state.ctm.apply(p.x, p.y);
+                    self.path.push(vec![point]);
+                }
+                Op::LineTo { p } | Op::CurveTo { p, .. } => {
+                    // Curves are reduced to their end points; charts only
+                    // need the vertices.
+                    let point = self.state.ctm.apply(p.x, p.y);
+                    match self.path.last_mut() {
+                        Some(subpath) => subpath.push(point),
+                        None => self.path.push(vec![point]),
+                    }
+                }
+                Op::Rect { rect } => {
+                    let ctm = self.state.ctm;
+                    let (x, y, w, h) = (rect.x, rect.y, rect.width, rect.height);
+                    self.path.push(vec![
+                        ctm.apply(x, y),
+                        ctm.apply(x + w, y),
+                        ctm.apply(x + w, y + h),
+                        ctm.apply(x, y + h),
+                        ctm.apply(x, y),
+                    ]);
+                }
+                Op::Close => {
+                    if let Some(subpath) = self.path.last_mut() {
+                        if let (Some(&first), Some(&last)) = (subpath.first(), subpath.last()) {
+                            if first != last {
+                                subpath.push(first);
+                            }
+                            // Drawing continues from the start of the closed subpath.
+                            self.path.push(vec![first]);
+                        }
+                    }
+                }
+                Op::Stroke => self.paint(false, true),
+                Op::Fill { .. } => self.paint(true, false),
+                Op::FillAndStroke { .. } => self.paint(true, true),
+                Op::EndPath => self.path.clear(),
+                Op::BeginText => {
+                    self.text_matrix = Mat::IDENTITY;
+                    self.line_matrix = Mat::IDENTITY;
+                }
+                Op::EndText => {}
+                Op::CharSpacing { char_space } => self.state.char_spacing = *char_space,
+                Op::WordSpacing { word_space } => self.state.word_spacing = *word_space,
+                Op::TextScaling { horiz_scale } => self.state.horiz_scale = *horiz_scale / 100.0,
+                Op::Leading { leading } => self.state.leading = *leading,
+                Op::TextRise { rise } => self.state.rise = *rise,
+                Op::TextFont { name, size } => {
+                    self.state.font = Some(name.to_string());
+                    self.state.font_size = *size;
+                }
+                Op::MoveTextPosition { translation } => {
+                    self.line_matrix =
+                        Mat::translate(translation.x, translation.y).mul(&self.line_matrix);
+                    self.text_matrix = self.line_matrix;
+                }
+                Op::SetTextMatrix { matrix } => {
+                    self.line_matrix = Mat::from(*matrix);
+                    self.text_matrix = self.line_matrix;
+                }
+                Op::TextNewline => self.newline(),
+                Op::TextDraw { text } => {
+                    let mut run = self.begin_run();
+                    self.show(text.as_bytes(), &mut run);
+                    self.finish_run(run);
+                }
+                Op::TextDrawAdjusted { array } => {
+                    let mut run = self.begin_run();
+                    for item in array {
+                        match item {
+                            TextDrawAdjusted::Text(text) => self.show(text.as_bytes(), &mut run),
+                            TextDrawAdjusted::Spacing(adjust) => {
+                                let tx = -adjust / 1000.0
+                                    * self.state.font_size
+                                    * self.state.horiz_scale;
+                                self.text_matrix = Mat::translate(tx, 0.0).mul(&self.text_matrix);
+                                // Large negative kerning is how many producers encode a space.
+                                if *adjust < -200.0 && !run.text.ends_with(' ') && !run.text.is_empty() {
+                                    run.text.push(' ');
+                                }
+                            }
+                        }
+                    }
+                    self.finish_run(run);
+                }
+                Op::XObject { name } => self.xobject(name.as_str(), resources, depth),
+                Op::InlineImage { image } => self.image(image),
+                _ => {}
+            }
+        }
+
+        self.fonts = saved_fonts;
+    }
+
+    fn xobject(&mut self, name: &str, resources: &Resources, depth: usize) {
+        let Some(&xobject) = resources.xobjects.get(name) else {
+            return;
+        };
+        let Ok(xobject) = self.resolver.get(xobject) else {
+            return;
+        };
+        match *xobject {
+            XObject::Image(ref image) => self.image(image),
+            XObject::Form(ref form) if depth < MAX_FORM_DEPTH => self.form(form, depth),
+            _ => {}
+        }
+    }
+
+    fn image(&mut self, image: &ImageXObject) {
+        // Stencil masks paint the fill colour through a shape; they are not pictures.
+        if image.image_mask {
+            return;
+        }
+        let Some((format, data)) = encode_image(image, self.resolver) else {
+            return;
+        };
+
+        // Images are painted into the unit square of the current user space.
+        let ctm = self.state.ctm;
+        self.images.push(PdfImage {
+            format: format.to_string(),
+            width: image.width,
+            height: image.height,
+            data,
+            corners: [
+                ctm.apply(0.0, 0.0),
+                ctm.apply(1.0, 0.0),
+                ctm.apply(1.0, 1.0),
+                ctm.apply(0.0, 1.0),
+            ],
+        });
+    }
+
+    fn form(&mut self, form: &FormXObject, depth: usize) {
+        let (Ok(ops), Some(form_resources)) =
+            (form.operations(self.resolver), form.dict().resources.as_ref())
+        else {
+            return;
+        };
+
+        let saved = (self.state.clone(), self.text_matrix, self.line_matrix);
+        if let Some(matrix) = form
+            .dict()
+            .matrix
+            .clone()
+            .and_then(|m| Matrix::from_primitive(m, self.resolver).ok())
+        {
+            self.state.ctm = Mat::from(matrix).mul(&self.state.ctm);
+        }
+        self.run(&ops, form_resources, depth + 1);
+        (self.state, self.text_matrix, self.line_matrix) = saved;
+    }
+
+    fn paint(&mut self, fill: bool, stroke: bool) {
+        let subpaths: Vec<_> = std::mem::take(&mut self.path)
+            .into_iter()
+            .filter(|s| s.len() > 1)
+            .collect();
+        if subpaths.is_empty() {
+            return;
+        }
+
+        // Pattern and separation colours have no RGB value to report.
+        let paint = |on: bool, color: &Option<String>| on.then(|| color.clone().unwrap_or_default());
+        self.paths.push(PdfPath {
+            subpaths,
+            fill: paint(fill, &self.state.fill_color),
+            stroke: paint(stroke, &self.state.stroke_color),
+            line_width: self.state.line_width * self.state.ctm.x_scale(),
+        });
+    }
+
+    fn newline(&mut self) {
+        self.line_matrix = Mat::translate(0.0, -self.state.leading).mul(&self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    fn begin_run(&self) -> TextRun {
+        let trm = self.text_matrix.mul(&self.state.ctm);
+        let (x, y) = trm.apply(0.0, self.state.rise);
+        TextRun {
+            text: String::new(),
+            x,
+            y,
+            width: 0.0,
+            font_size: self.state.font_size * trm.y_scale(),
+            bold: self
+                .state
+                .font
+                .as_ref()
+                .and_then(|f| self.fonts.get(f))
+                .is_some_and(|f| f.bold),
+        }
+    }
+
+    fn finish_run(&mut self, mut run: TextRun) {
+        let trm = self.text_matrix.mul(&self.state.ctm);
+        let (end_x, _) = trm.apply(0.0, self.state.rise);
+        run.width = (end_x - run.x).max(0.0);
+        if !run.text.trim().is_empty() {
+            self.runs.push(run);
+        }
+    }
+
+    fn show(&mut self, bytes: &[u8], run: &mut TextRun) {
+        let Some(font) = self.state.font.as_ref().and_then(|f| self.fonts.get(f)) else {
+            return;
+        };
+
+        let mut advance = 0.0;
+        for (code, width, is_space) in font.glyphs(bytes) {
+            if let Some(text) = font.decode(code) {
+                run.text.push_str(&text);
+            }
+            let mut tx = width / 1000.0 * self.state.font_size + self.state.char_spacing;
+            if is_space {
+                tx += self.state.word_spacing;
+            }
+            advance += tx * self.state.horiz_scale;
+        }
+
+        self.text_matrix = Mat::translate(advance, 0.0).mul(&self.text_matrix);
+        if run.font_size == 0.0 {
+            run.font_size = self.state.font_size * self.text_matrix.mul(&self.state.ctm).x_scale();
+        }
+    }
+}
+
+/// Image bytes in a format image tools can open: JPEG and JPEG 2000 data
+/// as stored, raw and Flate-compressed samples as PNG. CCITT and JBIG2
+/// scans are not supported.
+fn encode_image(image: &ImageXObject, resolver: &impl Resolve) -> Option<(&'static str, Vec<u8>)> {
+    let (data, filter) = match image.raw_image_data(resolver) {
+        Ok(raw) => raw,
+        Err(e) => {
+            tracing::debug!("unreadable PDF image: {}", e);
+            return None;
+        }
+    };
+
+    let samples = match filter {
+        Some(StreamFilter::DCTDecode(_)) => return Some(("jpeg", data.to_vec())),
+        Some(StreamFilter::JPXDecode) => return Some(("jp2", data.to_vec())),
+        Some(StreamFilter::FlateDecode(_)) => image.image_data(resolver).ok()?,
+        None => data,
+        Some(other) => {
+            tracing::debug!("unsupported PDF image filter: {:?}", other);
+            return None;
+        }
+    };
+
+    let png = samples_to_png(&samples, image)?;
+    Some(("png", png))
+}
+
+/// Re-encode decoded image samples as PNG, converting CMYK and indexed
+/// colour to RGB.
+fn samples_to_png(samples: &[u8], image: &ImageXObject) -> Option<Vec<u8>> {
+    let (width, height) = (image.width, image.height);
+    let bits = image.bits_per_component.unwrap_or(8) as u32;
+    let (space, palette) = match image.color_space.as_ref()? {
+        ColorSpace::Indexed(base, _, lookup) => (base.as_ref(), Some(lookup)),
+        space => (space, None),
+    };
+    let channels = match space {
+        ColorSpace::DeviceGray | ColorSpace::CalGray(_) => 1,
+        ColorSpace::DeviceRGB | ColorSpace::CalRGB(_) => 3,
+        ColorSpace::DeviceCMYK | ColorSpace::CalCMYK(_) => 4,
+        ColorSpace::Icc(icc) => icc.info.components as usize,
+        _ => return None,
+    };
+    if !matches!(channels, 1 | 3 | 4) || !matches!(bits, 1 | 2 | 4 | 8 | 16) {
+        return None;
+    }
+
+    // Rows are padded to a whole byte.
+    let per_pixel = if palette.is_some() { 1 } else { channels };
+    let row_bytes = (width as usize * per_pixel * bits as usize).div_ceil(8);
+    // An empty image has no rows to encode.
+    if width == 0 || height == 0 || row_bytes == 0 || samples.len() < row_bytes * height as usize {
+        return None;
+    }
+    let max = ((1u32 << bits.min(8)) - 1) as f32;
+    let sample = |row: &[u8], i: usize| -> u8 {
+        match bits {
+            8 => row[i],
+            16 => row[i * 2],
+            _ => {
+                let bit = i * bits as usize;
+                (row[bit / 8] >> (8 - bits as usize - bit % 8)) & max as u8
+            }
+        }
+    };
+
+    let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+    for row in samples.chunks(row_bytes).take(height as usize) {
+        for x in 0..width as usize {
+            let color: Vec<u8> = match palette {
+                Some(lookup) => {
+                    let at = sample(row, x) as usize * channels;
+                    lookup.get(at..at + channels)?.to_vec()
+                }
+                None if bits < 8 => (0..channels)
+                    .map(|c| (sample(row, x * channels + c) as f32 / max * 255.0).round() as u8)
+                    .collect(),
+                None => (0..channels).map(|c| sample(row, x * channels + c)).collect(),
+            };
+            match color[..] {
+                [g] => rgb.extend([g; 3]),
+                [r, g, b] => rgb.extend([r, g, b]),
+                [c, m, y, k] => {
+                    let unit = |v: u8| v as f32 / 255.0;
+                    let [r, g, b] = cmyk_to_rgb([unit(c), unit(m), unit(y), unit(k)]);
+                    rgb.extend([r, g, b].map(|v| (v * 255.0).round() as u8));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    if channels == 1 && palette.is_none() {
+        let gray: Vec<u8> = rgb.iter().step_by(3).copied().collect();
+        GrayImage::from_raw(width, height, gray)?
+            .write_to(&mut png, ImageFormat::Png)
+            .ok()?;
+    } else {
+        RgbImage::from_raw(width, height, rgb)?
+            .write_to(&mut png, ImageFormat::Png)
+            .ok()?;
+    }
+    Some(png.into_inner())
+}
+
+/// `#rrggbb` for gray, RGB and CMYK colours (including `sc`/`scn` operands).
+fn color_hex(color: &Color) -> Option<String> {
+    let rgb = match color {
+        Color::Gray(g) => [*g; 3],
+        Color::Rgb(c) => [c.red, c.green, c.blue],
+        Color::Cmyk(c) => cmyk_to_rgb([c.cyan, c.magenta, c.yellow, c.key]),
+        Color::Other(args) => {
+            let n: Vec<f32> = args
+                .iter()
+                .map(|p| match p {
+                    Primitive::Integer(i) => Some(*i as f32),
+                    Primitive::Number(n) => Some(*n),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?;
+            match n[..] {
+                [g] => [g; 3],
+                [r, g, b] => [r, g, b],
+                [c, m, y, k] => cmyk_to_rgb([c, m, y, k]),
+                _ => return None,
+            }
+        }
+    };
+
+    let [r, g, b] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+fn cmyk_to_rgb([c, m, y, k]: [f32; 4]) -> [f32; 3] {
+    [(1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k)]
+}
+
+/// Whether a base font name such as `ABCDEF+Inter-SemiBold` names a bold
+/// or heavier face.
+fn is_bold(name: &str) -> bool {
+    let style = name.rsplit(['-', ',', '+']).next().unwrap_or(name).to_lowercase();
+    ["bold", "black", "heavy", "semibold", "demi"].iter().any(|w| style.contains(w))
+}
+
+/// Maps character codes of one font to Unicode text and glyph widths.
+struct FontDecoder {
+    cid: bool,
+    bold: bool,
+    to_unicode: Option<ToUnicodeMap>,
+    encoding: Option<HashMap<u8, String>>,
+    widths: Option<Widths>,
+}
+
+impl FontDecoder {
+    fn new(font: &Font, resolver: &impl Resolve) -> Self {
+        let to_unicode = font.to_unicode(resolver).and_then(|m| m.ok());
+        let widths = font.widths(resolver).ok().flatten();
+        let cid = font.is_cid();
+        let bold = font.name.as_ref().is_some_and(|n| is_bold(n.as_str()));
+
+        let encoding = (!cid).then(|| {
+            let base = font.encoding().map(|e| &e.base);
+            let mut table: HashMap<u8, String> = (0..=255u8)
+                .filter_map(|code| base_char(base, code).map(|c| (code, c.to_string())))
+                .collect();
+            if let Some(enc) = font.encoding() {
+                for (&code, name) in &enc.differences {
+                    if let (Ok(code), Some(text)) = (u8::try_from(code), glyph_to_unicode(name)) {
+                        table.insert(code, text);
+                    }
+                }
+            }
+            table
+        });
+
+        Self {
+            cid,
+            bold,
+            to_unicode,
+            encoding,
+            widths,
+        }
+    }
+
+    /// Split a string operand into (code, width, is-single-byte-space) triples.
+    fn glyphs(&self, bytes: &[u8]) -> Vec<(u16, f32, bool)> {
+        let width = |code: u16| {
+            self.widths
+                .as_ref()
+                .map(|w| w.get(code as usize))
+                .filter(|w| *w > 0.0)
+                .unwrap_or(500.0)
+        };
+
+        if self.cid {
+            bytes
+                .chunks(2)
+                .map(|c| {
+                    let code = if c.len() == 2 {
+                        u16::from_be_bytes([c[0], c[1]])
+                    } else {
+                        c[0] as u16
+                    };
+                    (code, width(code), false)
+                })
+                .collect()
+        } else {
+            bytes
+                .iter()
+                .map(|&b| (b as u16, width(b as u16), b == b' '))
+                .collect()
+        }
+    }
+
+    fn decode(&self, code: u16) -> Option<String> {
+        if let Some(text) = self.to_unicode.as_ref().and_then(|m| m.get(code)) {
+            return Some(text.to_string());
+        }
+        if let Some(table) = &self.encoding {
+            return u8::try_from(code).ok().and_then(|c| table.get(&c).cloned());
+        }
+        // Identity-encoded CID fonts without a ToUnicode map: best effort UCS-2.
+        char::from_u32(code as u32)
+            .filter(|c| !c.is_control())
+            .map(|c| c.to_string())
+    }
+}
+
+/// Character for `code` in one of the standard simple-font encodings.
+fn base_char(base: Option<&BaseEncoding>, code: u8) -> Option<char> {
+    let table: &[u16; 128] = match base {
+        Some(BaseEncoding::MacRomanEncoding) => &MAC_ROMAN_HIGH,
+        Some(BaseEncoding::StandardEncoding) => &STANDARD_HIGH,
+        _ => &WIN_ANSI_HIGH,
+    };
+
+    match code {
+        0x20..=0x7e => {
+            if matches!(base, Some(BaseEncoding::StandardEncoding)) {
+                match code {
+                    0x27 => return Some('\u{2019}'),
+                    0x60 => return Some('\u{2018}'),
+                    _ => {}
+                }
+            }
+            Some(code as char)
+        }
+        0x80..=0xff => match table[(code - 0x80) as usize] {
+            0 => None,
+            u => char::from_u32(u as u32),
+        },
+        _ => None,
+    }
+}
+
+/// Resolve an Adobe glyph name to Unicode text.
+fn glyph_to_unicode(name: &str) -> Option<String> {
+    let name = name.split('.').next().unwrap_or(name);
+
+    if let Some(c) = GLYPH_NAMES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c) {
+        return Some(c.to_string());
+    }
+    if name.len() == 1 && name.is_ascii() {
+        return Some(name.to_string());
+    }
+    if let Some(hex) = name.strip_prefix("uni") {
+        let chars: Option<String> = hex
+            .as_bytes()
+            .chunks(4)
+            .map(|c| {
+                std::str::from_utf8(c)
+                    .ok()
+                    .and_then(|s| u32::from_str_radix(s, 16).ok())
+                    .and_then(char::from_u32)
+            })
+            .collect();
+        return chars;
+    }
+    if let Some(hex) = name.strip_prefix('u') {
+        if (4..=6).contains(&hex.len()) {
+            return u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .map(String::from);
+        }
+    }
+    None
+}
+
+const GLYPH_NAMES: &[(&str, &str)] = &[
+    ("space", " "), ("exclam", "!"), ("quotedbl", "\""), ("numbersign", "#"),
+    ("dollar", "$"), ("percent", "%"), ("ampersand", "&"), ("quotesingle", "'"),
+    ("quoteright", "\u{2019}"), ("quoteleft", "\u{2018}"), ("parenleft", "("),
+    ("parenright", ")"), ("asterisk", "*"), ("plus", "+"), ("comma", ","),
+    ("hyphen", "-"), ("period", "."), ("slash", "/"), ("zero", "0"), ("one", "1"),
+    ("two", "2"), ("three", "3"), ("four", "4"), ("five", "5"), ("six", "6"),
+    ("seven", "7"), ("eight", "8"), ("nine", "9"), ("colon", ":"), ("semicolon", ";"),
+    ("less", "<"), ("equal", "="), ("greater", ">"), ("question", "?"), ("at", "@"),
+    ("bracketleft", "["), ("backslash", "\\"), ("bracketright", "]"),
+    ("asciicircum", "^"), ("underscore", "_"), ("grave", "`"), ("braceleft", "{"),
+    ("bar", "|"), ("braceright", "}"), ("asciitilde", "~"), ("bullet", "\u{2022}"),
+    ("endash", "\u{2013}"), ("emdash", "\u{2014}"), ("quotedblleft", "\u{201c}"),
+    ("quotedblright", "\u{201d}"), ("quotesinglbase", "\u{201a}"),
+    ("quotedblbase", "\u{201e}"), ("ellipsis", "\u{2026}"), ("Euro", "\u{20ac}"),
+    ("sterling", "\u{a3}"), ("yen", "\u{a5}"), ("cent", "\u{a2}"),
+    ("copyright", "\u{a9}"), ("registered", "\u{ae}"), ("trademark", "\u{2122}"),
+    ("degree", "\u{b0}"), ("plusminus", "\u{b1}"), ("multiply", "\u{d7}"),
+    ("divide", "\u{f7}"), ("section", "\u{a7}"), ("paragraph", "\u{b6}"),
+    ("periodcentered", "\u{b7}"), ("middot", "\u{b7}"), ("dagger", "\u{2020}"),
+    ("daggerdbl", "\u{2021}"), ("perthousand", "\u{2030}"), ("minus", "\u{2212}"),
+    ("fi", "fi"), ("fl", "fl"), ("ff", "ff"), ("ffi", "ffi"), ("ffl", "ffl"),
+    ("nbspace", "\u{a0}"), ("nonbreakingspace", "\u{a0}"),
+    ("onehalf", "\u{bd}"), ("onequarter", "\u{bc}"), ("threequarters", "\u{be}"),
+    ("arrowright", "\u{2192}"), ("arrowleft", "\u{2190}"), ("arrowup", "\u{2191}"),
+    ("arrowdown", "\u{2193}"), ("checkmark", "\u{2713}"),
+    ("Aacute", "\u{c1}"), ("aacute", "\u{e1}"), ("Agrave", "\u{c0}"), ("agrave", "\u{e0}"),
+    ("Acircumflex", "\u{c2}"), ("acircumflex", "\u{e2}"), ("Adieresis", "\u{c4}"),
+    ("adieresis", "\u{e4}"), ("Atilde", "\u{c3}"), ("atilde", "\u{e3}"),
+    ("Aring", "\u{c5}"), ("aring", "\u{e5}"), ("AE", "\u{c6}"), ("ae", "\u{e6}"),
+    ("Ccedilla", "\u{c7}"), ("ccedilla", "\u{e7}"), ("Eacute", "\u{c9}"),
+    ("eacute", "\u{e9}"), ("Egrave", "\u{c8}"), ("egrave", "\u{e8}"),
+    ("Ecircumflex", "\u{ca}"), ("ecircumflex", "\u{ea}"), ("Edieresis", "\u{cb}"),
+    ("edieresis", "\u{eb}"), ("Iacute", "\u{cd}"), ("iacute", "\u{ed}"),
+    ("Igrave", "\u{cc}"), ("igrave", "\u{ec}"), ("Icircumflex", "\u{ce}"),
+    ("icircumflex", "\u{ee}"), ("Idieresis", "\u{cf}"), ("idieresis", "\u{ef}"),
+    ("Ntilde", "\u{d1}"), ("ntilde", "\u{f1}"), ("Oacute", "\u{d3}"), ("oacute", "\u{f3}"),
+    ("Ograve", "\u{d2}"), ("ograve", "\u{f2}"), ("Ocircumflex", "\u{d4}"),
+    ("ocircumflex", "\u{f4}"), ("Odieresis", "\u{d6}"), ("odieresis", "\u{f6}"),
+    ("Otilde", "\u{d5}"), ("otilde", "\u{f5}"), ("Oslash", "\u{d8}"), ("oslash", "\u{f8}"),
+    ("Uacute", "\u{da}"), ("uacute", "\u{fa}"), ("Ugrave", "\u{d9}"), ("ugrave", "\u{f9}"),
+    ("Ucircumflex", "\u{db}"), ("ucircumflex", "\u{fb}"), ("Udieresis", "\u{dc}"),
+    ("udieresis", "\u{fc}"), ("germandbls", "\u{df}"), ("ydieresis", "\u{ff}"),
+    ("yacute", "\u{fd}"), ("Yacute", "\u{dd}"), ("exclamdown", "\u{a1}"),
+    ("questiondown", "\u{bf}"), ("guillemotleft", "\u{ab}"), ("guillemotright", "\u{bb}"),
+    ("ordfeminine", "\u{aa}"), ("ordmasculine", "\u{ba}"),
+];
+
+/// WinAnsiEncoding, codes 0x80..=0xFF (0 = undefined).
+const WIN_ANSI_HIGH: [u16; 128] = [
+    0x20ac, 0, 0x201a, 0x0192, 0x201e, 0x2026, 0x2020, 0x2021,
+    0x02c6, 0x2030, 0x0160, 0x2039, 0x0152, 0, 0x017d, 0,
+    0, 0x2018, 0x2019, 0x201c, 0x201d, 0x2022, 0x2013, 0x2014,
+    0x02dc, 0x2122, 0x0161, 0x203a, 0x0153, 0, 0x017e, 0x0178,
+    0x00a0, 0x00a1, 0x00a2, 0x00a3, 0x00a4, 0x00a5, 0x00a6, 0x00a7,
+    0x00a8, 0x00a9, 0x00aa, 0x00ab, 0x00ac, 0x00ad, 0x00ae, 0x00af,
+    0x00b0, 0x00b1, 0x00b2, 0x00b3, 0x00b4, 0x00b5, 0x00b6, 0x00b7,
+    0x00b8, 0x00b9, 0x00ba, 0x00bb, 0x00bc, 0x00bd, 0x00be, 0x00bf,
+    0x00c0, 0x00c1, 0x00c2, 0x00c3, 0x00c4, 0x00c5, 0x00c6, 0x00c7,
+    0x00c8, 0x00c9, 0x00ca, 0x00cb, 0x00cc, 0x00cd, 0x00ce, 0x00cf,
+    0x00d0, 0x00d1, 0x00d2, 0x00d3, 0x00d4, 0x00d5, 0x00d6, 0x00d7,
+    0x00d8, 0x00d9, 0x00da, 0x00db, 0x00dc, 0x00dd, 0x00de, 0x00df,
+    0x00e0, 0x00e1, 0x00e2, 0x00e3, 0x00e4, 0x00e5, 0x00e6, 0x00e7,
+    0x00e8, 0x00e9, 0x00ea, 0x00eb, 0x00ec, 0x00ed, 0x00ee, 0x00ef,
+    0x00f0, 0x00f1, 0x00f2, 0x00f3, 0x00f4, 0x00f5, 0x00f6, 0x00f7,
+    0x00f8, 0x00f9, 0x00fa, 0x00fb, 0x00fc, 0x00fd, 0x00fe, 0x00ff,
+];
+
+/// MacRomanEncoding, codes 0x80..=0xFF.
+const MAC_ROMAN_HIGH: [u16; 128] = [
+    0x00c4, 0x00c5, 0x00c7, 0x00c9, 0x00d1, 0x00d6, 0x00dc, 0x00e1,
+    0x00e0, 0x00e2, 0x00e4, 0x00e3, 0x00e5, 0x00e7, 0x00e9, 0x00e8,
+    0x00ea, 0x00eb, 0x00ed, 0x00ec, 0x00ee, 0x00ef, 0x00f1, 0x00f3,
+    0x00f2, 0x00f4, 0x00f6, 0x00f5, 0x00fa, 0x00f9, 0x00fb, 0x00fc,
+    0x2020, 0x00b0, 0x00a2, 0x00a3, 0x00a7, 0x2022, 0x00b6, 0x00df,
+    0x00ae, 0x00a9, 0x2122, 0x00b4, 0x00a8, 0x2260, 0x00c6, 0x00d8,
+    0x221e, 0x00b1, 0x2264, 0x2265, 0x00a5, 0x00b5, 0x2202, 0x2211,
+    0x220f, 0x03c0, 0x222b, 0x00aa, 0x00ba, 0x03a9, 0x00e6, 0x00f8,
+    0x00bf, 0x00a1, 0x00ac, 0x221a, 0x0192, 0x2248, 0x2206, 0x00ab,
+    0x00bb, 0x2026, 0x00a0, 0x00c0, 0x00c3, 0x00d5, 0x0152, 0x0153,
+    0x2013, 0x2014, 0x201c, 0x201d, 0x2018, 0x2019, 0x00f7, 0x25ca,
+    0x00ff, 0x0178, 0x2044, 0x20ac, 0x2039, 0x203a, 0xfb01, 0xfb02,
+    0x2021, 0x00b7, 0x201a, 0x201e, 0x2030, 0x00c2, 0x00ca, 0x00c1,
+    0x00cb, 0x00c8, 0x00cd, 0x00ce, 0x00cf, 0x00cc, 0x00d3, 0x00d4,
+    0xf8ff, 0x00d2, 0x00da, 0x00db, 0x00d9, 0x0131, 0x02c6, 0x02dc,
+    0x00af, 0x02d8, 0x02d9, 0x02da, 0x00b8, 0x02dd, 0x02db, 0x02c7,
+];
+
+/// StandardEncoding, codes 0x80..=0xFF.
+const STANDARD_HIGH: [u16; 128] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0x00a1, 0x00a2, 0x00a3, 0x2044, 0x00a5, 0x0192, 0x00a7,
+    0x00a4, 0x0027, 0x201c, 0x00ab, 0x2039, 0x203a, 0xfb01, 0xfb02,
+    0, 0x2013, 0x2020, 0x2021, 0x00b7, 0, 0x00b6, 0x2022,
+    0x201a, 0x201e, 0x201d, 0x00bb, 0x2026, 0x2030, 0, 0x00bf,
+    0, 0x0060, 0x00b4, 0x02c6, 0x02dc, 0x00af, 0x02d8, 0x02d9,
+    0x00a8, 0, 0x02da, 0x00b8, 0, 0x02dd, 0x02db, 0x02c7,
+    0x2014, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0x00c6, 0, 0x00aa, 0, 0, 0, 0,
+    0x0141, 0x00d8, 0x0152, 0x00ba, 0, 0, 0, 0,
+    0, 0x00e6, 0, 0, 0, 0x0131, 0, 0,
+    0x0142, 0x00f8, 0x0153, 0x00df, 0, 0, 0, 0,
+];
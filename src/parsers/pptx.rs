@@ -0,0 +1,437 @@
+// PowerPoint deck parsing
+use super::ooxml::{CoreProperties, Package, Relationship, XmlNode};
+use super::chart;
+use super::table::{self, Cell};
+use crate::{error::*, images::content_hash, models::*};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// English Metric Units per point.
+const EMU_PER_POINT: f32 = 12700.0;
+
+/// Font size PowerPoint falls back to when a run does not set one.
+const DEFAULT_FONT_SIZE: f32 = 18.0;
+
+/// Contents of a `.pptx` deck, one entry per slide in presentation order.
+pub struct PptxDocument {
+    pub slides: Vec<Slide>,
+    pub properties: CoreProperties,
+    /// Slide size in points.
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Default)]
+pub struct Slide {
+    pub spans: Vec<TextSpan>,
+    pub tables: Vec<TableData>,
+    pub charts: Vec<ChartData>,
+    pub images: Vec<ImageData>,
+    pub notes: Option<String>,
+}
+
+impl PptxDocument {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut package = Package::new(bytes)?;
+        let properties = package.core_properties();
+
+        let presentation_path = package
+            .relationships("")
+            .into_values()
+            .find(|r| r.kind == "officeDocument")
+            .map(|r| r.target)
+            .unwrap_or_else(|| "ppt/presentation.xml".to_string());
+        let presentation = package
+            .xml(&presentation_path)
+            .ok_or_else(|| TraceDeckError::PptxError("missing presentation part".into()))?;
+        let rels = package.relationships(&presentation_path);
+
+        let (width, height) = presentation
+            .child("sldSz")
+            .map(|s| (emu(s.attr("cx")), emu(s.attr("cy"))))
+            .unwrap_or((720.0, 540.0));
+
+        let slide_paths: Vec<String> = presentation
+            .path(&["sldIdLst"])
+            .map(|list| {
+                list.children("sldId")
+                    .filter_map(|s| rels.get(s.attr("r:id")?))
+                    .map(|r| r.target.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut reader = SlideReader {
+            package: &mut package,
+            slide_size: (width, height),
+            image_count: 0,
+        };
+        let slides = slide_paths
+            .iter()
+            .map(|path| reader.slide(path))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            slides,
+            properties,
+            width,
+            height,
+        })
+    }
+}
+
+fn emu(value: Option<&str>) -> f32 {
+    value.and_then(|v| v.parse::<f32>().ok()).unwrap_or(0.0) / EMU_PER_POINT
+}
+
+/// Maps child shape coordinates of a group into slide coordinates.
+#[derive(Clone, Copy)]
+struct GroupTransform {
+    offset: (f32, f32),
+    child_offset: (f32, f32),
+    scale: (f32, f32),
+}
+
+impl GroupTransform {
+    const IDENTITY: GroupTransform = GroupTransform {
+        offset: (0.0, 0.0),
+        child_offset: (0.0, 0.0),
+        scale: (1.0, 1.0),
+    };
+
+    fn apply(&self, b: BoundingBox) -> BoundingBox {
+        BoundingBox::new(
+            self.offset.0 + (b.x - self.child_offset.0) * self.scale.0,
+            self.offset.1 + (b.y - self.child_offset.1) * self.scale.1,
+            b.width * self.scale.0,
+            b.height * self.scale.1,
+        )
+    }
+
+    /// Compose with a nested group's `grpSpPr/xfrm`.
+    fn nest(&self, xfrm: &XmlNode) -> GroupTransform {
+        let point = |name: &str, x: &str, y: &str| {
+            xfrm.child(name)
+                .map(|n| (emu(n.attr(x)), emu(n.attr(y))))
+                .unwrap_or((0.0, 0.0))
+        };
+        let (off, ext) = (point("off", "x", "y"), point("ext", "cx", "cy"));
+        let (ch_off, ch_ext) = (point("chOff", "x", "y"), point("chExt", "cx", "cy"));
+
+        let outer = self.apply(BoundingBox::new(off.0, off.1, ext.0, ext.1));
+        let ratio = |a: f32, b: f32| if b > 0.0 { a / b } else { 1.0 };
+        GroupTransform {
+            offset: (outer.x, outer.y),
+            child_offset: ch_off,
+            scale: (ratio(outer.width, ch_ext.0), ratio(outer.height, ch_ext.1)),
+        }
+    }
+}
+
+struct SlideReader<'a, 'b> {
+    package: &'a mut Package<'b>,
+    slide_size: (f32, f32),
+    image_count: usize,
+}
+
+/// Per-slide state while walking the shape tree.
+struct SlideContext {
+    rels: HashMap<String, Relationship>,
+    placeholders: HashMap<String, BoundingBox>,
+    slide: Slide,
+}
+
+impl SlideReader<'_, '_> {
+    fn slide(&mut self, path: &str) -> Result<Slide> {
+        let root = self
+            .package
+            .xml(path)
+            .ok_or_else(|| TraceDeckError::PptxError(format!("missing slide part {}", path)))?;
+        let rels = self.package.relationships(path);
+
+        let placeholders = rels
+            .values()
+            .find(|r| r.kind == "slideLayout")
+            .and_then(|r| self.package.xml(&r.target))
+            .map(|layout| placeholder_boxes(&layout))
+            .unwrap_or_default();
+
+        let notes = rels
+            .values()
+            .find(|r| r.kind == "notesSlide")
+            .and_then(|r| self.package.xml(&r.target))
+            .and_then(|n| notes_text(&n));
+
+        let mut ctx = SlideContext {
+            rels,
+            placeholders,
+            slide: Slide {
+                notes,
+                ..Slide::default()
+            },
+        };
+
+        if let Some(tree) = root.path(&["cSld", "spTree"]) {
+            self.walk(tree, GroupTransform::IDENTITY, &mut ctx);
+        }
+
+        Ok(ctx.slide)
+    }
+
+    /// Visit shapes in document order, which is z-order from back to front.
+    fn walk(&mut self, tree: &XmlNode, transform: GroupTransform, ctx: &mut SlideContext) {
+        for shape in tree.elements() {
+            match shape.name.as_str() {
+                "sp" => {
+                    let bbox = shape_box(shape.path(&["spPr", "xfrm"]))
+                        .map(|b| transform.apply(b))
+                        .or_else(|| placeholder_key(shape).and_then(|k| ctx.placeholders.get(&k).copied()))
+                        .unwrap_or(BoundingBox::new(0.0, 0.0, self.slide_size.0, self.slide_size.1));
+                    if let Some(body) = shape.child("txBody") {
+                        let bulleted = shape
+                            .path(&["nvSpPr", "nvPr", "ph"])
+                            .is_some_and(|ph| matches!(ph.attr("type"), None | Some("body") | Some("obj")));
+                        ctx.slide.spans.extend(text_spans(body, bbox, bulleted));
+                    }
+                }
+                "grpSp" => {
+                    let nested = shape
+                        .path(&["grpSpPr", "xfrm"])
+                        .map(|x| transform.nest(x))
+                        .unwrap_or(transform);
+                    self.walk(shape, nested, ctx);
+                }
+                "graphicFrame" => {
+                    let Some(data) = shape.path(&["graphic", "graphicData"]) else {
+                        continue;
+                    };
+                    let bbox = shape_box(shape.child("xfrm")).map(|b| transform.apply(b));
+                    if let Some(table) = data.child("tbl") {
+                        ctx.slide.tables.extend(table_data(shape, table, bbox));
+                    } else if let Some(target) = data
+                        .child("chart")
+                        .and_then(|c| ctx.rels.get(c.attr("r:id")?))
+                        .map(|r| r.target.clone())
+                    {
+                        if let Some(chart) = self.package.xml(&target).and_then(|c| chart::parse_chart(&c, bbox)) {
+                            ctx.slide.charts.push(chart);
+                        }
+                    }
+                }
+                "pic" => {
+                    let bbox = shape_box(shape.path(&["spPr", "xfrm"])).map(|b| transform.apply(b));
+                    let target = shape
+                        .descendants("blip")
+                        .first()
+                        .and_then(|b| ctx.rels.get(b.attr("r:embed")?))
+                        .filter(|r| !r.external)
+                        .map(|r| r.target.clone());
+                    if let Some(target) = target {
+                        let image = self.image(&target, bbox);
+                        ctx.slide.images.push(image);
+                    }
+                }
+                "AlternateContent" => {
+                    if let Some(branch) = shape.child("Choice").or_else(|| shape.child("Fallback")) {
+                        self.walk(branch, transform, ctx);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn image(&mut self, target: &str, bbox: Option<BoundingBox>) -> ImageData {
+        let data = self.package.part(target).unwrap_or_default();
+        let (width, height) = Some(&data)
+            .and_then(|bytes| {
+                image::ImageReader::new(Cursor::new(bytes))
+                    .with_guessed_format()
+                    .ok()?
+                    .into_dimensions()
+                    .ok()
+            })
+            .unwrap_or((0, 0));
+
+        let id = format!("img_{}", self.image_count);
+        self.image_count += 1;
+
+        ImageData {
+            id,
+            format: target.rsplit('.').next().unwrap_or_default().to_lowercase(),
+            width,
+            height,
+            sha256: content_hash(&data),
+            data,
+            path: None,
+            bbox,
+        }
+    }
+}
+
+/// Shape rectangle from an `a:xfrm` element, in points.
+fn shape_box(xfrm: Option<&XmlNode>) -> Option<BoundingBox> {
+    let xfrm = xfrm?;
+    let off = xfrm.child("off")?;
+    let ext = xfrm.child("ext")?;
+    Some(BoundingBox::new(
+        emu(off.attr("x")),
+        emu(off.attr("y")),
+        emu(ext.attr("cx")),
+        emu(ext.attr("cy")),
+    ))
+}
+
+/// Key identifying a placeholder: its index when set, otherwise its type.
+fn placeholder_key(shape: &XmlNode) -> Option<String> {
+    let ph = shape.path(&["nvSpPr", "nvPr", "ph"])?;
+    match ph.attr("idx") {
+        Some(idx) if idx != "0" => Some(format!("idx:{}", idx)),
+        _ => Some(format!("type:{}", ph.attr("type").unwrap_or("body"))),
+    }
+}
+
+/// Positions of a layout's placeholders, which slides inherit when their
+/// own shapes carry no transform.
+fn placeholder_boxes(layout: &XmlNode) -> HashMap<String, BoundingBox> {
+    layout
+        .descendants("sp")
+        .into_iter()
+        .filter_map(|sp| {
+            let bbox = shape_box(sp.path(&["spPr", "xfrm"]))?;
+            let ph = sp.path(&["nvSpPr", "nvPr", "ph"])?;
+            let mut keys = vec![format!("type:{}", ph.attr("type").unwrap_or("body"))];
+            if let Some(idx) = ph.attr("idx") {
+                keys.push(format!("idx:{}", idx));
+            }
+            Some(keys.into_iter().map(move |k| (k, bbox)))
+        })
+        .flatten()
+        .collect()
+}
+
+/// One span per paragraph, stacked top-down inside the shape's box.
+/// Paragraphs are list items when they set a bullet, or when they sit in
+/// a body placeholder, which is bulleted unless told otherwise.
+fn text_spans(body: &XmlNode, bbox: BoundingBox, bulleted: bool) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    let mut y = bbox.y;
+
+    for paragraph in body.children("p") {
+        let text = paragraph_text(paragraph);
+        let font_size = paragraph
+            .descendants("rPr")
+            .into_iter()
+            .chain(paragraph.children("endParaRPr"))
+            .find_map(|r| r.attr("sz")?.parse::<f32>().ok())
+            .map(|sz| sz / 100.0)
+            .unwrap_or(DEFAULT_FONT_SIZE);
+        let line_height = font_size * 1.2;
+
+        let runs: Vec<&XmlNode> = paragraph.children("r").collect();
+        let bold = !runs.is_empty()
+            && runs
+                .iter()
+                .all(|r| r.child("rPr").and_then(|p| p.attr("b")).is_some_and(|b| b == "1" || b == "true"));
+        let properties = paragraph.child("pPr");
+        let bullet = match properties {
+            Some(p) if p.child("buNone").is_some() => false,
+            Some(p) if p.child("buChar").is_some() || p.child("buAutoNum").is_some() => true,
+            _ => bulleted,
+        };
+        let list_level = bullet.then(|| {
+            properties
+                .and_then(|p| p.attr("lvl")?.parse().ok())
+                .unwrap_or(0)
+        });
+
+        if !text.trim().is_empty() {
+            spans.push(TextSpan {
+                text: text.trim().to_string(),
+                bbox: BoundingBox::new(bbox.x, y.min(bbox.bottom()), bbox.width, line_height),
+                font_size,
+                confidence: None,
+                bold,
+                list_level,
+            });
+        }
+        y += line_height;
+    }
+
+    spans
+}
+
+fn paragraph_text(paragraph: &XmlNode) -> String {
+    let mut out = String::new();
+    for child in paragraph.elements() {
+        match child.name.as_str() {
+            "r" | "fld" => {
+                if let Some(t) = child.child("t") {
+                    out.push_str(&t.text());
+                }
+            }
+            "br" => out.push(' '),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// A table from its DrawingML markup. Merged areas are written as one
+/// cell with `gridSpan`/`rowSpan`, followed by `hMerge`/`vMerge`
+/// placeholders that are skipped here.
+fn table_data(frame: &XmlNode, table: &XmlNode, bbox: Option<BoundingBox>) -> Option<TableData> {
+    let mut cells = Vec::new();
+    let mut rows = 0;
+    let mut cols = table.child("tblGrid").map_or(0, |g| g.children("gridCol").count());
+    for (row, tr) in table.children("tr").enumerate() {
+        rows = row + 1;
+        for (col, tc) in tr.children("tc").enumerate() {
+            cols = cols.max(col + 1);
+            if tc.attr("hMerge") == Some("1") || tc.attr("vMerge") == Some("1") {
+                continue;
+            }
+            let span = |name| tc.attr(name).and_then(|s| s.parse().ok()).unwrap_or(1usize).max(1);
+            let text = tc
+                .descendants("p")
+                .into_iter()
+                .map(paragraph_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string();
+            cells.push(Cell { row, col, row_span: span("rowSpan"), col_span: span("gridSpan"), text });
+        }
+    }
+
+    if rows == 0 {
+        return None;
+    }
+    let caption = frame
+        .path(&["nvGraphicFramePr", "cNvPr"])
+        .and_then(|p| p.attr("title").or(p.attr("descr")))
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    Some(TableData {
+        bbox,
+        caption,
+        ..table::table_data(rows, cols, cells)
+    })
+}
+
+/// Text of the notes placeholder on a notes slide.
+fn notes_text(notes: &XmlNode) -> Option<String> {
+    let body = notes.descendants("sp").into_iter().find(|sp| {
+        sp.path(&["nvSpPr", "nvPr", "ph"])
+            .is_some_and(|ph| ph.attr("type") == Some("body"))
+    })?;
+
+    let text = body
+        .descendants("p")
+        .into_iter()
+        .map(paragraph_text)
+        .filter(|t| !t.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!text.is_empty()).then_some(text)
+}
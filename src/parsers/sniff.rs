@@ -0,0 +1,73 @@
+// File format detection from content
+use super::ooxml::XmlNode;
+use std::io::{Read, Seek};
+
+/// How far into the file a PDF header may start; producers are allowed to
+/// put junk before it.
+const PDF_HEADER_WINDOW: usize = 1024;
+
+/// Identify a file from its leading bytes and, for zip containers, from the
+/// OOXML content types. Returns a canonical type such as `pdf`, `docx`,
+/// `pptx`, `xlsx`, `png`, `jpg`, `gif`, `webp` or `zip`.
+pub fn detect<R: Read + Seek>(mut reader: R) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(PDF_HEADER_WINDOW);
+    reader
+        .by_ref()
+        .take(PDF_HEADER_WINDOW as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        reader.rewind().ok()?;
+        return Some(ooxml_type(reader).unwrap_or("zip"));
+    }
+
+    match head.as_slice() {
+        [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, ..] => Some("png"),
+        [0xff, 0xd8, 0xff, ..] => Some("jpg"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("webp"),
+        _ if head.windows(5).any(|w| w == b"%PDF-") => Some("pdf"),
+        _ => None,
+    }
+}
+
+/// Distinguish Word, PowerPoint and Excel packages by the content type of
+/// their main part, falling back to well-known part names.
+fn ooxml_type<R: Read + Seek>(reader: R) -> Option<&'static str> {
+    let mut archive = zip::ZipArchive::new(reader).ok()?;
+
+    let mut xml = String::new();
+    if let Ok(mut part) = archive.by_name("[Content_Types].xml") {
+        part.read_to_string(&mut xml).ok()?;
+    }
+
+    // Covers documents, templates and macro-enabled variants.
+    let types = XmlNode::parse(&xml).unwrap_or_default();
+    let main_parts = types
+        .elements()
+        .filter_map(|t| t.attr("ContentType"))
+        .filter(|t| t.ends_with(".main+xml"));
+    for content_type in main_parts {
+        if content_type.contains("wordprocessingml") || content_type.contains("ms-word") {
+            return Some("docx");
+        }
+        if content_type.contains("presentationml") || content_type.contains("ms-powerpoint") {
+            return Some("pptx");
+        }
+        if content_type.contains("spreadsheetml") || content_type.contains("ms-excel") {
+            return Some("xlsx");
+        }
+    }
+
+    let has = |name: &str| archive.index_for_name(name).is_some();
+    if has("word/document.xml") {
+        Some("docx")
+    } else if has("ppt/presentation.xml") {
+        Some("pptx")
+    } else if has("xl/workbook.xml") {
+        Some("xlsx")
+    } else {
+        None
+    }
+}
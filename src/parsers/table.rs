@@ -0,0 +1,542 @@
+// Table detection from positioned text and ruling lines
+use super::cell_value;
+use crate::models::*;
+
+/// Tolerance, in points, for rules to meet or share a coordinate.
+const EPSILON: f32 = 1.5;
+
+/// Filled rectangles thinner than this are drawn rules, not boxes.
+const RULE_WIDTH: f32 = 2.0;
+
+/// Largest gap between the lines of an unruled table, in multiples of the
+/// median font size.
+const ROW_GAP_EM: f32 = 1.5;
+
+/// Narrowest gutter that separates the columns of an unruled table, in
+/// multiples of the median font size.
+const GUTTER_EM: f32 = 0.8;
+
+/// Mean cell length above which aligned text reads as prose columns.
+const MAX_CELL_CHARS: f32 = 40.0;
+
+/// Longest line above a table read as its caption rather than prose.
+const MAX_CAPTION_CHARS: usize = 80;
+
+/// Find tables on a page. Ruling lines that cross into a grid give rows,
+/// columns and merged cells directly; elsewhere, runs of lines whose text
+/// lines up into shared columns are read as unruled tables. The first row
+/// is the header, together with any rows under a header cell spanning
+/// several columns.
+pub fn detect(spans: &[TextSpan], paths: &[VectorPath]) -> Vec<TableData> {
+    let em = median_font_size(spans);
+    let mut tables = ruled_tables(spans, paths);
+
+    let free: Vec<&TextSpan> = spans
+        .iter()
+        .filter(|s| !tables.iter().any(|t| contains(&t.bbox, &center(&s.bbox))))
+        .collect();
+    tables.extend(aligned_tables(&free, em));
+
+    tables.sort_by(|a, b| a.bbox.y.total_cmp(&b.bbox.y).then(a.bbox.x.total_cmp(&b.bbox.x)));
+    let outside: Vec<&TextSpan> = free
+        .into_iter()
+        .filter(|s| !tables.iter().any(|t| contains(&t.bbox, &center(&s.bbox))))
+        .collect();
+    let captions: Vec<Option<String>> = tables.iter().map(|t| caption(&t.bbox, &outside, em)).collect();
+    tables
+        .into_iter()
+        .zip(captions)
+        .map(|(grid, caption)| TableData {
+            bbox: Some(grid.bbox),
+            caption,
+            ..table_data(grid.rows, grid.cols, grid.cells)
+        })
+        .collect()
+}
+
+/// A caption for a table: a line starting `Table` just below it, or else
+/// a short line just above it that lies within its width. `spans` are
+/// those outside every table.
+fn caption(bbox: &BoundingBox, spans: &[&TextSpan], em: f32) -> Option<String> {
+    let lines = lines(spans);
+    let text = |line: &[&TextSpan]| {
+        let mut text = String::new();
+        for span in line {
+            append(&mut text, &span.text);
+        }
+        text
+    };
+    let within = |b: &BoundingBox| b.x >= bbox.x - em && b.right() <= bbox.right() + em;
+
+    let below = lines.iter().find(|l| {
+        let b = line_box(l);
+        b.y >= bbox.bottom() - EPSILON && b.y - bbox.bottom() < em * ROW_GAP_EM && within(&b)
+    });
+    if let Some(line) = below {
+        let text = text(line);
+        if text.to_lowercase().starts_with("table") {
+            return Some(text);
+        }
+    }
+
+    let above = lines.iter().rev().find(|l| {
+        let b = line_box(l);
+        b.bottom() <= bbox.y + EPSILON && bbox.y - b.bottom() < em * ROW_GAP_EM && within(&b)
+    })?;
+    let text = text(above);
+    (text.chars().count() <= MAX_CAPTION_CHARS).then_some(text)
+}
+
+/// A table as a grid of cells, some spanning several rows or columns.
+struct Grid {
+    bbox: BoundingBox,
+    rows: usize,
+    cols: usize,
+    cells: Vec<Cell>,
+}
+
+/// A cell of a table being assembled, positioned at its top-left grid cell.
+pub struct Cell {
+    pub row: usize,
+    pub col: usize,
+    pub row_span: usize,
+    pub col_span: usize,
+    pub text: String,
+}
+
+/// Build a table from its cells. The first row is the header, together
+/// with each row under a heading that spans several columns. Merged cells
+/// keep their text in their top-left position; stacked headings are
+/// joined so a column reads e.g. `2024 Q1`. Page, position and caption
+/// are left for the caller.
+pub fn table_data(rows: usize, cols: usize, cells: Vec<Cell>) -> TableData {
+    let mut header_rows = rows.min(1);
+    while header_rows + 1 < rows
+        && cells
+            .iter()
+            .any(|c| c.row == header_rows - 1 && c.col_span > 1 && !c.text.is_empty())
+    {
+        header_rows += 1;
+    }
+
+    let mut headers = vec![String::new(); cols];
+    for cell in cells.iter().filter(|c| c.row < header_rows && !c.text.is_empty()) {
+        for heading in &mut headers[cell.col.min(cols)..(cell.col + cell.col_span).min(cols)] {
+            append(heading, &cell.text);
+        }
+    }
+
+    let mut body = vec![vec![String::new(); cols]; rows - header_rows];
+    for cell in cells.iter().filter(|c| c.row >= header_rows && c.row < rows && c.col < cols) {
+        body[cell.row - header_rows][cell.col] = cell.text.clone();
+    }
+
+    let cells = cells
+        .into_iter()
+        .map(|c| TableCell {
+            row: c.row,
+            col: c.col,
+            row_span: c.row_span,
+            col_span: c.col_span,
+            header: c.row < header_rows,
+            // The locale is not known yet; metrics re-read cells in it.
+            value: cell_value::parse(&c.text, None),
+            text: c.text,
+        })
+        .collect();
+
+    TableData {
+        rows: body,
+        headers,
+        cells,
+        header_rows,
+        page: 0,
+        bbox: None,
+        caption: None,
+    }
+}
+
+/// Axis-aligned rule: `pos` is the y of a horizontal rule or the x of a
+/// vertical one, spanning `from..to` along the other axis.
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    horizontal: bool,
+    pos: f32,
+    from: f32,
+    to: f32,
+}
+
+impl Rule {
+    fn covers(&self, at: f32) -> bool {
+        at >= self.from - EPSILON && at <= self.to + EPSILON
+    }
+
+    fn touches(&self, other: &Rule) -> bool {
+        if self.horizontal == other.horizontal {
+            (self.pos - other.pos).abs() < EPSILON
+                && self.from <= other.to + EPSILON
+                && other.from <= self.to + EPSILON
+        } else {
+            self.covers(other.pos) && other.covers(self.pos)
+        }
+    }
+}
+
+/// Horizontal and vertical rules: stroked segments, including the edges of
+/// stroked boxes, and hairline filled rectangles.
+fn rules(paths: &[VectorPath]) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    for path in paths {
+        for subpath in &path.subpaths {
+            if path.stroke.is_some() {
+                for pair in subpath.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                    if (y0 - y1).abs() < EPSILON && (x0 - x1).abs() >= EPSILON {
+                        rules.push(Rule { horizontal: true, pos: y0, from: x0.min(x1), to: x0.max(x1) });
+                    } else if (x0 - x1).abs() < EPSILON && (y0 - y1).abs() >= EPSILON {
+                        rules.push(Rule { horizontal: false, pos: x0, from: y0.min(y1), to: y0.max(y1) });
+                    }
+                }
+            } else if path.fill.is_some() {
+                let Some(b) = extent(subpath) else {
+                    continue;
+                };
+                if b.height <= RULE_WIDTH && b.width > RULE_WIDTH {
+                    rules.push(Rule { horizontal: true, pos: b.y + b.height / 2.0, from: b.x, to: b.right() });
+                } else if b.width <= RULE_WIDTH && b.height > RULE_WIDTH {
+                    rules.push(Rule { horizontal: false, pos: b.x + b.width / 2.0, from: b.y, to: b.bottom() });
+                }
+            }
+        }
+    }
+    rules
+}
+
+/// Grids formed by connected horizontal and vertical rules. Adjacent grid
+/// cells with no rule between them are merged.
+fn ruled_tables(spans: &[TextSpan], paths: &[VectorPath]) -> Vec<Grid> {
+    let rules = rules(paths);
+
+    // Connected components of touching rules. Sorted by position, a rule
+    // can only touch a parallel one within `EPSILON` after it, or a
+    // crossing one positioned along its extent.
+    let by_pos = |horizontal: bool| {
+        let mut ids: Vec<usize> = (0..rules.len()).filter(|&i| rules[i].horizontal == horizontal).collect();
+        ids.sort_by(|&a, &b| rules[a].pos.total_cmp(&rules[b].pos));
+        ids
+    };
+    let (across, down) = (by_pos(true), by_pos(false));
+    let mut component: Vec<usize> = (0..rules.len()).collect();
+    for ids in [&across, &down] {
+        for (n, &i) in ids.iter().enumerate() {
+            for &j in ids[n + 1..].iter().take_while(|&&j| rules[j].pos - rules[i].pos < EPSILON) {
+                if rules[i].touches(&rules[j]) {
+                    union(&mut component, i, j);
+                }
+            }
+        }
+    }
+    for &i in &across {
+        let first = down.partition_point(|&j| rules[j].pos < rules[i].from - EPSILON);
+        for &j in down[first..].iter().take_while(|&&j| rules[j].pos <= rules[i].to + EPSILON) {
+            if rules[i].touches(&rules[j]) {
+                union(&mut component, i, j);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<Rule>> = Vec::new();
+    let mut group_of: Vec<Option<usize>> = vec![None; rules.len()];
+    for (i, rule) in rules.iter().enumerate() {
+        let r = find(&mut component, i);
+        let g = *group_of[r].get_or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[g].push(*rule);
+    }
+
+    groups
+        .iter()
+        .filter_map(|group| grid(group, spans))
+        .collect()
+}
+
+fn grid(rules: &[Rule], spans: &[TextSpan]) -> Option<Grid> {
+    let ys = distinct(rules.iter().filter(|r| r.horizontal).map(|r| r.pos));
+    let xs = distinct(rules.iter().filter(|r| !r.horizontal).map(|r| r.pos));
+    if ys.len() < 3 || xs.len() < 3 {
+        return None;
+    }
+    let (rows, cols) = (ys.len() - 1, xs.len() - 1);
+
+    let ruled = |horizontal: bool, pos: f32, at: f32| {
+        rules
+            .iter()
+            .any(|r| r.horizontal == horizontal && (r.pos - pos).abs() < EPSILON && r.covers(at))
+    };
+
+    // Merge grid cells across missing rules.
+    let mut owner: Vec<usize> = (0..rows * cols).collect();
+    for r in 0..rows {
+        for c in 0..cols {
+            let mid_y = (ys[r] + ys[r + 1]) / 2.0;
+            let mid_x = (xs[c] + xs[c + 1]) / 2.0;
+            if c + 1 < cols && !ruled(false, xs[c + 1], mid_y) {
+                union(&mut owner, r * cols + c, r * cols + c + 1);
+            }
+            if r + 1 < rows && !ruled(true, ys[r + 1], mid_x) {
+                union(&mut owner, r * cols + c, (r + 1) * cols + c);
+            }
+        }
+    }
+
+    let mut cells: Vec<Cell> = Vec::new();
+    let mut cell_of = vec![0; rows * cols];
+    for i in 0..rows * cols {
+        let (r, c) = (i / cols, i % cols);
+        let o = find(&mut owner, i);
+        if o == i {
+            cell_of[i] = cells.len();
+            cells.push(Cell { row: r, col: c, row_span: 1, col_span: 1, text: String::new() });
+        } else {
+            // A merged cell is owned by its top-left grid cell: extend its span.
+            let cell = &mut cells[cell_of[o]];
+            cell_of[i] = cell_of[o];
+            cell.row_span = cell.row_span.max(r - cell.row + 1);
+            cell.col_span = cell.col_span.max(c + 1 - cell.col);
+        }
+    }
+
+    let bbox = BoundingBox::new(xs[0], ys[0], xs[cols] - xs[0], ys[rows] - ys[0]);
+    let mut placed: Vec<(usize, &TextSpan)> = spans
+        .iter()
+        .filter_map(|span| {
+            let (x, y) = center(&span.bbox);
+            let r = ys.windows(2).position(|w| y >= w[0] && y < w[1])?;
+            let c = xs.windows(2).position(|w| x >= w[0] && x < w[1])?;
+            Some((cell_of[r * cols + c], span))
+        })
+        .collect();
+    if placed.is_empty() {
+        return None;
+    }
+    placed.sort_by(|a, b| a.1.bbox.y.total_cmp(&b.1.bbox.y).then(a.1.bbox.x.total_cmp(&b.1.bbox.x)));
+    for (cell, span) in placed {
+        append(&mut cells[cell].text, &span.text);
+    }
+    // Chart gridlines also cross into a lattice, but leave it mostly empty.
+    let filled = cells.iter().filter(|c| !c.text.is_empty()).count();
+    if filled * 2 < cells.len() {
+        return None;
+    }
+
+    Some(Grid { bbox, rows, cols, cells })
+}
+
+/// Unruled tables: runs of closely spaced lines that each hold several
+/// spans, whose spans fall into columns separated by clear gutters.
+fn aligned_tables(spans: &[&TextSpan], em: f32) -> Vec<Grid> {
+    let lines = lines(spans);
+
+    let mut tables = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start;
+        while end < lines.len() && lines[end].len() >= 2 {
+            let follows = end == start
+                || line_box(&lines[end]).y - line_box(&lines[end - 1]).bottom() < em * ROW_GAP_EM;
+            if !follows {
+                break;
+            }
+            end += 1;
+        }
+        if end - start >= 3 {
+            tables.extend(aligned_table(&lines[start..end], em));
+        }
+        start = end.max(start + 1);
+    }
+    tables
+}
+
+fn aligned_table(lines: &[Vec<&TextSpan>], em: f32) -> Option<Grid> {
+    // Columns come from the fullest lines, so a heading centred over two
+    // columns does not bridge the gutter between them.
+    let mut counts: Vec<usize> = lines.iter().map(|l| l.len()).collect();
+    counts.sort_unstable();
+    let typical = counts[counts.len() / 2];
+    let mut extents: Vec<(f32, f32)> = lines
+        .iter()
+        .filter(|l| l.len() >= typical)
+        .flat_map(|l| l.iter().map(|s| (s.bbox.x, s.bbox.right())))
+        .collect();
+    extents.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut columns: Vec<(f32, f32)> = Vec::new();
+    for (from, to) in extents {
+        match columns.last_mut() {
+            Some(last) if from < last.1 + em * GUTTER_EM => last.1 = last.1.max(to),
+            _ => columns.push((from, to)),
+        }
+    }
+    let cols = columns.len();
+    if cols < 2 {
+        return None;
+    }
+
+    let mut cells: Vec<Cell> = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        let mut row_cells: Vec<Cell> = Vec::new();
+        for span in line {
+            let overlapping: Vec<usize> = (0..cols)
+                .filter(|&c| span.bbox.x < columns[c].1 && span.bbox.right() > columns[c].0)
+                .collect();
+            let (first, last) = match (overlapping.first(), overlapping.last()) {
+                (Some(&first), Some(&last)) => (first, last),
+                _ => {
+                    let nearest = (0..cols).min_by(|&a, &b| {
+                        gap(span, columns[a]).total_cmp(&gap(span, columns[b]))
+                    })?;
+                    (nearest, nearest)
+                }
+            };
+            match row_cells.iter_mut().find(|c| c.col <= last && first < c.col + c.col_span) {
+                Some(cell) => {
+                    let end = (cell.col + cell.col_span).max(last + 1);
+                    cell.col = cell.col.min(first);
+                    cell.col_span = end - cell.col;
+                    append(&mut cell.text, &span.text);
+                }
+                None => row_cells.push(Cell {
+                    row,
+                    col: first,
+                    row_span: 1,
+                    col_span: last + 1 - first,
+                    text: span.text.trim().to_string(),
+                }),
+            }
+        }
+        cells.extend(row_cells);
+    }
+
+    // Columns of long text are prose laid out side by side.
+    let chars = cells.iter().map(|c| c.text.chars().count()).sum::<usize>() as f32;
+    if chars / cells.len() as f32 > MAX_CELL_CHARS {
+        return None;
+    }
+    // Two columns of words are as likely a split bullet list as a table;
+    // ask for figures in the body.
+    if cols == 2 {
+        let numeric_rows = (1..lines.len())
+            .filter(|&r| cells.iter().any(|c| c.row == r && is_numeric(&c.text)))
+            .count();
+        if numeric_rows * 2 < lines.len() - 1 {
+            return None;
+        }
+    }
+
+    let bbox = lines
+        .iter()
+        .map(|l| line_box(l))
+        .reduce(|a, b| a.union(&b))?;
+    Some(Grid { bbox, rows: lines.len(), cols, cells })
+}
+
+/// Group spans into visual lines, top to bottom, each sorted left to right.
+fn lines<'a>(spans: &[&'a TextSpan]) -> Vec<Vec<&'a TextSpan>> {
+    let mut sorted: Vec<&TextSpan> = spans.to_vec();
+    sorted.sort_by(|a, b| a.bbox.y.total_cmp(&b.bbox.y));
+
+    let mut lines: Vec<Vec<&TextSpan>> = Vec::new();
+    for span in sorted {
+        let joins = lines.last().is_some_and(|line| {
+            let b = line_box(line);
+            let overlap = b.bottom().min(span.bbox.bottom()) - b.y.max(span.bbox.y);
+            overlap > b.height.min(span.bbox.height) * 0.5
+        });
+        match lines.last_mut() {
+            Some(line) if joins => line.push(span),
+            _ => lines.push(vec![span]),
+        }
+    }
+    for line in &mut lines {
+        line.sort_by(|a, b| a.bbox.x.total_cmp(&b.bbox.x));
+    }
+    lines
+}
+
+fn line_box(line: &[&TextSpan]) -> BoundingBox {
+    line[1..].iter().fold(line[0].bbox, |b, s| b.union(&s.bbox))
+}
+
+/// Horizontal distance from a span to a column.
+fn gap(span: &TextSpan, column: (f32, f32)) -> f32 {
+    (column.0 - span.bbox.right()).max(span.bbox.x - column.1).max(0.0)
+}
+
+/// Whether a cell holds a figure such as `$2.4M`, `35%` or `1,200`.
+fn is_numeric(text: &str) -> bool {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).count();
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    digits > 0 && digits >= letters
+}
+
+/// Union-find root of `i`, with the smallest index as each set's root.
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    parent[a.max(b)] = a.min(b);
+}
+
+fn append(text: &mut String, more: &str) {
+    let more = more.trim();
+    if more.is_empty() {
+        return;
+    }
+    if !text.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(more);
+}
+
+fn extent(points: &[(f32, f32)]) -> Option<BoundingBox> {
+    let (first, rest) = points.split_first()?;
+    let b = rest.iter().fold(BoundingBox::new(first.0, first.1, 0.0, 0.0), |b, p| {
+        b.union(&BoundingBox::new(p.0, p.1, 0.0, 0.0))
+    });
+    Some(b)
+}
+
+fn center(b: &BoundingBox) -> (f32, f32) {
+    (b.x + b.width / 2.0, b.y + b.height / 2.0)
+}
+
+fn contains(b: &BoundingBox, (x, y): &(f32, f32)) -> bool {
+    *x >= b.x && *x <= b.right() && *y >= b.y && *y <= b.bottom()
+}
+
+/// Sorted values with near-duplicates removed.
+fn distinct(values: impl Iterator<Item = f32>) -> Vec<f32> {
+    let mut sorted: Vec<f32> = values.collect();
+    sorted.sort_by(f32::total_cmp);
+    let mut out: Vec<f32> = Vec::new();
+    for v in sorted {
+        if out.last().is_none_or(|last| v - last >= EPSILON) {
+            out.push(v);
+        }
+    }
+    out
+}
+
+fn median_font_size(spans: &[TextSpan]) -> f32 {
+    let mut sizes: Vec<f32> = spans.iter().map(|s| s.font_size).collect();
+    sizes.sort_by(f32::total_cmp);
+    sizes.get(sizes.len() / 2).copied().unwrap_or(10.0).max(1.0)
+}
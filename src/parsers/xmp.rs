@@ -0,0 +1,80 @@
+// XMP metadata packets
+use super::ooxml::{split_keywords, XmlNode};
+
+/// Descriptive properties of a document, as found in an XMP packet or a
+/// PDF Info dictionary.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub creator_tool: Option<String>,
+    pub producer: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub keywords: Vec<String>,
+}
+
+impl DocumentInfo {
+    /// Read the Dublin Core, XMP basic and PDF schema properties. Each may be
+    /// written as an attribute of `rdf:Description` or as a child element,
+    /// and a packet may split them across several descriptions.
+    pub fn from_xmp(xml: &str) -> Option<Self> {
+        let root = XmlNode::parse(xml.trim_start_matches('\u{feff}'))?;
+        let descriptions = if root.name == "Description" {
+            vec![&root]
+        } else {
+            root.descendants("Description")
+        };
+
+        let property = |name: &str| {
+            descriptions.iter().find_map(|d| {
+                let value = match d.attr(name) {
+                    Some(value) => value.to_string(),
+                    None => {
+                        let node = d.child(name)?;
+                        // Language alternatives and sequences keep values in `rdf:li`.
+                        match node.descendants("li").first() {
+                            Some(li) => li.text(),
+                            None => node.text(),
+                        }
+                    }
+                };
+                Some(value.trim().to_string()).filter(|v| !v.is_empty())
+            })
+        };
+
+        let mut keywords: Vec<String> = descriptions
+            .iter()
+            .filter_map(|d| d.child("subject"))
+            .flat_map(|s| s.descendants("li"))
+            .map(|li| li.text().trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if keywords.is_empty() {
+            keywords = property("Keywords").map(|k| split_keywords(&k)).unwrap_or_default();
+        }
+
+        Some(Self {
+            title: property("title"),
+            author: property("creator"),
+            creator_tool: property("CreatorTool"),
+            producer: property("Producer"),
+            created: property("CreateDate"),
+            modified: property("ModifyDate"),
+            keywords,
+        })
+    }
+
+    /// Fill properties missing from `self` with those of `other`.
+    pub fn or(self, other: DocumentInfo) -> DocumentInfo {
+        DocumentInfo {
+            title: self.title.or(other.title),
+            author: self.author.or(other.author),
+            creator_tool: self.creator_tool.or(other.creator_tool),
+            producer: self.producer.or(other.producer),
+            created: self.created.or(other.created),
+            modified: self.modified.or(other.modified),
+            keywords: if self.keywords.is_empty() { other.keywords } else { self.keywords },
+        }
+    }
+}
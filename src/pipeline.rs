@@ -0,0 +1,104 @@
+// Staged processing of extracted documents
+use crate::{error::*, extractors, models::*, processors, risk};
+use std::sync::Arc;
+
+/// A document on its way through a `Pipeline`: the parser's output, which
+/// stages may enrich, and the analysis built up so far.
+#[derive(Debug, Clone)]
+pub struct DocumentContext {
+    pub extracted: ExtractedContent,
+    pub processed: ProcessedData,
+    /// Filled by the risk stage, most severe first.
+    pub risk_flags: Vec<RiskFlag>,
+}
+
+impl DocumentContext {
+    pub fn new(extracted: ExtractedContent) -> Self {
+        Self {
+            extracted,
+            processed: ProcessedData::default(),
+            risk_flags: vec![],
+        }
+    }
+}
+
+/// One step of document analysis. Stages run in order, each reading what
+/// earlier stages left in the context and adding its own results.
+/// Stages outside this crate can record results under
+/// `ProcessedData::extensions`.
+pub trait Stage: Send + Sync {
+    /// Short identifier, used in logs and to place other stages.
+    fn name(&self) -> &str;
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()>;
+}
+
+/// An ordered list of stages. The default pipeline classifies text blocks,
+/// detects sections and language, reads metrics and market-size claims,
+/// collects charts and visual elements, then raises risk flags. Stages
+/// that feed the risk rules, like verification, go before `risk`. Clones
+/// share their stages.
+#[derive(Clone)]
+pub struct Pipeline {
+    stages: Vec<Arc<dyn Stage>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+            .with_stage(extractors::TextBlockStage)
+            .with_stage(extractors::StructureStage)
+            .with_stage(extractors::MetricStage)
+            .with_stage(extractors::MarketStage)
+            .with_stage(processors::ChartStage::new())
+            .with_stage(processors::VisualElementStage)
+            .with_stage(risk::RiskStage::default())
+    }
+}
+
+impl Pipeline {
+    /// A pipeline with no stages.
+    pub fn new() -> Self {
+        Self { stages: vec![] }
+    }
+
+    /// Append `stage` to the end of the pipeline.
+    pub fn with_stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(Arc::new(stage));
+        self
+    }
+
+    /// Insert `stage` right after the stage called `name`, or at the end
+    /// when there is none.
+    pub fn with_stage_after(mut self, name: &str, stage: impl Stage + 'static) -> Self {
+        let at = self
+            .stages
+            .iter()
+            .position(|s| s.name() == name)
+            .map_or(self.stages.len(), |i| i + 1);
+        self.stages.insert(at, Arc::new(stage));
+        self
+    }
+
+    /// Drop the stage called `name`, e.g. to replace it with another.
+    pub fn without_stage(mut self, name: &str) -> Self {
+        self.stages.retain(|s| s.name() != name);
+        self
+    }
+
+    /// Names of the stages, in the order they run.
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Run every stage over `extracted`, stopping at the first error.
+    pub fn run(&self, extracted: ExtractedContent) -> Result<DocumentContext> {
+        let mut ctx = DocumentContext::new(extracted);
+        for stage in &self.stages {
+            let start = std::time::Instant::now();
+            stage.run(&mut ctx)?;
+            tracing::debug!("stage {} took {:?}", stage.name(), start.elapsed());
+        }
+        Ok(ctx)
+    }
+}
@@ -1,35 +1,112 @@
-use crate::{models::*, error::*};
-
-pub fn process(content: &ExtractedContent) -> Result<ProcessedData> {
-    // Process extracted content
-    let text_blocks = vec![
-        TextBlock {
-            content: content.text.clone(),
-            block_type: "content".to_string(),
-            confidence: 0.95,
-        },
-    ];
-
-    let visual_elements = content
-        .images
+use crate::{models::*, error::*, ocr::OcrEngine, pipeline::*};
+use std::sync::Arc;
+
+mod raster_chart;
+mod vector_chart;
+
+/// Run the default pipeline over `content`.
+pub fn process(content: ExtractedContent) -> Result<ProcessedData> {
+    Ok(Pipeline::default().run(content)?.processed)
+}
+
+/// Collects charts: embedded chart data, and charts recovered from vector
+/// drawings and raster images. With an OCR engine, the tick and category
+/// labels of raster charts are read so their values come out on the
+/// chart's own scale rather than relative to its largest mark.
+#[derive(Default)]
+pub struct ChartStage {
+    ocr: Option<Arc<dyn OcrEngine>>,
+}
+
+impl ChartStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read raster chart labels with `engine`.
+    pub fn with_ocr(mut self, engine: impl OcrEngine + 'static) -> Self {
+        self.ocr = Some(Arc::new(engine));
+        self
+    }
+}
+
+impl Stage for ChartStage {
+    fn name(&self) -> &str {
+        "charts"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        ctx.processed.charts = ctx
+            .extracted
+            .pages
+            .iter()
+            .flat_map(|page| {
+                let embedded = page.charts.iter().cloned().map(|chart| ("embedded", chart));
+                let vector = vector_chart::detect(page).into_iter().map(|chart| ("vector", chart));
+                let raster = page
+                    .images
+                    .iter()
+                    .flat_map(|image| raster_chart::detect(image, self.ocr.as_deref()))
+                    .map(|chart| ("raster", chart));
+                embedded
+                    .chain(vector)
+                    .chain(raster)
+                    .map(|(source, chart)| chart_element(page.number, source, chart))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+/// Lists images and the charts found by `ChartStage` as visual elements.
+pub struct VisualElementStage;
+
+impl Stage for VisualElementStage {
+    fn name(&self) -> &str {
+        "visual_elements"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        ctx.processed.visual_elements = ctx
+            .extracted
+            .pages
+            .iter()
+            .flat_map(|page| {
+                page.images.iter().map(|img| VisualElement {
+                    element_type: "image".to_string(),
+                    page: page.number,
+                    bbox: img.bbox,
+                })
+            })
+            .chain(ctx.processed.charts.iter().map(|c| VisualElement {
+                element_type: "chart".to_string(),
+                page: c.page,
+                bbox: c.chart.bbox,
+            }))
+            .collect();
+        Ok(())
+    }
+}
+
+fn chart_element(page: u32, source: &str, chart: ChartData) -> ChartElement {
+    let scores: Vec<f32> = chart
+        .series
         .iter()
-        .enumerate()
-        .map(|(idx, img)| VisualElement {
-            element_type: "image".to_string(),
-            position: (0, idx as u32 * 100),
-            size: (img.width, img.height),
-        })
+        .flat_map(|s| s.values.iter().zip(&s.confidence))
+        .filter(|(v, _)| v.is_some())
+        .map(|(_, c)| *c)
         .collect();
-
-    let structure = DocumentStructure {
-        sections: vec![],
-        total_pages: content.metadata.pages.unwrap_or(1),
-        language: None,
+    let confidence = if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().sum::<f32>() / scores.len() as f32
     };
 
-    Ok(ProcessedData {
-        text_blocks,
-        visual_elements,
-        structure,
-    })
+    ChartElement {
+        page,
+        source: source.to_string(),
+        chart,
+        confidence,
+    }
 }
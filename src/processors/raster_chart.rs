@@ -0,0 +1,773 @@
+// Chart digitization from bitmaps
+use crate::{models::*, normalize, ocr::*};
+use image::imageops::FilterType;
+use image::RgbImage;
+
+/// Longest side, in pixels, an image is reduced to before analysis.
+const MAX_SIDE: u32 = 1200;
+
+/// Charts are drawn on a flat background; photos are not.
+const MIN_BACKGROUND_SHARE: f32 = 0.4;
+
+/// Minimum channel spread for a colour to count as a series colour rather
+/// than text, axes or gridlines.
+const MIN_CHROMA: u8 = 48;
+
+/// Most series colours considered per image.
+const MAX_SERIES: usize = 8;
+
+/// Tolerance, in pixels, for bars to share a baseline.
+const BASELINE_TOLERANCE: u32 = 2;
+
+/// Pixel geometry only gives values relative to the largest mark.
+const BAR_CONFIDENCE: f32 = 0.35;
+const LINE_CONFIDENCE: f32 = 0.25;
+
+/// Added to the confidence of values read against OCR'd tick labels.
+const CALIBRATED_BOOST: f32 = 0.2;
+
+/// Largest error, as a share of the labelled range, of a tick label off the
+/// straight line through the others before the axis is not trusted.
+const MAX_TICK_ERROR: f64 = 0.02;
+
+/// Detect column, bar and line charts in a bitmap by clustering its colours,
+/// and read approximate values off the geometry.
+///
+/// With an OCR engine, numeric tick labels beside the value axis give the
+/// pixel-to-value scale and labels under (or, for bars, beside) the
+/// category axis name the categories. Without one, or when the ticks cannot
+/// be read, values are relative: fractions of the largest mark for bars and
+/// of the axis height for lines, flagged by a `relative` axis scale.
+pub fn detect(image: &ImageData, ocr: Option<&dyn OcrEngine>) -> Vec<ChartData> {
+    let Ok(decoded) = image::load_from_memory(&image.data) else {
+        return vec![];
+    };
+    let mut rgb = decoded.to_rgb8();
+    let (full_w, full_h) = rgb.dimensions();
+    if full_w.max(full_h) > MAX_SIDE {
+        let ratio = MAX_SIDE as f32 / full_w.max(full_h) as f32;
+        let (w, h) = (
+            (full_w as f32 * ratio) as u32,
+            (full_h as f32 * ratio) as u32,
+        );
+        // Nearest neighbour keeps flat colours flat.
+        rgb = image::imageops::resize(&rgb, w.max(1), h.max(1), FilterType::Nearest);
+    }
+
+    let Some(raster) = Raster::new(rgb) else {
+        return vec![];
+    };
+
+    // Map analysis pixels back to the image's placement on the page.
+    let frame = image
+        .bbox
+        .unwrap_or(BoundingBox::new(0.0, 0.0, full_w as f32, full_h as f32));
+    let sx = frame.width / raster.width as f32;
+    let sy = frame.height / raster.height as f32;
+    let to_page = |b: Box| {
+        BoundingBox::new(
+            frame.x + b.x0 as f32 * sx,
+            frame.y + b.y0 as f32 * sy,
+            (b.x1 - b.x0 + 1) as f32 * sx,
+            (b.y1 - b.y0 + 1) as f32 * sy,
+        )
+    };
+
+    let mut bars: Vec<Mark> = Vec::new();
+    let mut strokes: Vec<Mark> = Vec::new();
+    for (series, color) in raster.palette.iter().enumerate() {
+        for mark in raster.components(color, series) {
+            if mark.fill() >= 0.85 && mark.bbox.width() >= 3 && mark.bbox.height() >= 3 {
+                bars.push(mark);
+            } else if mark.fill() < 0.35 && mark.bbox.width() >= raster.width / 20 {
+                strokes.push(mark);
+            }
+        }
+    }
+
+    // Labels around the plot, in analysis pixels.
+    let labels: Vec<OcrWord> = match ocr {
+        Some(engine) if !bars.is_empty() || !strokes.is_empty() => match engine.recognize(&image.data) {
+            Ok(words) => {
+                let (rx, ry) = (raster.width as f32 / full_w as f32, raster.height as f32 / full_h as f32);
+                words
+                    .into_iter()
+                    .map(|w| OcrWord {
+                        bbox: BoundingBox::new(w.bbox.x * rx, w.bbox.y * ry, w.bbox.width * rx, w.bbox.height * ry),
+                        ..w
+                    })
+                    .collect()
+            }
+            Err(e) => {
+                tracing::warn!("OCR of chart labels failed for {}: {}", image.id, e);
+                vec![]
+            }
+        },
+        _ => vec![],
+    };
+
+    let axis = raster.x_axis();
+    let mut charts = Vec::new();
+    for vertical in [true, false] {
+        for group in bar_groups(&bars, vertical) {
+            if let Some(mut chart) = bar_chart(&group, vertical, axis.is_some(), &labels) {
+                chart.bbox = Some(to_page(union(group.iter().map(|m| m.bbox))));
+                charts.push(chart);
+            }
+        }
+    }
+    if let Some((mut chart, plot)) = line_chart(&raster, &strokes, axis, &labels) {
+        chart.bbox = Some(to_page(plot));
+        charts.push(chart);
+    }
+    charts
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Box {
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+impl Box {
+    fn width(&self) -> u32 {
+        self.x1 - self.x0 + 1
+    }
+
+    fn height(&self) -> u32 {
+        self.y1 - self.y0 + 1
+    }
+}
+
+fn union(boxes: impl Iterator<Item = Box>) -> Box {
+    boxes
+        .reduce(|a, b| Box {
+            x0: a.x0.min(b.x0),
+            y0: a.y0.min(b.y0),
+            x1: a.x1.max(b.x1),
+            y1: a.y1.max(b.y1),
+        })
+        .unwrap_or(Box {
+            x0: 0,
+            y0: 0,
+            x1: 0,
+            y1: 0,
+        })
+}
+
+/// A connected blob of one series colour.
+#[derive(Debug, Clone)]
+struct Mark {
+    bbox: Box,
+    pixels: u32,
+    series: usize,
+    /// Mean row of the blob's pixels in each column, for line tracing.
+    columns: Vec<(u32, f32)>,
+}
+
+impl Mark {
+    fn fill(&self) -> f32 {
+        self.pixels as f32 / (self.bbox.width() * self.bbox.height()) as f32
+    }
+}
+
+/// Colour-quantized image: 16 levels per channel.
+struct Raster {
+    width: u32,
+    height: u32,
+    buckets: Vec<u16>,
+    /// Dark neutral pixels: axes, ticks and text.
+    ink: Vec<bool>,
+    /// Series colours, each a set of neighbouring buckets, most common first.
+    palette: Vec<Vec<u16>>,
+}
+
+impl Raster {
+    fn new(rgb: RgbImage) -> Option<Self> {
+        let (width, height) = rgb.dimensions();
+        let total = (width * height) as usize;
+        if total == 0 {
+            return None;
+        }
+
+        let buckets: Vec<u16> = rgb
+            .pixels()
+            .map(|p| ((p[0] as u16 >> 4) << 8) | ((p[1] as u16 >> 4) << 4) | (p[2] as u16 >> 4))
+            .collect();
+        let mut counts = vec![0usize; 4096];
+        for &b in &buckets {
+            counts[b as usize] += 1;
+        }
+
+        let background = (0..4096u16).max_by_key(|&b| counts[b as usize])?;
+        if (counts[background as usize] as f32) < total as f32 * MIN_BACKGROUND_SHARE {
+            return None;
+        }
+
+        let ink = rgb
+            .pixels()
+            .map(|p| {
+                let (lo, hi) = (p.0.iter().min().unwrap(), p.0.iter().max().unwrap());
+                hi - lo < MIN_CHROMA && (*hi as u16) < 160
+            })
+            .collect();
+
+        let min_count = (total / 500).max(30);
+        let mut candidates: Vec<u16> = (0..4096u16)
+            .filter(|&b| {
+                b != background && counts[b as usize] >= min_count && chroma(b) >= MIN_CHROMA
+            })
+            .collect();
+        candidates.sort_by_key(|&b| std::cmp::Reverse(counts[b as usize]));
+
+        // Anti-aliasing and compression spread one colour over neighbouring buckets.
+        let mut palette: Vec<Vec<u16>> = Vec::new();
+        for bucket in candidates {
+            if let Some(color) = palette.iter_mut().find(|c| distance(c[0], bucket) <= 32) {
+                color.push(bucket);
+            } else if palette.len() < MAX_SERIES {
+                palette.push(vec![bucket]);
+            }
+        }
+
+        Some(Self {
+            width,
+            height,
+            buckets,
+            ink,
+            palette,
+        })
+    }
+
+    /// Connected components (4-neighbour) of one palette colour.
+    fn components(&self, color: &[u16], series: usize) -> Vec<Mark> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let member: Vec<bool> = self.buckets.iter().map(|b| color.contains(b)).collect();
+        let mut seen = vec![false; member.len()];
+        let mut marks = Vec::new();
+
+        for start in 0..member.len() {
+            if !member[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut stack = vec![start];
+            let mut pixels: Vec<usize> = Vec::new();
+            while let Some(i) = stack.pop() {
+                pixels.push(i);
+                let (x, y) = (i % w, i / w);
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < w).then(|| i + 1),
+                    (y > 0).then(|| i - w),
+                    (y + 1 < h).then(|| i + w),
+                ];
+                for n in neighbours.into_iter().flatten() {
+                    if member[n] && !seen[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            if pixels.len() < 12 {
+                continue;
+            }
+
+            let bbox = union(pixels.iter().map(|&i| {
+                let (x, y) = ((i % w) as u32, (i / w) as u32);
+                Box {
+                    x0: x,
+                    y0: y,
+                    x1: x,
+                    y1: y,
+                }
+            }));
+            let mut sums = vec![(0u64, 0u32); bbox.width() as usize];
+            for &i in &pixels {
+                let col = &mut sums[(i % w) - bbox.x0 as usize];
+                col.0 += (i / w) as u64;
+                col.1 += 1;
+            }
+            let columns = sums
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, n))| *n > 0)
+                .map(|(x, (sum, n))| (bbox.x0 + x as u32, *sum as f32 / *n as f32))
+                .collect();
+
+            marks.push(Mark {
+                bbox,
+                pixels: pixels.len() as u32,
+                series,
+                columns,
+            });
+        }
+        marks
+    }
+
+    /// Lowest row with a dark run across half the image: the category axis.
+    fn x_axis(&self) -> Option<u32> {
+        let w = self.width as usize;
+        (0..self.height).rev().find(|&y| {
+            let row = &self.ink[y as usize * w..(y as usize + 1) * w];
+            let mut best = 0;
+            let mut run = 0;
+            for &dark in row {
+                run = if dark { run + 1 } else { 0 };
+                best = best.max(run);
+            }
+            best * 2 >= w
+        })
+    }
+}
+
+fn center(bucket: u16) -> [u8; 3] {
+    [
+        (((bucket >> 8) & 0xf) << 4) as u8 + 8,
+        (((bucket >> 4) & 0xf) << 4) as u8 + 8,
+        ((bucket & 0xf) << 4) as u8 + 8,
+    ]
+}
+
+fn chroma(bucket: u16) -> u8 {
+    let c = center(bucket);
+    c.iter().max().unwrap() - c.iter().min().unwrap()
+}
+
+fn distance(a: u16, b: u16) -> u8 {
+    let (a, b) = (center(a), center(b));
+    (0..3).map(|i| a[i].abs_diff(b[i])).max().unwrap()
+}
+
+/// Category-axis extent, shared edge and length of a bar.
+fn bar_geometry(b: &Box, vertical: bool) -> ((u32, u32), u32, u32) {
+    if vertical {
+        ((b.x0, b.x1), b.y1, b.height())
+    } else {
+        ((b.y0, b.y1), b.x0, b.width())
+    }
+}
+
+/// Runs of bars on one baseline with similar thickness and varying length.
+fn bar_groups(bars: &[Mark], vertical: bool) -> Vec<Vec<Mark>> {
+    let mut sorted: Vec<&Mark> = bars.iter().collect();
+    sorted.sort_by_key(|m| {
+        let (span, base, _) = bar_geometry(&m.bbox, vertical);
+        (base, span.0)
+    });
+
+    let mut baselines: Vec<Vec<&Mark>> = Vec::new();
+    for bar in sorted {
+        let base = bar_geometry(&bar.bbox, vertical).1;
+        match baselines.last_mut() {
+            Some(group)
+                if base.abs_diff(bar_geometry(&group[0].bbox, vertical).1)
+                    <= BASELINE_TOLERANCE =>
+            {
+                group.push(bar)
+            }
+            _ => baselines.push(vec![bar]),
+        }
+    }
+
+    baselines
+        .into_iter()
+        .map(|mut group| {
+            group.sort_by_key(|m| bar_geometry(&m.bbox, vertical).0 .0);
+            group.into_iter().cloned().collect::<Vec<_>>()
+        })
+        .filter(|group| {
+            if group.len() < 2 {
+                return false;
+            }
+            let geometry: Vec<_> = group
+                .iter()
+                .map(|m| bar_geometry(&m.bbox, vertical))
+                .collect();
+            let thickness: Vec<u32> = geometry.iter().map(|(s, _, _)| s.1 - s.0 + 1).collect();
+            let lengths: Vec<u32> = geometry.iter().map(|(_, _, l)| *l).collect();
+            let overlapping = geometry.windows(2).any(|w| w[1].0 .0 <= w[0].0 .1);
+            let (t_min, t_max) = (
+                *thickness.iter().min().unwrap(),
+                *thickness.iter().max().unwrap(),
+            );
+            let (l_min, l_max) = (
+                *lengths.iter().min().unwrap(),
+                *lengths.iter().max().unwrap(),
+            );
+            !overlapping && t_max * 2 <= t_min * 3 && (l_max - l_min) * 20 > l_max
+        })
+        .collect()
+}
+
+/// Read a group of bars as a chart, or `None` when nothing but the marks
+/// themselves says it is one: logos, icons and buttons are solid blocks
+/// too, but come with no axis line, tick labels or category labels.
+fn bar_chart(bars: &[Mark], vertical: bool, has_axis: bool, labels: &[OcrWord]) -> Option<ChartData> {
+    let thickness = {
+        let mut t: Vec<u32> = bars
+            .iter()
+            .map(|m| bar_geometry(&m.bbox, vertical).0)
+            .map(|s| s.1 - s.0 + 1)
+            .collect();
+        t.sort_unstable();
+        t[t.len() / 2]
+    };
+    let longest = bars
+        .iter()
+        .map(|m| bar_geometry(&m.bbox, vertical).2)
+        .max()
+        .unwrap_or(1) as f64;
+
+    // Adjacent bars in distinct colours share a category.
+    let mut clusters: Vec<Vec<&Mark>> = Vec::new();
+    for bar in bars {
+        let starts_new = clusters.last().is_none_or(|cluster| {
+            let prev = bar_geometry(&cluster[cluster.len() - 1].bbox, vertical).0;
+            let gap = bar_geometry(&bar.bbox, vertical).0 .0 as f32 - prev.1 as f32;
+            gap > thickness as f32 * 0.35 || cluster.iter().any(|m| m.series == bar.series)
+        });
+        if starts_new {
+            clusters.push(vec![bar]);
+        } else {
+            clusters.last_mut().unwrap().push(bar);
+        }
+    }
+
+    let mut order: Vec<usize> = Vec::new();
+    for bar in bars {
+        if !order.contains(&bar.series) {
+            order.push(bar.series);
+        }
+    }
+
+    let plot = union(bars.iter().map(|m| m.bbox));
+    let scale = Scale::fit(labels, plot, vertical);
+    let mut confidence = if has_axis {
+        BAR_CONFIDENCE
+    } else {
+        BAR_CONFIDENCE * 0.7
+    };
+    if scale.is_some() {
+        confidence += CALIBRATED_BOOST;
+    }
+    // The far end of a bar, where its value is read off the axis.
+    let value = |m: &Mark| match &scale {
+        Some(scale) if vertical => scale.at(m.bbox.y0 as f32),
+        Some(scale) => scale.at((m.bbox.x1 + 1) as f32),
+        None => bar_geometry(&m.bbox, vertical).2 as f64 / longest,
+    };
+    let series = order
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| {
+            let (values, confidence) = clusters
+                .iter()
+                .map(|cluster| match cluster.iter().find(|m| m.series == s) {
+                    Some(m) => (Some(value(m)), confidence),
+                    None => (None, 0.0),
+                })
+                .unzip();
+            ChartSeries {
+                name: format!("Series {}", i + 1),
+                values,
+                confidence,
+            }
+        })
+        .collect();
+
+    // Category labels are looked for within half a bar of each cluster.
+    let spans: Vec<(f32, f32)> = clusters
+        .iter()
+        .map(|cluster| {
+            let first = bar_geometry(&cluster[0].bbox, vertical).0;
+            let last = bar_geometry(&cluster[cluster.len() - 1].bbox, vertical).0;
+            let pad = thickness as f32 * 0.5;
+            (first.0 as f32 - pad, (last.1 + 1) as f32 + pad)
+        })
+        .collect();
+
+    let categories = category_labels(labels, plot, vertical, &spans);
+    let labelled = categories.iter().filter(|c| !c.is_empty()).count();
+    if !has_axis && scale.is_none() && (labelled == 0 || labelled * 2 < categories.len()) {
+        return None;
+    }
+
+    Some(ChartData {
+        chart_type: if vertical { "column" } else { "bar" }.to_string(),
+        title: None,
+        categories,
+        series,
+        value_axis: Some(scale.map_or_else(relative_axis, |s| s.axis())),
+        bbox: None,
+    })
+}
+
+/// Trace wide, thin marks per colour and sample them at the turning points
+/// of the traces.
+fn line_chart(raster: &Raster, strokes: &[Mark], axis: Option<u32>, labels: &[OcrWord]) -> Option<(ChartData, Box)> {
+    let mut traces: Vec<Vec<(u32, f32)>> = Vec::new();
+    for series in 0..raster.palette.len() {
+        let mut columns: Vec<(u32, f32)> = strokes
+            .iter()
+            .filter(|m| m.series == series)
+            .flat_map(|m| m.columns.iter().copied())
+            .collect();
+        if columns.is_empty() {
+            continue;
+        }
+        columns.sort_by_key(|c| c.0);
+        columns.dedup_by_key(|c| c.0);
+
+        let (x0, x1) = (columns[0].0, columns[columns.len() - 1].0);
+        let span = x1 - x0 + 1;
+        let pixels: u32 = strokes
+            .iter()
+            .filter(|m| m.series == series)
+            .map(|m| m.pixels)
+            .sum();
+        let thin = pixels as f32 / (columns.len() as f32) <= raster.height as f32 * 0.1;
+        if span * 4 >= raster.width && columns.len() as u32 * 5 >= span * 4 && thin {
+            traces.push(columns);
+        }
+    }
+    if traces.is_empty() {
+        return None;
+    }
+
+    let top = traces
+        .iter()
+        .flat_map(|t| t.iter().map(|c| c.1))
+        .fold(f32::INFINITY, f32::min);
+    let lowest = traces
+        .iter()
+        .flat_map(|t| t.iter().map(|c| c.1))
+        .fold(f32::NEG_INFINITY, f32::max);
+    let base = axis
+        .map(|a| a as f32)
+        .filter(|&a| a >= lowest)
+        .unwrap_or(lowest);
+    let range = (base - top).max(1.0);
+
+    let epsilon = (raster.height as f32 * 0.015).max(1.5);
+    let mut xs: Vec<u32> = traces.iter().flat_map(|t| simplify(t, epsilon)).collect();
+    xs.sort_unstable();
+    xs.dedup_by(|a, b| a.abs_diff(*b) <= 3);
+
+    let plot = Box {
+        x0: xs[0],
+        y0: top as u32,
+        x1: xs[xs.len() - 1],
+        y1: base as u32,
+    };
+    let scale = Scale::fit(labels, plot, true);
+    let mut confidence = if axis.is_some() {
+        LINE_CONFIDENCE
+    } else {
+        LINE_CONFIDENCE * 0.7
+    };
+    if scale.is_some() {
+        confidence += CALIBRATED_BOOST;
+    }
+    let value = |y: f32| match &scale {
+        Some(scale) => scale.at(y),
+        None => ((base - y) / range) as f64,
+    };
+    let series = traces
+        .iter()
+        .enumerate()
+        .map(|(i, trace)| {
+            let (values, confidence) = xs
+                .iter()
+                .map(|&x| match sample(trace, x) {
+                    Some(y) => (Some(value(y)), confidence),
+                    None => (None, 0.0),
+                })
+                .unzip();
+            ChartSeries {
+                name: format!("Series {}", i + 1),
+                values,
+                confidence,
+            }
+        })
+        .collect();
+
+    // Labels are looked for within half the distance to the next point.
+    let spacing = xs.windows(2).map(|w| w[1] - w[0]).min().unwrap_or(raster.width) as f32;
+    let spans: Vec<(f32, f32)> = xs
+        .iter()
+        .map(|&x| (x as f32 - spacing * 0.5, x as f32 + spacing * 0.5))
+        .collect();
+    let chart = ChartData {
+        chart_type: "line".to_string(),
+        title: None,
+        categories: category_labels(labels, plot, true, &spans),
+        series,
+        value_axis: Some(scale.map_or_else(relative_axis, |s| s.axis())),
+        bbox: None,
+    };
+    Some((chart, plot))
+}
+
+/// Columns of the vertices kept by Ramer-Douglas-Peucker simplification.
+fn simplify(trace: &[(u32, f32)], epsilon: f32) -> Vec<u32> {
+    fn keep(points: &[(u32, f32)], epsilon: f32, out: &mut Vec<u32>) {
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let (dx, dy) = (last.0 as f32 - first.0 as f32, last.1 - first.1);
+        let norm = (dx * dx + dy * dy).sqrt().max(1e-3);
+        let farthest = points[1..points.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let d = (dy * (p.0 as f32 - first.0 as f32) - dx * (p.1 - first.1)).abs() / norm;
+                (i + 1, d)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, _)) = farthest.filter(|(_, d)| *d > epsilon) {
+            keep(&points[..=i], epsilon, out);
+            keep(&points[i..], epsilon, out);
+        } else {
+            out.push(first.0);
+            out.push(last.0);
+        }
+    }
+
+    let mut out = Vec::new();
+    if trace.len() > 1 {
+        keep(trace, epsilon, &mut out);
+    } else if let Some(p) = trace.first() {
+        out.push(p.0);
+    }
+    out
+}
+
+/// Row of a trace at column `x`, interpolating across small gaps.
+fn sample(trace: &[(u32, f32)], x: u32) -> Option<f32> {
+    let i = trace.partition_point(|c| c.0 < x);
+    match (i.checked_sub(1).map(|j| trace[j]), trace.get(i)) {
+        (_, Some(&(cx, y))) if cx == x => Some(y),
+        (Some((x0, y0)), Some(&(x1, y1)))
+            if x.abs_diff(x0) <= 3 || x1.abs_diff(x) <= 3 || x1 - x0 <= 12 =>
+        {
+            let t = (x - x0) as f32 / (x1 - x0) as f32;
+            Some(y0 + (y1 - y0) * t)
+        }
+        (Some((x0, y0)), None) if x.abs_diff(x0) <= 3 => Some(y0),
+        (None, Some(&(x1, y1))) if x1.abs_diff(x) <= 3 => Some(y1),
+        _ => None,
+    }
+}
+
+/// Linear map from pixel position along the value axis to data values,
+/// fitted to the numeric tick labels beside the plot.
+struct Scale {
+    slope: f64,
+    intercept: f64,
+    unit: Option<String>,
+}
+
+impl Scale {
+    /// Fit the tick labels left of a vertical plot, or under a horizontal
+    /// one. Needs two distinct ticks on a line running the right way, each
+    /// within `MAX_TICK_ERROR` of it, so stray numbers are not taken for
+    /// an axis.
+    fn fit(labels: &[OcrWord], plot: Box, vertical: bool) -> Option<Self> {
+        let mut unit = None;
+        let ticks: Vec<(f64, f64)> = labels
+            .iter()
+            .filter_map(|w| {
+                let b = &w.bbox;
+                // Left of and above the baseline, or under it and right of
+                // it, which keeps category labels out.
+                let beside = if vertical {
+                    b.x + b.width <= plot.x0 as f32 + 1.0 && b.y < plot.y1 as f32
+                } else {
+                    b.y >= plot.y1 as f32 - 1.0 && b.x + b.width > plot.x0 as f32
+                };
+                let quantity = normalize::parse_quantity(&w.text, None).filter(|_| beside)?;
+                if unit.is_none() {
+                    unit = match &quantity.unit {
+                        Some(normalize::Unit::Percent) => Some("%".to_string()),
+                        Some(normalize::Unit::Currency(code)) => {
+                            Some(w.text.chars().find(|c| "$€£¥₹".contains(*c)).map_or(code.clone(), String::from))
+                        }
+                        _ => None,
+                    };
+                }
+                let position = if vertical { b.y + b.height / 2.0 } else { b.x + b.width / 2.0 };
+                Some((position as f64, quantity.value))
+            })
+            .collect();
+        if ticks.len() < 2 {
+            return None;
+        }
+
+        let n = ticks.len() as f64;
+        let (mean_p, mean_v) = (
+            ticks.iter().map(|t| t.0).sum::<f64>() / n,
+            ticks.iter().map(|t| t.1).sum::<f64>() / n,
+        );
+        let spread: f64 = ticks.iter().map(|t| (t.0 - mean_p).powi(2)).sum();
+        if spread < 1.0 {
+            return None;
+        }
+        let slope = ticks.iter().map(|t| (t.0 - mean_p) * (t.1 - mean_v)).sum::<f64>() / spread;
+        // Values grow up the page, and to the right.
+        if (vertical && slope >= 0.0) || (!vertical && slope <= 0.0) {
+            return None;
+        }
+        let intercept = mean_v - slope * mean_p;
+        let (lo, hi) = ticks.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), t| (lo.min(t.1), hi.max(t.1)));
+        let fits = ticks.iter().all(|t| (slope * t.0 + intercept - t.1).abs() <= (hi - lo) * MAX_TICK_ERROR);
+        fits.then_some(Self { slope, intercept, unit })
+    }
+
+    fn at(&self, position: f32) -> f64 {
+        self.slope * position as f64 + self.intercept
+    }
+
+    fn axis(self) -> ChartAxis {
+        ChartAxis {
+            title: None,
+            number_format: None,
+            unit: self.unit,
+            scale: None,
+        }
+    }
+}
+
+/// Labels under a vertical plot, or left of a horizontal one, grouped by
+/// the category span their centre falls in.
+fn category_labels(labels: &[OcrWord], plot: Box, vertical: bool, spans: &[(f32, f32)]) -> Vec<String> {
+    spans
+        .iter()
+        .map(|&(start, end)| {
+            let mut words: Vec<&OcrWord> = labels
+                .iter()
+                .filter(|w| {
+                    let b = &w.bbox;
+                    let (outside, centre) = if vertical {
+                        (b.y >= plot.y1 as f32 - 1.0, b.x + b.width / 2.0)
+                    } else {
+                        (b.x + b.width <= plot.x0 as f32 + 1.0, b.y + b.height / 2.0)
+                    };
+                    outside && (start..end).contains(&centre)
+                })
+                .collect();
+            // Line by line, then left to right.
+            words.sort_by_key(|w| ((w.bbox.y / w.bbox.height.max(1.0)).round() as i64, w.bbox.x as i64));
+            words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ")
+        })
+        .collect()
+}
+
+fn relative_axis() -> ChartAxis {
+    ChartAxis {
+        title: None,
+        number_format: None,
+        unit: None,
+        scale: Some("relative".to_string()),
+    }
+}
@@ -0,0 +1,897 @@
+// Chart recovery from PDF vector drawings
+use crate::models::*;
+
+/// Tolerance, in points, for edges and baselines to count as shared.
+const EPSILON: f32 = 1.0;
+
+/// Confidence of a value read from a data label printed on its mark.
+const LABEL_CONFIDENCE: f32 = 0.95;
+
+/// Confidence of a value scaled from the data labels of other bars.
+const PROPORTION_CONFIDENCE: f32 = 0.6;
+
+/// Confidence of a value known only relative to the largest mark.
+const RELATIVE_CONFIDENCE: f32 = 0.2;
+
+/// Detect bar, line and area charts drawn as vector paths on a page and
+/// invert their geometry to recover approximate values. Tick labels give
+/// the value scale; data labels on the marks take precedence where present.
+/// Stacked bars are not reconstructed.
+pub fn detect(page: &PageContent) -> Vec<ChartData> {
+    if page.paths.is_empty() {
+        return vec![];
+    }
+
+    let page_area = page.width.unwrap_or(0.0) * page.height.unwrap_or(0.0);
+    let ctx = Context::new(page);
+
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut lines: Vec<Polyline> = Vec::new();
+    for path in &page.paths {
+        for subpath in &path.subpaths {
+            if let Some(fill) = &path.fill {
+                if let Some(bbox) = rectangle(subpath) {
+                    // Skip backgrounds and panels.
+                    if page_area == 0.0 || bbox.width * bbox.height < page_area * 0.5 {
+                        rects.push(Rect {
+                            bbox,
+                            color: fill.clone(),
+                        });
+                    }
+                    continue;
+                }
+                if let Some(line) = area_outline(subpath, fill, ctx.em) {
+                    lines.push(line);
+                }
+            } else if let Some(stroke) = &path.stroke {
+                if let Some(line) = polyline(subpath, stroke, ctx.em) {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+
+    let mut charts = Vec::new();
+    for orientation in [Orientation::Vertical, Orientation::Horizontal] {
+        for group in bar_groups(&rects, orientation) {
+            charts.extend(bar_chart(&ctx, &rects, &group, orientation));
+        }
+    }
+    for group in line_groups(&lines) {
+        charts.extend(line_chart(&ctx, &rects, &group));
+    }
+    charts
+}
+
+/// Text of the page, with numeric labels parsed once.
+struct Context<'a> {
+    labels: Vec<Label<'a>>,
+    /// Median font size, the unit for distances between marks and labels.
+    em: f32,
+    paths: &'a [VectorPath],
+}
+
+impl<'a> Context<'a> {
+    fn new(page: &'a PageContent) -> Self {
+        let mut sizes: Vec<f32> = page.spans.iter().map(|s| s.font_size).collect();
+        sizes.sort_by(f32::total_cmp);
+        let em = sizes.get(sizes.len() / 2).copied().unwrap_or(10.0).max(1.0);
+
+        let labels = page
+            .spans
+            .iter()
+            .map(|span| Label {
+                span,
+                number: parse_number(&span.text),
+            })
+            .collect();
+
+        Self {
+            labels,
+            em,
+            paths: &page.paths,
+        }
+    }
+
+    /// Linear map from a coordinate along the value axis to a value, fitted
+    /// to the numeric tick labels beside the plot.
+    fn scale(&self, plot: &BoundingBox, orientation: Orientation) -> Option<Scale> {
+        let ticks: Vec<(f64, &Number)> = self
+            .labels
+            .iter()
+            .filter_map(|l| Some((l, l.number.as_ref()?)))
+            .filter(|(l, _)| match orientation {
+                Orientation::Vertical => self.left_of(plot, &l.span.bbox),
+                Orientation::Horizontal => self.below(plot, &l.span.bbox),
+            })
+            .map(|(l, n)| (orientation.value_coord(&center_box(&l.span.bbox)) as f64, n))
+            .collect();
+
+        let scale = Scale::fit(&ticks)?;
+        // Values grow up the page or to the right.
+        let increasing = match orientation {
+            Orientation::Vertical => scale.slope < 0.0,
+            Orientation::Horizontal => scale.slope > 0.0,
+        };
+        (increasing && scale.r2 > 0.9).then_some(scale)
+    }
+
+    /// Tick label region of a vertical value axis.
+    fn left_of(&self, plot: &BoundingBox, b: &BoundingBox) -> bool {
+        let cy = b.y + b.height / 2.0;
+        let reach = (self.em * 15.0).max(plot.height);
+        b.right() <= plot.x + EPSILON
+            && b.right() >= plot.x - self.em * 8.0
+            && cy >= plot.y - reach
+            && cy <= plot.bottom() + reach
+    }
+
+    /// Label region under the plot: category labels, or ticks of a
+    /// horizontal value axis.
+    fn below(&self, plot: &BoundingBox, b: &BoundingBox) -> bool {
+        let cx = b.x + b.width / 2.0;
+        let reach = (self.em * 15.0).max(plot.width);
+        b.y >= plot.bottom() - EPSILON
+            && b.y <= plot.bottom() + self.em * 3.0
+            && cx >= plot.x - self.em
+            && cx <= plot.right() + reach
+    }
+
+    /// Text labels (in reading order) whose centre falls within `range`
+    /// along the category axis, beside the plot.
+    fn category_label(
+        &self,
+        plot: &BoundingBox,
+        range: (f32, f32),
+        orientation: Orientation,
+    ) -> String {
+        self.labels
+            .iter()
+            .filter(|l| match orientation {
+                Orientation::Vertical => self.below(plot, &l.span.bbox),
+                Orientation::Horizontal => self.left_of(plot, &l.span.bbox),
+            })
+            .filter(|l| {
+                let c = orientation.category_coord(&center_box(&l.span.bbox));
+                c >= range.0 && c <= range.1
+            })
+            .map(|l| l.span.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Nearest horizontal rule under the plot spanning most of its width.
+    fn x_axis(&self, plot: &BoundingBox) -> Option<f32> {
+        self.paths
+            .iter()
+            .filter(|p| p.stroke.is_some())
+            .flat_map(|p| p.subpaths.iter())
+            .filter_map(|s| match s[..] {
+                [(x0, y0), (x1, y1)] if (y0 - y1).abs() < EPSILON => {
+                    Some((x0.min(x1), x0.max(x1), y0))
+                }
+                _ => None,
+            })
+            .filter(|&(left, right, y)| {
+                y >= plot.bottom() - EPSILON
+                    && right - left >= plot.width * 0.8
+                    && left <= plot.x + EPSILON
+            })
+            .map(|(_, _, y)| y)
+            .min_by(f32::total_cmp)
+    }
+
+    /// Nearest non-numeric line of text above the plot.
+    fn title(&self, plot: &BoundingBox) -> Option<String> {
+        self.labels
+            .iter()
+            .filter(|l| l.number.is_none())
+            .map(|l| l.span)
+            .filter(|span| {
+                let b = &span.bbox;
+                let cx = b.x + b.width / 2.0;
+                b.bottom() <= plot.y + EPSILON
+                    && b.bottom() >= plot.y - self.em * 6.0
+                    && cx >= plot.x
+                    && cx <= plot.right()
+            })
+            .max_by(|a, b| a.bbox.bottom().total_cmp(&b.bbox.bottom()))
+            .map(|span| span.text.trim().to_string())
+    }
+
+    /// Series name from a legend entry: a small swatch of the series colour
+    /// with text just to its right.
+    fn legend_name(&self, rects: &[Rect], color: &str) -> Option<String> {
+        let max = self.em * 2.5;
+        let swatches = rects
+            .iter()
+            .filter(|r| r.color == color && r.bbox.width <= max && r.bbox.height <= max)
+            .map(|r| r.bbox)
+            .chain(
+                self.paths
+                    .iter()
+                    .filter(|p| p.stroke.as_deref() == Some(color))
+                    .flat_map(|p| {
+                        p.subpaths.iter().filter_map(|s| match s[..] {
+                            [(x0, y0), (x1, y1)]
+                                if (y0 - y1).abs() < EPSILON && (x1 - x0).abs() <= max =>
+                            {
+                                Some(BoundingBox::new(x0.min(x1), y0 - 1.0, (x1 - x0).abs(), 2.0))
+                            }
+                            _ => None,
+                        })
+                    }),
+            );
+
+        for swatch in swatches {
+            let cy = swatch.y + swatch.height / 2.0;
+            let name = self.labels.iter().find(|l| {
+                let b = &l.span.bbox;
+                b.x >= swatch.right() - EPSILON
+                    && b.x <= swatch.right() + self.em * 1.5
+                    && cy >= b.y - self.em * 0.5
+                    && cy <= b.bottom() + self.em * 0.5
+            });
+            if let Some(label) = name {
+                return Some(label.span.text.trim().to_string());
+            }
+        }
+        None
+    }
+}
+
+struct Label<'a> {
+    span: &'a TextSpan,
+    number: Option<Number>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Number {
+    value: f64,
+    unit: Option<String>,
+}
+
+/// Parse a chart label such as `120`, `$1.2M`, `(40)`, `35%` or `2.5k`.
+fn parse_number(text: &str) -> Option<Number> {
+    let mut s = text.trim();
+    let mut negative = false;
+    if let Some(inner) = s.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        negative = true;
+        s = inner.trim();
+    }
+    if let Some(rest) = s.strip_prefix('-').or_else(|| s.strip_prefix('\u{2212}')) {
+        negative = !negative;
+        s = rest.trim_start();
+    }
+
+    let mut unit = None;
+    if let Some(c) = s.chars().next().filter(|c| "$€£¥₹".contains(*c)) {
+        unit = Some(c.to_string());
+        s = s[c.len_utf8()..].trim_start();
+    }
+    if let Some(rest) = s.strip_suffix('%') {
+        unit = Some("%".to_string());
+        s = rest.trim_end();
+    }
+
+    let (digits, multiplier) = [
+        ("bn", 1e9),
+        ("B", 1e9),
+        ("M", 1e6),
+        ("m", 1e6),
+        ("K", 1e3),
+        ("k", 1e3),
+    ]
+    .iter()
+    .find_map(|(suffix, m)| s.strip_suffix(suffix).map(|d| (d.trim_end(), *m)))
+    .unwrap_or((s, 1.0));
+
+    let cleaned = digits.replace(',', "");
+    if cleaned.is_empty() || !cleaned.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let value = cleaned.parse::<f64>().ok()? * multiplier;
+    Some(Number {
+        value: if negative { -value } else { value },
+        unit,
+    })
+}
+
+/// Least-squares fit of tick values against their positions.
+struct Scale {
+    slope: f64,
+    intercept: f64,
+    r2: f64,
+    /// Extent of the ticks along the axis.
+    range: (f64, f64),
+    unit: Option<String>,
+}
+
+impl Scale {
+    fn fit(ticks: &[(f64, &Number)]) -> Option<Scale> {
+        let n = ticks.len() as f64;
+        if ticks.len() < 2 {
+            return None;
+        }
+        let mean_x = ticks.iter().map(|t| t.0).sum::<f64>() / n;
+        let mean_y = ticks.iter().map(|t| t.1.value).sum::<f64>() / n;
+        let var_x: f64 = ticks.iter().map(|t| (t.0 - mean_x).powi(2)).sum();
+        let var_y: f64 = ticks.iter().map(|t| (t.1.value - mean_y).powi(2)).sum();
+        if var_x < 1e-6 || var_y < 1e-12 {
+            return None;
+        }
+        let cov: f64 = ticks
+            .iter()
+            .map(|t| (t.0 - mean_x) * (t.1.value - mean_y))
+            .sum();
+        let slope = cov / var_x;
+        let intercept = mean_y - slope * mean_x;
+        let residual: f64 = ticks
+            .iter()
+            .map(|t| (t.1.value - (slope * t.0 + intercept)).powi(2))
+            .sum();
+
+        let lo = ticks.iter().map(|t| t.0).fold(f64::INFINITY, f64::min);
+        let hi = ticks.iter().map(|t| t.0).fold(f64::NEG_INFINITY, f64::max);
+        Some(Scale {
+            slope,
+            intercept,
+            r2: 1.0 - residual / var_y,
+            range: (lo, hi),
+            unit: ticks.iter().find_map(|t| t.1.unit.clone()),
+        })
+    }
+
+    /// Value at an axis coordinate, with lower confidence when it lies
+    /// beyond the labelled ticks.
+    fn value(&self, coord: f32) -> (f64, f32) {
+        let coord = coord as f64;
+        let margin = (self.range.1 - self.range.0) * 0.1;
+        let mut confidence = 0.85 * self.r2 as f32;
+        if coord < self.range.0 - margin || coord > self.range.1 + margin {
+            confidence *= 0.7;
+        }
+        (self.slope * coord + self.intercept, confidence)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Orientation {
+    /// Columns rising from a shared bottom edge.
+    Vertical,
+    /// Bars extending right from a shared left edge.
+    Horizontal,
+}
+
+impl Orientation {
+    fn base(self, b: &BoundingBox) -> f32 {
+        match self {
+            Orientation::Vertical => b.bottom(),
+            Orientation::Horizontal => b.x,
+        }
+    }
+
+    /// Coordinate of the value end of a bar.
+    fn end(self, b: &BoundingBox) -> f32 {
+        match self {
+            Orientation::Vertical => b.y,
+            Orientation::Horizontal => b.right(),
+        }
+    }
+
+    fn length(self, b: &BoundingBox) -> f32 {
+        match self {
+            Orientation::Vertical => b.height,
+            Orientation::Horizontal => b.width,
+        }
+    }
+
+    /// Extent along the category axis.
+    fn span(self, b: &BoundingBox) -> (f32, f32) {
+        match self {
+            Orientation::Vertical => (b.x, b.right()),
+            Orientation::Horizontal => (b.y, b.bottom()),
+        }
+    }
+
+    fn thickness(self, b: &BoundingBox) -> f32 {
+        let (start, end) = self.span(b);
+        end - start
+    }
+
+    fn value_coord(self, b: &BoundingBox) -> f32 {
+        match self {
+            Orientation::Vertical => b.y,
+            Orientation::Horizontal => b.x,
+        }
+    }
+
+    fn category_coord(self, b: &BoundingBox) -> f32 {
+        match self {
+            Orientation::Vertical => b.x,
+            Orientation::Horizontal => b.y,
+        }
+    }
+}
+
+/// Zero-size box at the centre of `b`.
+fn center_box(b: &BoundingBox) -> BoundingBox {
+    BoundingBox::new(b.x + b.width / 2.0, b.y + b.height / 2.0, 0.0, 0.0)
+}
+
+#[derive(Debug, Clone)]
+struct Rect {
+    bbox: BoundingBox,
+    color: String,
+}
+
+/// Axis-aligned rectangle described by a closed subpath.
+fn rectangle(points: &[(f32, f32)]) -> Option<BoundingBox> {
+    let mut corners: Vec<(f32, f32)> = points.to_vec();
+    if corners.len() > 1 && corners.first() == corners.last() {
+        corners.pop();
+    }
+    if corners.len() != 4 {
+        return None;
+    }
+
+    let xs = distinct(corners.iter().map(|p| p.0));
+    let ys = distinct(corners.iter().map(|p| p.1));
+    if xs.len() != 2 || ys.len() != 2 {
+        return None;
+    }
+    // Consecutive corners must share an edge, not a diagonal.
+    let axis_aligned = (0..4).all(|i| {
+        let (a, b) = (corners[i], corners[(i + 1) % 4]);
+        (a.0 - b.0).abs() < EPSILON || (a.1 - b.1).abs() < EPSILON
+    });
+    axis_aligned.then(|| BoundingBox::new(xs[0], ys[0], xs[1] - xs[0], ys[1] - ys[0]))
+}
+
+fn distinct(values: impl Iterator<Item = f32>) -> Vec<f32> {
+    let mut out: Vec<f32> = Vec::new();
+    for v in values {
+        if !out.iter().any(|o| (o - v).abs() < EPSILON) {
+            out.push(v);
+        }
+    }
+    out.sort_by(f32::total_cmp);
+    out
+}
+
+/// Runs of same-baseline bars with similar thickness and differing lengths.
+fn bar_groups(rects: &[Rect], orientation: Orientation) -> Vec<Vec<Rect>> {
+    let mut bars: Vec<&Rect> = rects
+        .iter()
+        .filter(|r| orientation.thickness(&r.bbox) >= 2.0 && orientation.length(&r.bbox) >= 1.0)
+        .collect();
+    bars.sort_by(|a, b| {
+        orientation
+            .base(&a.bbox)
+            .total_cmp(&orientation.base(&b.bbox))
+            .then(
+                orientation
+                    .span(&a.bbox)
+                    .0
+                    .total_cmp(&orientation.span(&b.bbox).0),
+            )
+    });
+
+    let mut baselines: Vec<Vec<&Rect>> = Vec::new();
+    for bar in bars {
+        match baselines.last_mut() {
+            Some(group)
+                if (orientation.base(&group[0].bbox) - orientation.base(&bar.bbox)).abs()
+                    < EPSILON =>
+            {
+                group.push(bar)
+            }
+            _ => baselines.push(vec![bar]),
+        }
+    }
+
+    let mut groups = Vec::new();
+    for mut baseline in baselines {
+        baseline.sort_by(|a, b| {
+            orientation
+                .span(&a.bbox)
+                .0
+                .total_cmp(&orientation.span(&b.bbox).0)
+        });
+        let thickness = median(baseline.iter().map(|r| orientation.thickness(&r.bbox)));
+
+        // Far-apart runs on the same baseline are separate charts.
+        let mut run: Vec<Rect> = Vec::new();
+        for bar in baseline {
+            let gap = run
+                .last()
+                .map(|prev| orientation.span(&bar.bbox).0 - orientation.span(&prev.bbox).1);
+            if gap.is_some_and(|g| g > thickness * 4.0 || g < -EPSILON) {
+                groups.push(std::mem::take(&mut run));
+            }
+            run.push(bar.clone());
+        }
+        groups.push(run);
+    }
+
+    groups
+        .into_iter()
+        .filter(|group| {
+            if group.len() < 2 {
+                return false;
+            }
+            let thickness: Vec<f32> = group
+                .iter()
+                .map(|r| orientation.thickness(&r.bbox))
+                .collect();
+            let lengths: Vec<f32> = group.iter().map(|r| orientation.length(&r.bbox)).collect();
+            let (t_min, t_max) = min_max(&thickness);
+            let (l_min, l_max) = min_max(&lengths);
+            t_max <= t_min * 1.5 && l_max - l_min > l_max * 0.05
+        })
+        .collect()
+}
+
+fn bar_chart(
+    ctx: &Context,
+    rects: &[Rect],
+    bars: &[Rect],
+    orientation: Orientation,
+) -> Option<ChartData> {
+    let plot = bars
+        .iter()
+        .skip(1)
+        .fold(bars[0].bbox, |acc, r| acc.union(&r.bbox));
+    let thickness = median(bars.iter().map(|r| orientation.thickness(&r.bbox)));
+
+    // Clusters of adjacent bars in distinct colours share a category.
+    let mut clusters: Vec<Vec<&Rect>> = Vec::new();
+    for bar in bars {
+        let starts_new = clusters.last().is_none_or(|cluster| {
+            let prev = cluster.last().unwrap();
+            let gap = orientation.span(&bar.bbox).0 - orientation.span(&prev.bbox).1;
+            gap > thickness * 0.35 || cluster.iter().any(|r| r.color == bar.color)
+        });
+        if starts_new {
+            clusters.push(vec![bar]);
+        } else {
+            clusters.last_mut().unwrap().push(bar);
+        }
+    }
+
+    let mut colors: Vec<&str> = Vec::new();
+    for bar in bars {
+        if !colors.contains(&bar.color.as_str()) {
+            colors.push(&bar.color);
+        }
+    }
+
+    // Data labels printed at the end of, or inside, each bar.
+    let data_label = |bar: &Rect| -> Option<&Number> {
+        let (start, end) = orientation.span(&bar.bbox);
+        ctx.labels.iter().find_map(|l| {
+            let number = l.number.as_ref()?;
+            let b = &l.span.bbox;
+            let c = orientation.category_coord(&center_box(b));
+            if c < start || c > end {
+                return None;
+            }
+            let near_end = match orientation {
+                Orientation::Vertical => {
+                    b.bottom() <= bar.bbox.y + EPSILON * 2.0
+                        && b.bottom() >= bar.bbox.y - ctx.em * 2.0
+                }
+                Orientation::Horizontal => {
+                    b.x >= bar.bbox.right() - EPSILON * 2.0
+                        && b.x <= bar.bbox.right() + ctx.em * 2.0
+                }
+            };
+            let inside = {
+                let center = center_box(b);
+                center.x >= bar.bbox.x
+                    && center.x <= bar.bbox.right()
+                    && center.y >= bar.bbox.y
+                    && center.y <= bar.bbox.bottom()
+            };
+            (near_end || inside).then_some(number)
+        })
+    };
+
+    let scale = ctx.scale(&plot, orientation);
+    let labelled: Vec<(f32, &Number)> = bars
+        .iter()
+        .filter_map(|b| Some((orientation.length(&b.bbox), data_label(b)?)))
+        .collect();
+    let per_point = {
+        let ratios: Vec<f32> = labelled
+            .iter()
+            .filter(|(len, _)| *len > 0.0)
+            .map(|(len, n)| n.value as f32 / len)
+            .collect();
+        (!ratios.is_empty()).then(|| median(ratios.into_iter()))
+    };
+    let longest = bars
+        .iter()
+        .map(|b| orientation.length(&b.bbox))
+        .fold(0.0, f32::max);
+
+    let measure = |bar: &Rect| -> (f64, f32) {
+        if let Some(label) = data_label(bar) {
+            return (label.value, LABEL_CONFIDENCE);
+        }
+        if let Some(scale) = &scale {
+            return scale.value(orientation.end(&bar.bbox));
+        }
+        let length = orientation.length(&bar.bbox);
+        match per_point {
+            Some(ratio) => ((length * ratio) as f64, PROPORTION_CONFIDENCE),
+            None => ((length / longest) as f64, RELATIVE_CONFIDENCE),
+        }
+    };
+
+    let categories: Vec<String> = clusters
+        .iter()
+        .map(|cluster| {
+            let start = orientation.span(&cluster[0].bbox).0 - thickness * 0.25;
+            let end = orientation.span(&cluster[cluster.len() - 1].bbox).1 + thickness * 0.25;
+            ctx.category_label(&plot, (start, end), orientation)
+        })
+        .collect();
+
+    let named = categories.iter().filter(|c| !c.is_empty()).count();
+    if scale.is_none() && labelled.is_empty() && named * 2 < categories.len() {
+        // Nothing on the page reads like a chart: likely decoration.
+        return None;
+    }
+
+    let series = colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+            let (values, confidence) = clusters
+                .iter()
+                .map(|cluster| match cluster.iter().find(|r| r.color == *color) {
+                    Some(bar) => {
+                        let (value, confidence) = measure(bar);
+                        (Some(value), confidence)
+                    }
+                    None => (None, 0.0),
+                })
+                .unzip();
+            ChartSeries {
+                name: ctx
+                    .legend_name(rects, color)
+                    .unwrap_or_else(|| format!("Series {}", i + 1)),
+                values,
+                confidence,
+            }
+        })
+        .collect();
+
+    let unit = scale
+        .as_ref()
+        .and_then(|s| s.unit.clone())
+        .or_else(|| labelled.iter().find_map(|(_, n)| n.unit.clone()));
+
+    Some(ChartData {
+        chart_type: match orientation {
+            Orientation::Vertical => "column".to_string(),
+            Orientation::Horizontal => "bar".to_string(),
+        },
+        title: ctx.title(&plot),
+        categories,
+        series,
+        value_axis: Some(value_axis(unit, scale.is_none() && per_point.is_none())),
+        bbox: Some(plot),
+    })
+}
+
+#[derive(Debug, Clone)]
+struct Polyline {
+    points: Vec<(f32, f32)>,
+    color: String,
+    /// Bottom edge of a filled area series.
+    baseline: Option<f32>,
+}
+
+impl Polyline {
+    fn bbox(&self) -> BoundingBox {
+        let (x0, x1) = (self.points[0].0, self.points[self.points.len() - 1].0);
+        let ys = self.points.iter().map(|p| p.1).chain(self.baseline);
+        let (y0, y1) = ys.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), y| {
+            (lo.min(y), hi.max(y))
+        });
+        BoundingBox::new(x0, y0, x1 - x0, y1 - y0)
+    }
+}
+
+/// An open stroked path stepping left to right, as drawn by line charts.
+fn polyline(points: &[(f32, f32)], color: &str, em: f32) -> Option<Polyline> {
+    if points.len() < 3 || points.first() == points.last() {
+        return None;
+    }
+    let increasing = points.windows(2).all(|w| w[1].0 > w[0].0 + 0.5);
+    let (lo, hi) = min_max(&points.iter().map(|p| p.1).collect::<Vec<_>>());
+    let width = points[points.len() - 1].0 - points[0].0;
+    (increasing && hi - lo > EPSILON && width >= em * 3.0).then(|| Polyline {
+        points: points.to_vec(),
+        color: color.to_string(),
+        baseline: None,
+    })
+}
+
+/// A filled polygon with a flat bottom edge and a stepped top edge, as
+/// drawn by area charts.
+fn area_outline(points: &[(f32, f32)], color: &str, em: f32) -> Option<Polyline> {
+    if points.len() < 5 || points.first() != points.last() {
+        return None;
+    }
+    let base = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+    let mut top: Vec<(f32, f32)> = points[..points.len() - 1]
+        .iter()
+        .copied()
+        .filter(|p| p.1 < base - EPSILON)
+        .collect();
+    top.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut line = polyline(&top, color, em)?;
+    line.baseline = Some(base);
+    Some(line)
+}
+
+/// Series whose horizontal extents mostly overlap belong to one chart.
+fn line_groups(lines: &[Polyline]) -> Vec<Vec<Polyline>> {
+    let mut sorted: Vec<&Polyline> = lines.iter().collect();
+    sorted.sort_by(|a, b| a.points[0].0.total_cmp(&b.points[0].0));
+
+    let mut groups: Vec<Vec<Polyline>> = Vec::new();
+    for line in sorted {
+        let b = line.bbox();
+        let joins = groups.last().is_some_and(|group| {
+            let g = group
+                .iter()
+                .skip(1)
+                .fold(group[0].bbox(), |acc, l| acc.union(&l.bbox()));
+            let overlap = g.right().min(b.right()) - g.x.max(b.x);
+            overlap > g.width.min(b.width) * 0.5
+        });
+        if joins {
+            groups.last_mut().unwrap().push(line.clone());
+        } else {
+            groups.push(vec![line.clone()]);
+        }
+    }
+    groups
+}
+
+fn line_chart(ctx: &Context, rects: &[Rect], lines: &[Polyline]) -> Option<ChartData> {
+    let plot = lines
+        .iter()
+        .skip(1)
+        .fold(lines[0].bbox(), |acc, l| acc.union(&l.bbox()));
+
+    // Categories sit at the x positions of the vertices.
+    let xs = distinct(lines.iter().flat_map(|l| l.points.iter().map(|p| p.0)));
+    let spacing = xs
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .fold(f32::INFINITY, f32::min);
+
+    let scale = ctx.scale(&plot, Orientation::Vertical);
+    // Lines float above the category axis; labels sit under the axis, not
+    // under the lowest point.
+    let axis = ctx
+        .x_axis(&plot)
+        .or_else(|| scale.as_ref().map(|s| s.range.1 as f32));
+    let mut frame = plot;
+    if let Some(y) = axis.filter(|&y| y > plot.bottom()) {
+        frame.height = y - plot.y;
+    }
+    let data_label = |point: (f32, f32)| -> Option<&Number> {
+        ctx.labels.iter().find_map(|l| {
+            let number = l.number.as_ref()?;
+            let c = center_box(&l.span.bbox);
+            ((c.x - point.0).abs() < spacing / 2.0 && (c.y - point.1).abs() < ctx.em * 1.5)
+                .then_some(number)
+        })
+    };
+
+    let mut labelled = 0;
+    let series: Vec<ChartSeries> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (values, confidence) = xs
+                .iter()
+                .map(
+                    |&x| match line.points.iter().find(|p| (p.0 - x).abs() < EPSILON) {
+                        Some(&point) => {
+                            let (value, confidence) = match (data_label(point), &scale) {
+                                (Some(label), _) => {
+                                    labelled += 1;
+                                    (label.value, LABEL_CONFIDENCE)
+                                }
+                                (None, Some(scale)) => scale.value(point.1),
+                                (None, None) => (
+                                    ((plot.bottom() - point.1) / plot.height.max(1.0)) as f64,
+                                    RELATIVE_CONFIDENCE,
+                                ),
+                            };
+                            (Some(value), confidence)
+                        }
+                        None => (None, 0.0),
+                    },
+                )
+                .unzip();
+            ChartSeries {
+                name: ctx
+                    .legend_name(rects, &line.color)
+                    .unwrap_or_else(|| format!("Series {}", i + 1)),
+                values,
+                confidence,
+            }
+        })
+        .collect();
+
+    let categories: Vec<String> = xs
+        .iter()
+        .map(|&x| {
+            ctx.category_label(
+                &frame,
+                (x - spacing / 2.0, x + spacing / 2.0),
+                Orientation::Vertical,
+            )
+        })
+        .collect();
+
+    let named = categories.iter().filter(|c| !c.is_empty()).count();
+    if scale.is_none() && labelled == 0 && named * 2 < categories.len() {
+        return None;
+    }
+
+    let unit = scale.as_ref().and_then(|s| s.unit.clone()).or_else(|| {
+        lines
+            .iter()
+            .flat_map(|l| l.points.iter())
+            .find_map(|&p| data_label(p)?.unit.clone())
+    });
+    let area = lines.iter().all(|l| l.baseline.is_some());
+    let chart_type = match (area, lines.iter().any(|l| l.baseline.is_some())) {
+        (true, _) => "area",
+        (false, true) => "combo",
+        (false, false) => "line",
+    };
+
+    Some(ChartData {
+        chart_type: chart_type.to_string(),
+        title: ctx.title(&plot),
+        categories,
+        series,
+        value_axis: Some(value_axis(unit, scale.is_none() && labelled == 0)),
+        bbox: Some(plot),
+    })
+}
+
+fn value_axis(unit: Option<String>, relative: bool) -> ChartAxis {
+    ChartAxis {
+        title: None,
+        number_format: None,
+        unit,
+        // Without ticks or labels values are fractions of the largest mark.
+        scale: relative.then(|| "relative".to_string()),
+    }
+}
+
+fn median(values: impl Iterator<Item = f32>) -> f32 {
+    let mut v: Vec<f32> = values.collect();
+    v.sort_by(f32::total_cmp);
+    v.get(v.len() / 2).copied().unwrap_or(0.0)
+}
+
+fn min_max(values: &[f32]) -> (f32, f32) {
+    values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
@@ -0,0 +1,720 @@
+// Risk flags raised from a processed document
+use crate::{error::Result, extractors, models::*, normalize::{self, Unit}, parsers::cell_value, pipeline::{DocumentContext, Stage}};
+
+/// Runway, in months, below which a company must raise imminently.
+const MIN_RUNWAY_MONTHS: f64 = 6.0;
+
+/// Runway, in months, below which a company has less than a year left.
+const SHORT_RUNWAY_MONTHS: f64 = 12.0;
+
+/// Share of revenue from the largest customers that makes them a risk,
+/// and a serious one.
+const CONCENTRATION: f64 = 25.0;
+const HIGH_CONCENTRATION: f64 = 50.0;
+
+/// Words and phrases a customer concentration statement combines: who,
+/// which of them, and a share of what.
+const CUSTOMER_WORDS: &[&str] = &["customer", "customers", "client", "clients", "account", "accounts"];
+const LARGEST_WORDS: &[&str] = &["top", "largest", "biggest", "single", "one customer", "one client", "key customer", "key customers"];
+const REVENUE_WORDS: &[&str] = &["revenue", "revenues", "sales", "arr", "bookings", "gmv"];
+
+/// Sections a pitch deck must show before missing ones are flagged, so
+/// arbitrary documents are not held to a deck's outline.
+const MIN_DECK_SECTIONS: usize = 3;
+
+/// Relative difference between two statements of one metric for the same
+/// period that counts as an inconsistency.
+const MAX_METRIC_SPREAD: f64 = 0.1;
+
+/// Phrases that point to legal exposure.
+const LEGAL_TERMS: &[&str] = &[
+    "litigation", "lawsuit", "lawsuits", "legal proceedings", "legal dispute", "cease and desist",
+    "patent infringement", "infringement claim", "class action", "regulatory investigation",
+    "under investigation", "subpoena", "consent decree", "unlicensed", "pending regulatory approval",
+    "awaiting regulatory approval", "not yet licensed", "ip is owned by", "ip owned by",
+];
+
+/// Words that, just before a legal term, deny it: "no pending litigation".
+const NEGATIONS: &[&str] = &["no", "not", "never", "free", "without", "zero"];
+
+/// One check over a processed document.
+pub trait RiskRule: Send + Sync {
+    /// Short identifier, recorded on the flags it raises.
+    fn name(&self) -> &str;
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag>;
+}
+
+/// The rules run over every processed document. The default engine checks
+/// runway, customer concentration, missing financials and team, hockey-stick
+/// projections, claims contradicted by or missing from reference data,
+/// market sizes out of order, metrics stated inconsistently, and legal
+/// exposure.
+pub struct RiskEngine {
+    rules: Vec<Box<dyn RiskRule>>,
+}
+
+impl Default for RiskEngine {
+    fn default() -> Self {
+        Self::new()
+            .with_rule(ShortRunwayRule)
+            .with_rule(CustomerConcentrationRule)
+            .with_rule(MissingFinancialsRule)
+            .with_rule(MissingTeamRule)
+            .with_rule(HockeyStickRule)
+            .with_rule(ContradictedClaimsRule)
+            .with_rule(UnverifiableClaimsRule)
+            .with_rule(MarketSizeOrderRule)
+            .with_rule(InconsistentMetricsRule)
+            .with_rule(LegalExposureRule)
+    }
+}
+
+impl RiskEngine {
+    /// An engine with no rules.
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    pub fn with_rule(mut self, rule: impl RiskRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Drop the rule called `name`, e.g. to replace it with another.
+    pub fn without_rule(mut self, name: &str) -> Self {
+        self.rules.retain(|r| r.name() != name);
+        self
+    }
+
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|r| r.name()).collect()
+    }
+
+    /// Flags raised by every rule, most severe first.
+    pub fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let mut flags: Vec<RiskFlag> = self.rules.iter().flat_map(|r| r.evaluate(doc)).collect();
+        flags.sort_by_key(|f| std::cmp::Reverse(f.severity));
+        flags
+    }
+}
+
+/// Runs a `RiskEngine` over what the stages before it found, into
+/// `DocumentContext::risk_flags`. The last default stage, so a custom
+/// engine replaces it: `without_stage("risk").with_stage(RiskStage::new(engine))`.
+#[derive(Default)]
+pub struct RiskStage {
+    engine: RiskEngine,
+}
+
+impl RiskStage {
+    pub fn new(engine: RiskEngine) -> Self {
+        Self { engine }
+    }
+}
+
+impl Stage for RiskStage {
+    fn name(&self) -> &str {
+        "risk"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        ctx.risk_flags = self.engine.evaluate(ctx);
+        Ok(())
+    }
+}
+
+fn flag(rule: &str, severity: Severity, category: RiskCategory, explanation: String, pages: Vec<u32>) -> RiskFlag {
+    RiskFlag {
+        rule: rule.to_string(),
+        severity,
+        category,
+        explanation,
+        pages,
+    }
+}
+
+/// Runway under six months, or under a year.
+pub struct ShortRunwayRule;
+
+impl RiskRule for ShortRunwayRule {
+    fn name(&self) -> &str {
+        "short_runway"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let Some(runway) = doc
+            .processed
+            .metrics
+            .iter()
+            .filter(|m| m.kind == MetricKind::Runway)
+            .min_by(|a, b| a.value.total_cmp(&b.value))
+        else {
+            return vec![];
+        };
+        let severity = if runway.value < MIN_RUNWAY_MONTHS {
+            Severity::High
+        } else if runway.value < SHORT_RUNWAY_MONTHS {
+            Severity::Medium
+        } else {
+            return vec![];
+        };
+        let explanation = format!(
+            "Runway of {} months; the company will need to raise {}",
+            number(runway.value),
+            if severity == Severity::High { "within months" } else { "within a year" },
+        );
+        vec![flag(self.name(), severity, RiskCategory::Financial, explanation, vec![runway.page])]
+    }
+}
+
+/// A large share of revenue from the biggest customers.
+pub struct CustomerConcentrationRule;
+
+impl RiskRule for CustomerConcentrationRule {
+    fn name(&self) -> &str {
+        "customer_concentration"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        for (sentence, page) in sentences(doc) {
+            let words = words(sentence);
+            let mentions = |phrases: &[&str]| phrases.iter().any(|p| phrase_at(&words, p).next().is_some());
+            if !(mentions(CUSTOMER_WORDS) && mentions(LARGEST_WORDS) && mentions(REVENUE_WORDS)) {
+                continue;
+            }
+            let Some(share) = percentages(sentence).into_iter().filter(|p| *p <= 100.0).reduce(f64::max) else {
+                continue;
+            };
+            let severity = match share {
+                s if s >= HIGH_CONCENTRATION => Severity::High,
+                s if s >= CONCENTRATION => Severity::Medium,
+                _ => continue,
+            };
+            let explanation = format!("{}% of revenue depends on the largest customers: \"{}\"", number(share), sentence);
+            flags.push(flag(self.name(), severity, RiskCategory::Financial, explanation, vec![page]));
+        }
+        flags
+    }
+}
+
+/// A pitch deck with no financials section and no revenue or burn figures.
+pub struct MissingFinancialsRule;
+
+impl RiskRule for MissingFinancialsRule {
+    fn name(&self) -> &str {
+        "missing_financials"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let figures = [MetricKind::Revenue, MetricKind::Arr, MetricKind::Mrr, MetricKind::Gmv, MetricKind::Burn];
+        if !is_deck(doc) || has_section(doc, SectionKind::Financials) || doc.processed.metrics.iter().any(|m| figures.contains(&m.kind)) {
+            return vec![];
+        }
+        let explanation = "No financials: the deck has no financial section and states no revenue or burn".to_string();
+        vec![flag(self.name(), Severity::Medium, RiskCategory::Financial, explanation, vec![])]
+    }
+}
+
+/// A pitch deck that does not present its team.
+pub struct MissingTeamRule;
+
+impl RiskRule for MissingTeamRule {
+    fn name(&self) -> &str {
+        "missing_team"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        if !is_deck(doc) || has_section(doc, SectionKind::Team) || doc.processed.metrics.iter().any(|m| m.kind == MetricKind::Headcount) {
+            return vec![];
+        }
+        let explanation = "No team section: the deck does not say who is building the company".to_string();
+        vec![flag(self.name(), Severity::Medium, RiskCategory::Team, explanation, vec![])]
+    }
+}
+
+/// Projected yearly figures that stay flat and then shoot up, or multiply
+/// many times over a few years. Growth a company reports for past years is
+/// a track record, not a projection, so only jumps into projected years
+/// count.
+pub struct HockeyStickRule;
+
+/// Growth in a single year that counts as a jump, after a year of at most
+/// `FLAT_GROWTH`.
+const JUMP_GROWTH: f64 = 3.0;
+const FLAT_GROWTH: f64 = 1.5;
+
+/// Growth from first to last year that is implausible on its own.
+const TOTAL_GROWTH: f64 = 20.0;
+
+/// Words that mark figures as projected rather than reported.
+const PROJECTION_WORDS: &[&str] = &[
+    "projected", "projection", "projections", "forecast", "forecasts", "forecasted", "plan", "planned",
+    "target", "targets", "budget", "estimate", "estimated", "expected", "outlook",
+];
+
+impl RiskRule for HockeyStickRule {
+    fn name(&self) -> &str {
+        "hockey_stick"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        for Series { name, mut points, unit, pages, projected } in yearly_series(doc) {
+            points.sort_by_key(|(year, _)| *year);
+            points.dedup_by_key(|(year, _)| *year);
+            if points.len() < 3 || points.iter().any(|(_, v)| *v <= 0.0) {
+                continue;
+            }
+            let growth: Vec<f64> = points.windows(2).map(|w| w[1].1 / w[0].1).collect();
+            let (jump_at, jump) = growth
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(i, g)| (i, *g))
+                .expect("at least two growth steps");
+            let flat_before = growth[..jump_at].iter().any(|g| *g <= FLAT_GROWTH);
+            let (first, last) = (points[0], points[points.len() - 1]);
+            let total = last.1 / first.1;
+            let jumps = jump >= JUMP_GROWTH && flat_before && projected.contains(&points[jump_at + 1].0);
+            let multiplies = total >= TOTAL_GROWTH && projected.contains(&last.0);
+            if !(jumps || multiplies) {
+                continue;
+            }
+            let explanation = format!(
+                "Hockey-stick projection: {} goes from {} in {} to {} in {} ({}x), with a {}x jump in {}",
+                name,
+                amount(first.1, unit.as_deref()),
+                first.0,
+                amount(last.1, unit.as_deref()),
+                last.0,
+                number(total),
+                number(jump),
+                points[jump_at + 1].0,
+            );
+            flags.push(flag(self.name(), Severity::Medium, RiskCategory::Financial, explanation, pages));
+        }
+        flags
+    }
+}
+
+/// A figure over years, as `(year, value)` points.
+struct Series {
+    name: String,
+    points: Vec<(u32, f64)>,
+    unit: Option<String>,
+    pages: Vec<u32>,
+    /// Years whose figures are projected.
+    projected: Vec<u32>,
+}
+
+/// Yearly series of growth figures, from metrics stated per year and from
+/// charts over years.
+fn yearly_series(doc: &DocumentContext) -> Vec<Series> {
+    // Years after the document was written can only be projections, as
+    // can figures on a page headed as such.
+    let written = doc.extracted.metadata.created_at.as_ref().or(doc.extracted.metadata.modified_at.as_ref());
+    let written: Option<u32> = written.and_then(|d| d.get(..4)?.parse().ok());
+    let future = |year: u32| written.is_some_and(|w| year > w);
+    let headed: Vec<u32> = doc
+        .processed
+        .text_blocks
+        .iter()
+        .filter(|b| matches!(b.block_type.as_str(), "title" | "heading") && marks_projection(&b.content))
+        .map(|b| b.page)
+        .collect();
+
+    let mut series = Vec::new();
+    for kind in [MetricKind::Revenue, MetricKind::Arr, MetricKind::Mrr, MetricKind::Gmv, MetricKind::Users] {
+        let metrics: Vec<&Metric> = doc
+            .processed
+            .metrics
+            .iter()
+            .filter(|m| m.kind == kind && m.period.as_ref().is_some_and(|p| p.len() == 4))
+            .collect();
+        let Some(unit) = metrics.first().map(|m| m.unit.clone()) else {
+            continue;
+        };
+        let points: Vec<(u32, f64)> = metrics
+            .iter()
+            .filter(|m| m.unit == unit)
+            .filter_map(|m| Some((m.period.as_ref()?.parse().ok()?, m.value)))
+            .collect();
+        let projected = metrics
+            .iter()
+            .filter(|m| m.unit == unit)
+            .filter_map(|m| Some((m, m.period.as_ref()?.parse().ok()?)))
+            .filter(|(m, year)| future(*year) || headed.contains(&m.page) || marks_projection(&m.text))
+            .map(|(_, year)| year)
+            .collect();
+        let mut pages: Vec<u32> = metrics.iter().map(|m| m.page).collect();
+        pages.sort();
+        pages.dedup();
+        series.push(Series {
+            name: metric_label(kind).to_string(),
+            points,
+            unit: unit.filter(|u| u.len() == 3),
+            pages,
+            projected,
+        });
+    }
+
+    for element in &doc.processed.charts {
+        let chart = &element.chart;
+        let years: Option<Vec<u32>> = chart
+            .categories
+            .iter()
+            .map(|c| match cell_value::parse(c, None) {
+                CellValue::Date { value } if value.len() == 4 => value.parse().ok(),
+                _ => None,
+            })
+            .collect();
+        let Some(years) = years else {
+            continue;
+        };
+        let all_projected = headed.contains(&element.page) || chart.title.as_deref().is_some_and(marks_projection);
+        let projected: Vec<u32> = years
+            .iter()
+            .zip(&chart.categories)
+            .filter(|(year, category)| all_projected || future(**year) || marks_projection(category))
+            .map(|(year, _)| *year)
+            .collect();
+        for s in &chart.series {
+            let points = years.iter().zip(&s.values).filter_map(|(y, v)| Some((*y, (*v)?))).collect();
+            let name = chart.title.clone().filter(|_| chart.series.len() == 1).unwrap_or_else(|| s.name.clone());
+            series.push(Series {
+                name,
+                points,
+                unit: None,
+                pages: vec![element.page],
+                projected: projected.clone(),
+            });
+        }
+    }
+    series
+}
+
+/// Whether `text` marks figures as projected: `forecast`, `plan` and the
+/// like, or a year with an estimate suffix such as `2026E` or `FY27F`.
+fn marks_projection(text: &str) -> bool {
+    words(text).iter().any(|w| {
+        let year = w.strip_prefix("fy").unwrap_or(w);
+        let estimate = year
+            .strip_suffix(['e', 'f', 'p'])
+            .is_some_and(|y| matches!(y.len(), 2 | 4) && y.chars().all(|c| c.is_ascii_digit()));
+        estimate || PROJECTION_WORDS.contains(&w.as_str())
+    })
+}
+
+/// Claims that reference data contradicts.
+pub struct ContradictedClaimsRule;
+
+impl RiskRule for ContradictedClaimsRule {
+    fn name(&self) -> &str {
+        "contradicted_claims"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        doc.processed
+            .verifications
+            .iter()
+            .filter(|v| v.verdict == Verdict::Contradicted)
+            .filter_map(|v| {
+                let evidence = v.evidence.first()?;
+                let claim = &v.claim;
+                let explanation = match (&claim.value, &evidence.value, v.delta) {
+                    (ClaimValue::Number { value, unit }, ClaimValue::Number { value: reference, unit: reference_unit }, Some(delta)) => format!(
+                        "{} of {} is {}% {} the {} reported by {}",
+                        claim_label(claim),
+                        amount(*value, unit.as_deref()),
+                        number(delta.abs() * 100.0),
+                        if delta > 0.0 { "above" } else { "below" },
+                        amount(*reference, reference_unit.as_deref()),
+                        evidence.source,
+                    ),
+                    (claimed, reference, _) => format!(
+                        "{} \"{}\" disagrees with \"{}\" reported by {}",
+                        claim_label(claim),
+                        text(claimed),
+                        text(reference),
+                        evidence.source,
+                    ),
+                };
+                let category = if claim.kind == ClaimKind::MarketSize { RiskCategory::Market } else { RiskCategory::Consistency };
+                Some(flag(self.name(), Severity::High, category, explanation, claim.page.into_iter().collect()))
+            })
+            .collect()
+    }
+}
+
+/// Claims that no reference data confirms: checked and not found, or,
+/// when nothing was checked, market sizes that cite no source.
+pub struct UnverifiableClaimsRule;
+
+impl RiskRule for UnverifiableClaimsRule {
+    fn name(&self) -> &str {
+        "unverifiable_claims"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let (claims, reason): (Vec<Claim>, &str) = if doc.processed.verifications.is_empty() {
+            let unsourced = doc.processed.market_claims.iter().filter(|c| c.source.is_none()).map(Claim::from).collect();
+            (unsourced, "cite no source")
+        } else {
+            let unverifiable = doc
+                .processed
+                .verifications
+                .iter()
+                .filter(|v| v.verdict == Verdict::Unverifiable)
+                .map(|v| v.claim.clone())
+                .collect();
+            (unverifiable, "could not be checked against reference data")
+        };
+        if claims.is_empty() {
+            return vec![];
+        }
+        let listed: Vec<String> = claims
+            .iter()
+            .map(|c| format!("{} {}", claim_label(c), text(&c.value)))
+            .collect();
+        let mut pages: Vec<u32> = claims.iter().filter_map(|c| c.page).collect();
+        pages.sort();
+        pages.dedup();
+        let explanation = format!(
+            "{} claim{} {}: {}",
+            claims.len(),
+            if claims.len() == 1 { "" } else { "s" },
+            reason,
+            listed.join(", ")
+        );
+        vec![flag(self.name(), Severity::Low, RiskCategory::Market, explanation, pages)]
+    }
+}
+
+/// A serviceable market larger than the total one, or an obtainable
+/// market larger than the serviceable one.
+pub struct MarketSizeOrderRule;
+
+impl RiskRule for MarketSizeOrderRule {
+    fn name(&self) -> &str {
+        "market_size_order"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let largest = |kind: MarketSizeKind| {
+            doc.processed
+                .market_claims
+                .iter()
+                .filter(|c| c.kind == kind)
+                .max_by(|a, b| a.value.total_cmp(&b.value))
+        };
+        let sizes = [largest(MarketSizeKind::Tam), largest(MarketSizeKind::Sam), largest(MarketSizeKind::Som)];
+        let present: Vec<&MarketClaim> = sizes.into_iter().flatten().collect();
+        let mut flags = Vec::new();
+        for pair in present.windows(2) {
+            let (outer, inner) = (pair[0], pair[1]);
+            let comparable = outer.currency.is_none() || inner.currency.is_none() || outer.currency == inner.currency;
+            if !comparable || inner.value <= outer.value {
+                continue;
+            }
+            let explanation = format!(
+                "{} of {} exceeds {} of {}; each market should contain the next",
+                market_label(inner.kind),
+                amount(inner.value, inner.currency.as_deref()),
+                market_label(outer.kind),
+                amount(outer.value, outer.currency.as_deref()),
+            );
+            let mut pages = vec![outer.page, inner.page];
+            pages.dedup();
+            flags.push(flag(self.name(), Severity::High, RiskCategory::Consistency, explanation, pages));
+        }
+        flags
+    }
+}
+
+/// One metric stated with different values for the same period.
+pub struct InconsistentMetricsRule;
+
+impl RiskRule for InconsistentMetricsRule {
+    fn name(&self) -> &str {
+        "inconsistent_metrics"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let metrics = &doc.processed.metrics;
+        let mut flags = Vec::new();
+        let mut seen: Vec<(MetricKind, Option<&str>)> = Vec::new();
+        for (i, a) in metrics.iter().enumerate() {
+            for b in &metrics[i + 1..] {
+                // Without a period, two figures on one page are more likely a
+                // before and after than a contradiction.
+                let same = a.kind == b.kind && a.unit == b.unit && a.period == b.period && (a.period.is_some() || a.page != b.page);
+                let key = (a.kind, a.period.as_deref());
+                if !same || a.value == b.value || seen.contains(&key) {
+                    continue;
+                }
+                let spread = (a.value - b.value).abs() / a.value.abs().max(b.value.abs());
+                if spread <= MAX_METRIC_SPREAD {
+                    continue;
+                }
+                seen.push(key);
+                let explanation = format!(
+                    "{}{} is stated as {} on page {} and {} on page {}",
+                    metric_label(a.kind),
+                    a.period.as_ref().map(|p| format!(" for {}", p)).unwrap_or_default(),
+                    amount(a.value, a.unit.as_deref()),
+                    a.page,
+                    amount(b.value, b.unit.as_deref()),
+                    b.page,
+                );
+                let mut pages = vec![a.page, b.page];
+                pages.dedup();
+                flags.push(flag(self.name(), Severity::Medium, RiskCategory::Consistency, explanation, pages));
+            }
+        }
+        flags
+    }
+}
+
+/// Litigation, investigations, missing licenses and IP held by others.
+pub struct LegalExposureRule;
+
+impl RiskRule for LegalExposureRule {
+    fn name(&self) -> &str {
+        "legal_exposure"
+    }
+
+    fn evaluate(&self, doc: &DocumentContext) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        for (sentence, page) in sentences(doc) {
+            let words = words(sentence);
+            let found = LEGAL_TERMS.iter().find(|term| {
+                phrase_at(&words, term).any(|at| {
+                    !words[at.saturating_sub(3)..at].iter().any(|w| NEGATIONS.contains(&w.as_str()))
+                })
+            });
+            if let Some(term) = found {
+                let explanation = format!("Possible legal exposure ({}): \"{}\"", term, sentence);
+                flags.push(flag(self.name(), Severity::Medium, RiskCategory::Legal, explanation, vec![page]));
+            }
+        }
+        flags
+    }
+}
+
+/// Whether the document reads as a pitch deck: it covers several of the
+/// standard sections.
+fn is_deck(doc: &DocumentContext) -> bool {
+    let mut kinds: Vec<SectionKind> = doc.processed.structure.sections.iter().filter_map(|s| s.kind).collect();
+    kinds.sort_by_key(|k| *k as u8);
+    kinds.dedup();
+    kinds.len() >= MIN_DECK_SECTIONS
+}
+
+fn has_section(doc: &DocumentContext, kind: SectionKind) -> bool {
+    doc.processed.structure.sections.iter().any(|s| s.kind == Some(kind))
+}
+
+/// Sentences of the document's body text with their pages.
+fn sentences(doc: &DocumentContext) -> Vec<(&str, u32)> {
+    doc.processed
+        .text_blocks
+        .iter()
+        .filter(|b| !matches!(b.block_type.as_str(), "header" | "footer" | "page_number"))
+        .flat_map(|b| extractors::sentences(&b.content).into_iter().map(move |s| (s.trim(), b.page)))
+        .collect()
+}
+
+/// Lowercased words of a sentence, without surrounding punctuation.
+fn words(sentence: &str) -> Vec<String> {
+    extractors::tokens(sentence).iter().map(|t| extractors::normalize_word(t)).collect()
+}
+
+/// Word positions where `phrase`, lowercase words separated by spaces,
+/// starts in `words`.
+fn phrase_at<'a>(words: &'a [String], phrase: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let phrase: Vec<&str> = phrase.split(' ').collect();
+    (0..words.len().saturating_sub(phrase.len() - 1))
+        .filter(move |&at| words[at..at + phrase.len()].iter().zip(&phrase).all(|(w, p)| w == p))
+}
+
+/// Percentages stated in a sentence.
+fn percentages(sentence: &str) -> Vec<f64> {
+    sentence
+        .split_whitespace()
+        .filter_map(|t| normalize::parse_quantity(t.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | ':' | '.')), None))
+        .filter(|q| q.unit == Some(Unit::Percent))
+        .map(|q| q.value)
+        .collect()
+}
+
+/// A figure rounded for reading: `2.4`, `18`.
+fn number(value: f64) -> String {
+    let rounded = format!("{:.1}", value);
+    rounded.strip_suffix(".0").unwrap_or(&rounded).to_string()
+}
+
+/// An amount as a deck would write it: `$2.4M`, `EUR 9B`, `18 months`.
+fn amount(value: f64, unit: Option<&str>) -> String {
+    let (scaled, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "B"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e4 => (value / 1e3, "K"),
+        _ => (value, ""),
+    };
+    let figure = format!("{}{}", number(scaled), suffix);
+    match unit {
+        Some("USD") => format!("${}", figure),
+        Some("%") => format!("{}%", figure),
+        Some(unit) if unit.len() == 3 && unit.chars().all(|c| c.is_ascii_uppercase()) => format!("{} {}", unit, figure),
+        Some(unit) => format!("{} {}", figure, unit),
+        None => figure,
+    }
+}
+
+fn text(value: &ClaimValue) -> String {
+    match value {
+        ClaimValue::Number { value, unit } => amount(*value, unit.as_deref()),
+        ClaimValue::Text { value } => value.clone(),
+    }
+}
+
+fn claim_label(claim: &Claim) -> String {
+    match claim.attribute.as_str() {
+        "tam" | "sam" | "som" => claim.attribute.to_uppercase(),
+        "market_size" => "Market size".to_string(),
+        attribute => {
+            let attribute = attribute.replace('_', " ");
+            let mut chars = attribute.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        }
+    }
+}
+
+fn market_label(kind: MarketSizeKind) -> &'static str {
+    match kind {
+        MarketSizeKind::Tam => "TAM",
+        MarketSizeKind::Sam => "SAM",
+        MarketSizeKind::Som => "SOM",
+        MarketSizeKind::Market => "Market size",
+    }
+}
+
+fn metric_label(kind: MetricKind) -> &'static str {
+    match kind {
+        MetricKind::Arr => "ARR",
+        MetricKind::Mrr => "MRR",
+        MetricKind::Revenue => "Revenue",
+        MetricKind::Gmv => "GMV",
+        MetricKind::GrossMargin => "Gross margin",
+        MetricKind::Cac => "CAC",
+        MetricKind::Ltv => "LTV",
+        MetricKind::Churn => "Churn",
+        MetricKind::Nrr => "Net revenue retention",
+        MetricKind::Burn => "Burn",
+        MetricKind::Runway => "Runway",
+        MetricKind::Headcount => "Headcount",
+        MetricKind::Users => "Users",
+    }
+}
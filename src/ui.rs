@@ -166,11 +166,11 @@ pub fn rule() {
 
 pub fn status_line(status: &str, message: &str) {
     let symbol = match status {
-        "ok" => format!("[+]").green(),
-        "err" => format!("[-]").red(),
-        "warn" => format!("[!]").yellow(),
-        "info" => format!("[*]").bright_blue(),
-        _ => format!("[?]").bright_black(),
+        "ok" => "[+]".green(),
+        "err" => "[-]".red(),
+        "warn" => "[!]".yellow(),
+        "info" => "[*]".bright_blue(),
+        _ => "[?]".bright_black(),
     };
     println!("  {} {}", symbol, message);
 }
@@ -180,6 +180,12 @@ pub struct Spinner {
     current: usize,
 }
 
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Spinner {
     pub fn new() -> Self {
         Self {
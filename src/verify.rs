@@ -0,0 +1,501 @@
+// Verification of claims against reference data
+use crate::{error::*, models::*, normalize::{self, FxRates, Unit}, pipeline::*};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Relative difference within which a figure counts as supported.
+const DEFAULT_TOLERANCE: f64 = 0.2;
+
+/// A source of reference values: a local dataset, a public API, ...
+pub trait DataProvider: Send + Sync {
+    /// Short identifier, recorded on the evidence it provides.
+    fn name(&self) -> &str;
+
+    /// Reference values that may bear on `claim`. Providers may return
+    /// more than matches; the verifier keeps those for the same attribute,
+    /// subject, year and geography.
+    fn lookup(&self, claim: &Claim) -> Result<Vec<Evidence>>;
+}
+
+/// Checks claims against every provider, calling a figure supported when
+/// the closest reference value is within a tolerance of it, or equal for
+/// years, and contradicted otherwise. Claims no provider knows about are
+/// unverifiable.
+pub struct ClaimVerifier {
+    providers: Vec<Box<dyn DataProvider>>,
+    tolerance: f64,
+    fx: Option<FxRates>,
+}
+
+impl Default for ClaimVerifier {
+    fn default() -> Self {
+        Self {
+            providers: vec![],
+            tolerance: DEFAULT_TOLERANCE,
+            fx: None,
+        }
+    }
+}
+
+impl ClaimVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: impl DataProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Relative difference still counted as support, 0.2 (20%) by default.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Rates for comparing amounts stated in different currencies.
+    pub fn with_fx_rates(mut self, rates: FxRates) -> Self {
+        self.fx = Some(rates);
+        self
+    }
+
+    /// Check one claim. Provider failures are logged and count as no
+    /// evidence, so an unreachable API makes a claim unverifiable rather
+    /// than failing the document.
+    pub fn verify(&self, claim: &Claim) -> Verification {
+        let mut found: Vec<(f64, Evidence)> = Vec::new();
+        for provider in &self.providers {
+            let evidence = match provider.lookup(claim) {
+                Ok(evidence) => evidence,
+                Err(e) => {
+                    tracing::warn!("data provider {} failed for {}: {}", provider.name(), claim.attribute, e);
+                    continue;
+                }
+            };
+            for evidence in evidence.into_iter().filter(|e| matches(claim, e)) {
+                if let Some(difference) = self.difference(&claim.value, &evidence.value) {
+                    found.push((difference, evidence));
+                }
+            }
+        }
+        found.sort_by(|a, b| a.0.abs().total_cmp(&b.0.abs()));
+
+        let (verdict, delta) = match found.first() {
+            None => (Verdict::Unverifiable, None),
+            Some((difference, _)) => {
+                let tolerance = if is_year(&claim.attribute) { 0.0 } else { self.tolerance };
+                let verdict = if difference.abs() <= tolerance { Verdict::Supported } else { Verdict::Contradicted };
+                let delta = matches!(claim.value, ClaimValue::Number { .. }).then_some(*difference);
+                (verdict, delta)
+            }
+        };
+        Verification {
+            claim: claim.clone(),
+            verdict,
+            evidence: found.into_iter().map(|(_, e)| e).collect(),
+            delta,
+        }
+    }
+
+    pub fn verify_all(&self, claims: &[Claim]) -> Vec<Verification> {
+        claims.iter().map(|c| self.verify(c)).collect()
+    }
+
+    /// How far a claimed value is from a reference value: the relative
+    /// difference of figures, in the reference's currency, or for text 0
+    /// when they agree and 1 when not. None when they cannot be compared.
+    fn difference(&self, claimed: &ClaimValue, reference: &ClaimValue) -> Option<f64> {
+        match (claimed, reference) {
+            (ClaimValue::Number { value, unit }, ClaimValue::Number { value: expected, unit: expected_unit }) => {
+                let value = match (unit, expected_unit) {
+                    (Some(from), Some(to)) if !from.eq_ignore_ascii_case(to) => self.fx.as_ref()?.convert(*value, from, to)?,
+                    _ => *value,
+                };
+                (*expected != 0.0).then(|| value / expected - 1.0)
+            }
+            (ClaimValue::Text { value }, ClaimValue::Text { value: expected }) => {
+                let (a, b) = (value.trim().to_lowercase(), expected.trim().to_lowercase());
+                Some(if a.contains(&b) || b.contains(&a) { 0.0 } else { 1.0 })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether `evidence` is about what `claim` states: the same attribute,
+/// the subject the evidence names if any, and the same year and geography
+/// where both give one. Evidence about a named company or market never
+/// stands in for a claim that names none.
+fn matches(claim: &Claim, evidence: &Evidence) -> bool {
+    let same = |a: &Option<String>, b: &Option<String>, contained: bool| match (a, b) {
+        (Some(a), Some(b)) => {
+            let (a, b) = (a.to_lowercase(), b.to_lowercase());
+            a == b || (contained && (a.contains(&b) || b.contains(&a)))
+        }
+        _ => true,
+    };
+    same_attribute(&claim.attribute, &evidence.attribute)
+        && (evidence.subject.is_none() || (claim.subject.is_some() && same(&claim.subject, &evidence.subject, true)))
+        && same(&claim.geography, &evidence.geography, false)
+        && (claim.year.is_none() || evidence.year.is_none() || claim.year == evidence.year)
+}
+
+/// Attributes compare without regard to case or spacing, and a market
+/// size not labelled otherwise is compared with total addressable market.
+fn same_attribute(a: &str, b: &str) -> bool {
+    let canonical = |s: &str| {
+        let s = s.trim().to_lowercase().replace([' ', '-'], "_");
+        if s == "market_size" { "tam".to_string() } else { s }
+    };
+    canonical(a) == canonical(b)
+}
+
+/// Whether an attribute is a year, like `founded`, which must match
+/// exactly: 2017 is not within 20% of 2019 in any useful sense.
+fn is_year(attribute: &str) -> bool {
+    let attribute = attribute.trim().to_lowercase();
+    matches!(attribute.as_str(), "founded" | "incorporated" | "year") || attribute.ends_with("year")
+}
+
+/// Serves reference values from a local dataset, so claims can be checked
+/// offline.
+#[derive(Debug, Clone, Default)]
+pub struct FileProvider {
+    name: String,
+    records: Vec<Evidence>,
+}
+
+impl FileProvider {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            records: vec![],
+        }
+    }
+
+    pub fn with_record(mut self, record: Evidence) -> Self {
+        self.records.push(record);
+        self
+    }
+
+    /// Read a dataset from a `.csv` file with a header row, or otherwise a
+    /// JSON array of records. Both use the fields `subject`, `attribute`,
+    /// `value`, `unit`, `year`, `geography` and `source`; only `attribute`
+    /// and `value` are required, and values like `$50B` are normalized.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path.file_name().map_or("file".to_string(), |n| n.to_string_lossy().to_string());
+        let text = std::fs::read_to_string(path)?;
+        let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let records = if is_csv { csv_records(&text)? } else { json_records(&text)? };
+        Ok(Self {
+            records: records.into_iter().map(|r| r.into_evidence(&name)).collect(),
+            name,
+        })
+    }
+
+    pub fn records(&self) -> &[Evidence] {
+        &self.records
+    }
+}
+
+impl DataProvider for FileProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, claim: &Claim) -> Result<Vec<Evidence>> {
+        Ok(self
+            .records
+            .iter()
+            .filter(|r| same_attribute(&r.attribute, &claim.attribute))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Queries an HTTP API through a locally installed `curl`. The URL is a
+/// template whose `{kind}`, `{attribute}`, `{subject}`, `{year}` and
+/// `{geography}` are filled from the claim; the response must be records
+/// as read by `FileProvider::load`, as a JSON array or under `records`.
+/// Headers are passed to curl as a config on stdin, never on its command
+/// line, so keys stay out of process listings.
+#[derive(Debug, Clone)]
+pub struct HttpProvider {
+    name: String,
+    url: String,
+    binary: String,
+    headers: Vec<(String, String)>,
+    timeout_secs: u64,
+}
+
+impl HttpProvider {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            binary: "curl".to_string(),
+            headers: vec![],
+            timeout_secs: 10,
+        }
+    }
+
+    /// Path or name of the executable, `curl` by default.
+    pub fn with_binary(mut self, binary: &str) -> Self {
+        self.binary = binary.to_string();
+        self
+    }
+
+    /// Send a header with every request, e.g. an API key.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_timeout(mut self, seconds: u64) -> Self {
+        self.timeout_secs = seconds;
+        self
+    }
+
+    /// The URL requested for `claim`.
+    pub fn url_for(&self, claim: &Claim) -> String {
+        let kind = serde_json::to_value(claim.kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        let year = claim.year.map(|y| y.to_string()).unwrap_or_default();
+        [
+            ("{kind}", kind.as_str()),
+            ("{attribute}", claim.attribute.as_str()),
+            ("{subject}", claim.subject.as_deref().unwrap_or("")),
+            ("{year}", year.as_str()),
+            ("{geography}", claim.geography.as_deref().unwrap_or("")),
+        ]
+        .iter()
+        .fold(self.url.clone(), |url, (placeholder, value)| url.replace(placeholder, &encode(value)))
+    }
+}
+
+impl DataProvider for HttpProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, claim: &Claim) -> Result<Vec<Evidence>> {
+        let mut child = Command::new(&self.binary)
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .args(["--max-time", &self.timeout_secs.to_string()])
+            .args(["--config", "-"])
+            .arg(self.url_for(claim))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| TraceDeckError::ProviderError(format!("cannot run {}: {}", self.binary, e)))?;
+
+        let config: String = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("header = \"{}\"\n", quote(&format!("{}: {}", name, value))))
+            .collect();
+        if let Some(mut stdin) = child.stdin.take() {
+            // A binary that exits without reading its config closes the pipe.
+            match stdin.write_all(config.as_bytes()) {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+                _ => {}
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(TraceDeckError::ProviderError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        let records = json_records(&String::from_utf8_lossy(&output.stdout))?;
+        Ok(records.into_iter().map(|r| r.into_evidence(&self.name)).collect())
+    }
+}
+
+/// Quote a value for a curl config file. Line breaks cannot be part of a
+/// header and are dropped.
+fn quote(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, '\r' | '\n'))
+        .flat_map(|c| match c {
+            '"' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// Percent-encode a URL query value.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// A reference value as written in a dataset.
+#[derive(Debug, serde::Deserialize)]
+struct Record {
+    subject: Option<String>,
+    attribute: String,
+    value: serde_json::Value,
+    unit: Option<String>,
+    year: Option<u32>,
+    geography: Option<String>,
+    source: Option<String>,
+}
+
+impl Record {
+    fn into_evidence(self, provider: &str) -> Evidence {
+        let unit = self.unit.filter(|u| !u.is_empty());
+        let value = match self.value {
+            serde_json::Value::Number(n) => ClaimValue::Number { value: n.as_f64().unwrap_or(0.0), unit },
+            serde_json::Value::String(s) => value(&s, unit),
+            other => ClaimValue::Text { value: other.to_string() },
+        };
+        Evidence {
+            subject: self.subject.filter(|s| !s.is_empty()),
+            attribute: self.attribute,
+            value,
+            year: self.year,
+            geography: self.geography.filter(|g| !g.is_empty()),
+            source: self.source.filter(|s| !s.is_empty()).unwrap_or_else(|| provider.to_string()),
+            provider: provider.to_string(),
+        }
+    }
+}
+
+/// A figure when `text` reads as one, like `$50B` or `12%`, else text.
+fn value(text: &str, unit: Option<String>) -> ClaimValue {
+    match normalize::parse_quantity(text, None) {
+        Some(q) if !matches!(q.unit, Some(Unit::Other(_))) => {
+            let written = match q.unit {
+                Some(Unit::Currency(code)) => Some(code),
+                Some(Unit::Percent) => Some("%".to_string()),
+                _ => None,
+            };
+            ClaimValue::Number { value: q.value, unit: unit.or(written) }
+        }
+        _ => ClaimValue::Text { value: text.trim().to_string() },
+    }
+}
+
+fn json_records(text: &str) -> Result<Vec<Record>> {
+    let json: serde_json::Value = serde_json::from_str(text)?;
+    let records = match json {
+        serde_json::Value::Object(mut object) => object.remove("records").unwrap_or_default(),
+        other => other,
+    };
+    Ok(serde_json::from_value(records)?)
+}
+
+fn csv_records(text: &str) -> Result<Vec<Record>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = csv_fields(lines.next().unwrap_or_default())
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let attribute = column("attribute").ok_or_else(|| TraceDeckError::ParseError("CSV dataset has no attribute column".into()))?;
+    let value = column("value").ok_or_else(|| TraceDeckError::ParseError("CSV dataset has no value column".into()))?;
+
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields = csv_fields(line);
+            let field = |at: Option<usize>| at.and_then(|at| fields.get(at)).map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+            let year = match field(column("year")) {
+                Some(year) => Some(year.parse().map_err(|_| {
+                    TraceDeckError::ParseError(format!("CSV dataset row {}: invalid year {}", i + 2, year))
+                })?),
+                None => None,
+            };
+            Ok(Record {
+                subject: field(column("subject")),
+                attribute: field(Some(attribute)).unwrap_or_default(),
+                value: serde_json::Value::String(field(Some(value)).unwrap_or_default()),
+                unit: field(column("unit")),
+                year,
+                geography: field(column("geography")),
+                source: field(column("source")),
+            })
+        })
+        .collect()
+}
+
+/// Fields of a CSV line; double quotes enclose commas and `""` escapes a
+/// quote.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().expect("fields start non-empty").push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().expect("fields start non-empty").push(c),
+        }
+    }
+    fields
+}
+
+/// Checks the market-size claims found before it, and any claims given
+/// up front such as company facts or funding history, into
+/// `ProcessedData::verifications`. Not part of the default pipeline, as it
+/// needs providers; add it before the risk stage, e.g. with
+/// `with_stage_after("market", ...)`, so its verdicts are flagged.
+pub struct VerificationStage {
+    verifier: ClaimVerifier,
+    market: Option<String>,
+    claims: Vec<Claim>,
+}
+
+impl VerificationStage {
+    pub fn new(verifier: ClaimVerifier) -> Self {
+        Self {
+            verifier,
+            market: None,
+            claims: vec![],
+        }
+    }
+
+    /// The market the document addresses, as the subject of its market
+    /// sizes.
+    pub fn with_market(mut self, market: &str) -> Self {
+        self.market = Some(market.to_string());
+        self
+    }
+
+    /// Check `claim` too, e.g. a company fact taken from elsewhere.
+    pub fn with_claim(mut self, claim: Claim) -> Self {
+        self.claims.push(claim);
+        self
+    }
+}
+
+impl Stage for VerificationStage {
+    fn name(&self) -> &str {
+        "verification"
+    }
+
+    fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+        let claims: Vec<Claim> = ctx
+            .processed
+            .market_claims
+            .iter()
+            .map(|c| Claim { subject: self.market.clone(), ..Claim::from(c) })
+            .chain(self.claims.iter().cloned())
+            .collect();
+        ctx.processed.verifications = self.verifier.verify_all(&claims);
+        Ok(())
+    }
+}
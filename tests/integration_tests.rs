@@ -4,8 +4,1491 @@ mod tests {
 
     #[tokio::test]
     async fn test_document_processing() {
-        // Test basic processing
-        assert!(true);
+        let path = write_pdf(
+            "processing.pdf",
+            &["BT /F1 24 Tf 72 700 Td (Hello) Tj ( World) Tj ET"],
+        );
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(result.extracted.metadata.file_type, "pdf");
+        assert!(result.extracted.text.contains("Hello World"));
+    }
+
+    #[tokio::test]
+    async fn test_pdf_text_extraction() {
+        let path = write_pdf(
+            "text.pdf",
+            &[
+                "BT /F1 32 Tf 72 700 Td (Acme Robotics) Tj 0 -40 Td [(Series) -300 (A)] TJ ET",
+                "BT /F1 18 Tf 72 700 Td (\\223Traction\\224 \\200 2.4M ARR) Tj ET",
+            ],
+        );
+
+        let parser = DocumentParser::new(path.to_str().unwrap()).unwrap();
+        let extracted = parser.extract().await.unwrap();
+
+        assert_eq!(extracted.metadata.pages, Some(2));
+        assert!(extracted.text.contains("Acme Robotics\nSeries A"));
+        assert!(extracted.text.contains("\u{201c}Traction\u{201d} \u{20ac} 2.4M ARR"));
+    }
+
+    #[tokio::test]
+    async fn test_per_page_content() {
+        let path = write_pdf(
+            "pages.pdf",
+            &[
+                "BT /F1 32 Tf 72 500 Td (Problem) Tj ET",
+                "BT /F1 32 Tf 72 500 Td (Traction) Tj ET",
+            ],
+        );
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.pages.len(), 2);
+        assert_eq!(extracted.page(2).unwrap().text, "Traction");
+        assert_eq!(extracted.pages[0].width, Some(792.0));
+        assert_eq!(extracted.pages[0].height, Some(612.0));
+
+        let image = std::env::temp_dir().join(format!("trace-deck-{}-page.png", std::process::id()));
+        image::RgbImage::new(40, 30).save(&image).unwrap();
+        let extracted = DocumentParser::new(image.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.pages.len(), 1);
+        assert_eq!(extracted.pages[0].images.len(), 1);
+        assert_eq!(extracted.pages[0].width, Some(40.0));
+    }
+
+    #[tokio::test]
+    async fn test_reading_order_and_bounding_boxes() {
+        // Two columns drawn row by row, under a full-width title.
+        let path = write_pdf(
+            "columns.pdf",
+            &["BT /F1 32 Tf 72 540 Td (Why we win today) Tj ET \
+               BT /F1 14 Tf 72 450 Td (Left one) Tj ET BT /F1 14 Tf 420 450 Td (Right one) Tj ET \
+               BT /F1 14 Tf 72 430 Td (Left two) Tj ET BT /F1 14 Tf 420 430 Td (Right two) Tj ET"],
+        );
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            extracted.pages[0].text,
+            "Why we win today\nLeft one\nLeft two\nRight one\nRight two"
+        );
+
+        let title = &extracted.pages[0].spans[0].bbox;
+        assert_eq!(title.x, 72.0);
+        assert!((title.y - (612.0 - 540.0 - 32.0 * 0.8)).abs() < 0.01);
+        assert_eq!(title.height, 32.0);
+
+        let processed = processors::process(extracted).unwrap();
+        let blocks: Vec<_> = processed.text_blocks.iter().map(|b| b.content.as_str()).collect();
+        assert_eq!(blocks, ["Why we win today", "Left one\nLeft two", "Right one\nRight two"]);
+        assert!(processed.text_blocks.iter().all(|b| b.page == 1 && b.bbox.is_some()));
+        assert_eq!(processed.text_blocks[2].bbox.unwrap().x, 420.0);
+
+        // A landscape sheet shown portrait with /Rotate 90, its text drawn
+        // along the sheet's y axis so it reads left to right on screen.
+        let path = write_pdf_with(
+            "rotated.pdf",
+            &["BT /F1 14 Tf 0 1 -1 0 112 72 Tm (Body line) Tj ET \
+               BT /F1 24 Tf 0 1 -1 0 72 72 Tm (Rotated title) Tj ET"],
+            PdfExtras { rotate: 90, ..Default::default() },
+        );
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let page = &extracted.pages[0];
+        assert_eq!((page.width, page.height), (Some(612.0), Some(792.0)));
+        assert_eq!(page.text, "Rotated title\nBody line");
+        let title = &page.spans[0].bbox;
+        assert!((title.x - 72.0).abs() < 0.01);
+        assert!((title.y - (72.0 - 24.0 * 0.8)).abs() < 0.01);
+        assert!(title.width > title.height);
+    }
+
+    #[tokio::test]
+    async fn test_vector_chart_reconstruction() {
+        // Tick labels 0/100/200 every 100pt up from the baseline at y=100.
+        let ticks = "BT /F1 10 Tf 110 97 Td (0) Tj ET \
+                     BT /F1 10 Tf 100 197 Td (100) Tj ET \
+                     BT /F1 10 Tf 100 297 Td (200) Tj ET";
+        let columns = format!(
+            "BT /F1 10 Tf 150 340 Td (Revenue \\($K\\)) Tj ET {} \
+             0.2 0.4 0.8 rg 150 100 40 80 re f 210 100 40 150 re f 270 100 40 210 re f \
+             BT /F1 10 Tf 280 312 Td (210) Tj ET \
+             BT /F1 10 Tf 160 85 Td (2022) Tj ET BT /F1 10 Tf 220 85 Td (2023) Tj ET \
+             BT /F1 10 Tf 280 85 Td (2024) Tj ET",
+            ticks
+        );
+        let line = format!(
+            "{} 0.9 0.1 0.1 RG 2 w 150 160 m 210 250 l 270 230 l S \
+             BT /F1 10 Tf 145 85 Td (Q1) Tj ET BT /F1 10 Tf 205 85 Td (Q2) Tj ET \
+             BT /F1 10 Tf 265 85 Td (Q3) Tj ET",
+            ticks
+        );
+        let path = write_pdf("chart.pdf", &[&columns, &line]);
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        let charts = &result.processed.charts;
+        assert_eq!(charts.len(), 2);
+
+        let columns = &charts[0];
+        assert_eq!((columns.page, columns.source.as_str()), (1, "vector"));
+        assert_eq!(columns.chart.chart_type, "column");
+        assert_eq!(columns.chart.title.as_deref(), Some("Revenue ($K)"));
+        assert_eq!(columns.chart.categories, ["2022", "2023", "2024"]);
+        let series = &columns.chart.series[0];
+        assert!((series.values[0].unwrap() - 80.0).abs() < 1.0);
+        assert!((series.values[1].unwrap() - 150.0).abs() < 1.0);
+        // The data label wins over the axis.
+        assert_eq!(series.values[2], Some(210.0));
+        assert!(series.confidence[2] > series.confidence[0]);
+
+        let line = &charts[1];
+        assert_eq!((line.page, line.chart.chart_type.as_str()), (2, "line"));
+        assert_eq!(line.chart.categories, ["Q1", "Q2", "Q3"]);
+        let values: Vec<f64> = line.chart.series[0].values.iter().map(|v| v.unwrap().round()).collect();
+        assert_eq!(values, [60.0, 150.0, 130.0]);
+
+        let visuals = result.processed.visual_elements.iter().filter(|v| v.element_type == "chart");
+        assert_eq!(visuals.count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_raster_chart_digitization() {
+        // Three blue columns and a red line over a black axis, on white.
+        let mut img = image::RgbImage::from_pixel(300, 200, image::Rgb([255, 255, 255]));
+        for x in 10..290 {
+            img.put_pixel(x, 190, image::Rgb([0, 0, 0]));
+        }
+        for (i, height) in [40, 80, 120].into_iter().enumerate() {
+            for x in 20 + i as u32 * 40..50 + i as u32 * 40 {
+                for y in 190 - height..190 {
+                    img.put_pixel(x, y, image::Rgb([40, 90, 200]));
+                }
+            }
+        }
+        let line = |x: u32| match x {
+            160..=220 => 150.0 - (x - 160) as f32 * 1.5,
+            _ => 60.0 + (x - 220) as f32 * 40.0 / 70.0,
+        };
+        for x in 160..=290 {
+            let y = line(x).round() as u32;
+            for dy in y - 1..=y + 1 {
+                img.put_pixel(x, dy, image::Rgb([220, 30, 30]));
+            }
+        }
+        let path = std::env::temp_dir().join(format!("trace-deck-{}-chart.png", std::process::id()));
+        img.save(&path).unwrap();
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        assert!(!result.extracted.pages[0].images[0].data.is_empty());
+
+        let charts = &result.processed.charts;
+        assert!(charts.iter().all(|c| c.source == "raster"));
+        let columns = charts.iter().find(|c| c.chart.chart_type == "column").unwrap();
+        let values: Vec<f64> = columns.chart.series[0].values.iter().map(|v| v.unwrap()).collect();
+        assert_eq!(values, [1.0 / 3.0, 2.0 / 3.0, 1.0]);
+        assert_eq!(columns.chart.bbox.unwrap().x, 20.0);
+
+        let line = charts.iter().find(|c| c.chart.chart_type == "line").unwrap();
+        let values: Vec<f64> = line.chart.series[0].values.iter().map(|v| v.unwrap()).collect();
+        assert_eq!(values.len(), 3);
+        assert!((values[0] - 40.0 / 130.0).abs() < 0.02);
+        assert!((values[1] - 1.0).abs() < 0.02);
+        assert!((values[2] - 90.0 / 130.0).abs() < 0.02);
+        assert_eq!(line.chart.value_axis.as_ref().unwrap().scale.as_deref(), Some("relative"));
+        assert_eq!(line.chart.categories, ["", "", ""]);
+
+        // With OCR, tick labels put values on the chart's scale and the
+        // labels under the axis name the categories.
+        let word = |text: &str, x: f32, y: f32| OcrWord {
+            text: text.to_string(),
+            bbox: BoundingBox::new(x, y, 16.0, 12.0),
+            confidence: 0.9,
+        };
+        let engine = MockEngine::new(vec![
+            word("$100K", 0.0, 84.0),
+            word("$50K", 0.0, 134.0),
+            word("$0", 0.0, 184.0),
+            word("2023", 27.0, 192.0),
+            word("2024", 67.0, 192.0),
+            word("2025", 107.0, 192.0),
+            word("Q1", 152.0, 192.0),
+            word("Q2", 212.0, 192.0),
+            word("Q3", 282.0, 192.0),
+        ]);
+        let pipeline = Pipeline::default()
+            .without_stage("charts")
+            .with_stage_after("market", ChartStage::new().with_ocr(engine));
+        let result = process_document_with(path.to_str().unwrap(), &pipeline).await.unwrap();
+        let charts = &result.processed.charts;
+
+        let columns = &charts.iter().find(|c| c.chart.chart_type == "column").unwrap().chart;
+        assert_eq!(columns.categories, ["2023", "2024", "2025"]);
+        let values: Vec<f64> = columns.series[0].values.iter().map(|v| v.unwrap()).collect();
+        assert_eq!(values, [40e3, 80e3, 120e3]);
+        let axis = columns.value_axis.as_ref().unwrap();
+        assert_eq!((axis.unit.as_deref(), axis.scale.as_deref()), (Some("$"), None));
+
+        let line = charts.iter().find(|c| c.chart.chart_type == "line").unwrap();
+        assert_eq!(line.chart.categories, ["Q1", "Q2", "Q3"]);
+        let values: Vec<f64> = line.chart.series[0].values.iter().map(|v| v.unwrap()).collect();
+        for (value, expected) in values.iter().zip([40e3, 130e3, 90e3]) {
+            assert!((value - expected).abs() < 2e3, "{} != {}", value, expected);
+        }
+        assert!(line.confidence > 0.25);
+
+        // Solid blocks with no axis or labels are a logo, not a chart.
+        let mut logo = image::RgbImage::from_pixel(300, 200, image::Rgb([255, 255, 255]));
+        for (i, height) in [60, 100, 140].into_iter().enumerate() {
+            for x in 60 + i as u32 * 60..100 + i as u32 * 60 {
+                for y in 170 - height..170 {
+                    logo.put_pixel(x, y, image::Rgb([230, 120, 20]));
+                }
+            }
+        }
+        let logo_path = std::env::temp_dir().join(format!("trace-deck-{}-logo.png", std::process::id()));
+        logo.save(&logo_path).unwrap();
+        let result = process_document(logo_path.to_str().unwrap()).await.unwrap();
+        assert!(result.processed.charts.is_empty(), "{:#?}", result.processed.charts);
+    }
+
+    #[tokio::test]
+    async fn test_ocr_for_image_only_pages() {
+        let path = std::env::temp_dir().join(format!("trace-deck-{}-scan.png", std::process::id()));
+        image::RgbImage::new(200, 100).save(&path).unwrap();
+
+        let word = |text: &str, x: f32, y: f32, confidence: f32| OcrWord {
+            text: text.to_string(),
+            bbox: BoundingBox::new(x, y, text.len() as f32 * 8.0, 12.0),
+            confidence,
+        };
+        let engine = MockEngine::new(vec![
+            word("grew", 75.0, 10.0, 0.8),
+            word("Revenue", 10.0, 11.0, 0.9),
+            word("3x", 10.0, 40.0, 0.96),
+        ]);
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .with_ocr(engine)
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.text, "Revenue grew\n3x");
+        let line = &extracted.pages[0].spans[0];
+        assert!((line.confidence.unwrap() - 0.85).abs() < 1e-6);
+        assert_eq!(line.bbox.x, 10.0);
+
+        let processed = processors::process(extracted).unwrap();
+        assert!(processed.text_blocks[0].confidence < 0.95);
+
+        // The default engine drives a Tesseract-compatible binary.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let script = std::env::temp_dir().join(format!("trace-deck-{}-ocr.sh", std::process::id()));
+            std::fs::write(
+                &script,
+                "#!/bin/sh\ncat > /dev/null\n\
+                 printf 'level\\tpage_num\\tblock_num\\tpar_num\\tline_num\\tword_num\\tleft\\ttop\\twidth\\theight\\tconf\\ttext\\n'\n\
+                 printf '4\\t1\\t1\\t1\\t1\\t0\\t5\\t6\\t50\\t10\\t-1\\t\\n'\n\
+                 printf '5\\t1\\t1\\t1\\t1\\t1\\t5\\t6\\t50\\t10\\t91.5\\tTraction\\n'\n",
+            )
+            .unwrap();
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+            let engine = TesseractEngine::new().with_binary(script.to_str().unwrap());
+            let words = engine.recognize(&std::fs::read(&path).unwrap()).unwrap();
+            assert_eq!(words.len(), 1);
+            assert_eq!(words[0].text, "Traction");
+            assert_eq!(words[0].bbox, BoundingBox::new(5.0, 6.0, 50.0, 10.0));
+            assert!((words[0].confidence - 0.915).abs() < 1e-6);
+
+            // An image rejected before it is read reports Tesseract's reason.
+            let script = std::env::temp_dir().join(format!("trace-deck-{}-ocr-reject.sh", std::process::id()));
+            std::fs::write(&script, "#!/bin/sh\necho 'Error in pixReadStream: Unknown format' >&2\nexit 1\n").unwrap();
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+            let engine = TesseractEngine::new().with_binary(script.to_str().unwrap());
+            match engine.recognize(&vec![0; 1 << 20]) {
+                Err(TraceDeckError::OcrError(message)) => assert_eq!(message, "Error in pixReadStream: Unknown format"),
+                other => panic!("expected an OCR error, got {:?}", other.map(|w| w.len())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_format_sniffing() {
+        // A PDF saved under a Word extension is still a PDF.
+        let pdf = write_pdf("renamed.docx", &["BT /F1 24 Tf 72 700 Td (Hello) Tj ET"]);
+        let parser = DocumentParser::new(pdf.to_str().unwrap()).unwrap();
+        assert_eq!(parser.file_type(), "pdf");
+        assert_eq!(parser.extract().await.unwrap().text, "Hello");
+
+        let deck = write_pptx("deck-download");
+        assert_eq!(DocumentParser::new(deck.to_str().unwrap()).unwrap().file_type(), "pptx");
+
+        let png = std::env::temp_dir().join(format!("trace-deck-{}-noext", std::process::id()));
+        image::RgbImage::new(4, 4)
+            .write_to(&mut std::fs::File::create(&png).unwrap(), image::ImageFormat::Png)
+            .unwrap();
+        assert_eq!(DocumentParser::new(png.to_str().unwrap()).unwrap().file_type(), "png");
+
+        let sheet = write_zip("model.txt", &[("xl/workbook.xml", b"<workbook/>".as_slice())]);
+        match DocumentParser::new(sheet.to_str().unwrap()) {
+            Err(TraceDeckError::UnsupportedFormat { detected, claimed }) => {
+                assert_eq!((detected.as_str(), claimed.as_str()), ("xlsx", "txt"));
+            }
+            _ => panic!("expected an unsupported format error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_input() {
+        let pdf = std::fs::read(write_pdf("upload.pdf", &["BT /F1 24 Tf 72 700 Td (Uploaded) Tj ET"])).unwrap();
+
+        let result = process_bytes(pdf.as_slice(), None).await.unwrap();
+        assert_eq!(result.extracted.text, "Uploaded");
+        assert_eq!(result.extracted.metadata.file_size, pdf.len() as u64);
+
+        let result = process_reader(pdf.as_slice(), Some("deck.pdf")).await.unwrap();
+        assert_eq!(result.extracted.metadata.file_type, "pdf");
+
+        let pipeline = Pipeline::new().with_stage(TextBlockStage);
+        let result = process_bytes_with(pdf.as_slice(), None, &pipeline).await.unwrap();
+        assert_eq!(result.processed.text_blocks.len(), 1);
+        assert!(result.processed.structure.sections.is_empty());
+        let result = process_reader_with(pdf.as_slice(), None, &pipeline).await.unwrap();
+        assert_eq!(result.processed.text_blocks[0].content, "Uploaded");
+
+        // Without content to go on, the filename hint decides.
+        match DocumentParser::from_bytes(b"plain text".as_slice(), Some("notes.txt")) {
+            Err(TraceDeckError::UnsupportedFormat { detected, claimed }) => {
+                assert_eq!((detected.as_str(), claimed.as_str()), ("unknown", "txt"));
+            }
+            _ => panic!("expected an unsupported format error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pdf_metadata() {
+        let xmp = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+            <x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:ModifyDate="2026-10-15T09:30:00Z">
+                <xmp:CreatorTool>Canva (<xmp:Edition>web</xmp:Edition>)</xmp:CreatorTool>
+              </rdf:Description>
+              <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Untitled design</rdf:li></rdf:Alt></dc:title>
+                <dc:creator><rdf:Seq><rdf:li>Jane Founder</rdf:li></rdf:Seq></dc:creator>
+                <dc:subject><rdf:Bag><rdf:li>seed</rdf:li><rdf:li>fintech</rdf:li></rdf:Bag></dc:subject>
+              </rdf:Description>
+            </rdf:RDF></x:xmpmeta>
+            <?xpacket end="w"?>"#;
+        let path = write_pdf_with(
+            "metadata.pdf",
+            &["BT /F1 24 Tf 72 700 Td (Acme) Tj ET"],
+            PdfExtras {
+                info: Some("<< /Title (Acme Seed Deck) /Producer (Skia/PDF m120) /CreationDate (D:20251013080000+02'00') >>"),
+                xmp: Some(xmp),
+                ..Default::default()
+            },
+        );
+
+        let metadata = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap()
+            .metadata;
+
+        // The Info dictionary wins; XMP fills in what it lacks.
+        assert_eq!(metadata.title.as_deref(), Some("Acme Seed Deck"));
+        assert_eq!(metadata.author.as_deref(), Some("Jane Founder"));
+        assert_eq!(metadata.producer.as_deref(), Some("Skia/PDF m120"));
+        // Mixed content reads in document order.
+        assert_eq!(metadata.creator_tool.as_deref(), Some("Canva (web)"));
+        assert_eq!(metadata.created_at.as_deref(), Some("2025-10-13T08:00:00+02:00"));
+        assert_eq!(metadata.modified_at.as_deref(), Some("2026-10-15T09:30:00Z"));
+        assert_eq!(metadata.keywords, ["seed", "fintech"]);
+    }
+
+    #[tokio::test]
+    async fn test_pdf_image_extraction() {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>() + ">";
+        let image = |dict: &str, data: &[u8]| {
+            let data = hex(data);
+            format!("<< /Type /XObject /Subtype /Image {} /Length {} >>\nstream\n{}\nendstream", dict, data.len(), data)
+        };
+
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image::RgbImage::from_pixel(16, 8, image::Rgb([200, 30, 30]))
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        let images = [
+            // A 2x1 raw RGB logo and a JPEG photo.
+            image(
+                "/Width 2 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /ASCIIHexDecode",
+                &[255, 0, 0, 0, 0, 255],
+            ),
+            image(
+                "/Width 16 /Height 8 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter [/ASCIIHexDecode /DCTDecode]",
+                jpeg.get_ref(),
+            ),
+            // A malformed image with no columns, skipped without failing the page.
+            image(
+                "/Width 0 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /ASCIIHexDecode",
+                &[0, 0, 0],
+            ),
+        ];
+        let path = write_pdf_with(
+            "images.pdf",
+            &[
+                "BT /F1 24 Tf 72 560 Td (Customers) Tj ET q 100 0 0 50 72 400 cm /Im1 Do Q",
+                "q 200 0 0 100 300 200 cm /Im2 Do Q q 100 0 0 50 72 400 cm /Im1 Do Q q 10 0 0 10 72 72 cm /Im3 Do Q",
+            ],
+            PdfExtras { images: &images, ..Default::default() },
+        );
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .without_ocr()
+            .extract()
+            .await
+            .unwrap();
+
+        let logo = &extracted.pages[0].images[0];
+        assert_eq!((logo.format.as_str(), logo.width, logo.height), ("png", 2, 1));
+        assert_eq!(logo.bbox, Some(BoundingBox::new(72.0, 162.0, 100.0, 50.0)));
+        let decoded = image::load_from_memory(&logo.data).unwrap().to_rgb8();
+        assert_eq!(decoded.get_pixel(1, 0).0, [0, 0, 255]);
+
+        let photo = &extracted.pages[1].images[0];
+        assert_eq!((photo.format.as_str(), photo.width, photo.height), ("jpeg", 16, 8));
+        assert_eq!(photo.data, *jpeg.get_ref());
+        assert_eq!(photo.bbox, Some(BoundingBox::new(300.0, 312.0, 200.0, 100.0)));
+
+        // Every placement is reported, with ids unique across the document.
+        let ids: Vec<_> = extracted.images().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["img_0", "img_1", "img_2"]);
+    }
+
+    #[tokio::test]
+    async fn test_pdf_table_detection() {
+        // Ruled grid: "Segment" spans two rows, "Revenue ($M)" two columns.
+        let ruled = "0 0 0 RG 0.5 w \
+            72 500 m 400 500 l S 200 480 m 400 480 l S 72 460 m 400 460 l S \
+            72 440 m 400 440 l S 72 420 m 400 420 l S \
+            72 420 m 72 500 l S 200 420 m 200 500 l S 300 420 m 300 480 l S 400 420 m 400 500 l S \
+            BT /F1 10 Tf 80 476 Td (Segment) Tj ET BT /F1 10 Tf 210 486 Td (Revenue \\($M\\)) Tj ET \
+            BT /F1 10 Tf 210 466 Td (2024) Tj ET BT /F1 10 Tf 310 466 Td (2025) Tj ET \
+            BT /F1 10 Tf 80 446 Td (Enterprise) Tj ET BT /F1 10 Tf 210 446 Td (1.2) Tj ET \
+            BT /F1 10 Tf 310 446 Td (2.5) Tj ET BT /F1 10 Tf 80 426 Td (SMB) Tj ET \
+            BT /F1 10 Tf 210 426 Td (0.4) Tj ET BT /F1 10 Tf 310 426 Td (0.9) Tj ET";
+        let row = |y: u32, cells: [&str; 3]| {
+            format!(
+                "BT /F1 10 Tf 72 {y} Td ({}) Tj ET BT /F1 10 Tf 250 {y} Td ({}) Tj ET \
+                 BT /F1 10 Tf 400 {y} Td ({}) Tj ET ",
+                cells[0], cells[1], cells[2]
+            )
+        };
+        let aligned = [
+            "BT /F1 24 Tf 72 560 Td (Competition) Tj ET ".to_string(),
+            row(500, ["Competitor", "Price", "SSO"]),
+            row(484, ["Acme", "$49", "Yes"]),
+            row(468, ["Globex", "$99", "No"]),
+            row(452, ["Initech", "$29", "Yes"]),
+        ]
+        .concat();
+        let path = write_pdf("tables.pdf", &[ruled, &aligned]);
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        let ruled = &extracted.pages[0].tables;
+        assert_eq!(ruled.len(), 1);
+        assert_eq!(ruled[0].headers, ["Segment", "Revenue ($M) 2024", "Revenue ($M) 2025"]);
+        assert_eq!(ruled[0].rows, [["Enterprise", "1.2", "2.5"], ["SMB", "0.4", "0.9"]]);
+
+        let aligned = &extracted.pages[1].tables;
+        assert_eq!(aligned.len(), 1);
+        assert_eq!(aligned[0].headers, ["Competitor", "Price", "SSO"]);
+        assert_eq!(aligned[0].rows[1], ["Globex", "$99", "No"]);
+        assert_eq!(aligned[0].rows.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_typed_table_cells() {
+        let cell = |props: &str, text: &str| {
+            format!("<w:tc><w:tcPr>{props}</w:tcPr><w:p><w:r><w:t>{text}</w:t></w:r></w:p></w:tc>")
+        };
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Caption"/></w:pPr><w:r><w:t>Table 1: Key metrics</w:t></w:r></w:p>
+            <w:tbl>
+              <w:tr>{}{}</w:tr>
+              <w:tr>{}{}{}</w:tr>
+              <w:tr>{}{}{}</w:tr>
+              <w:tr>{}{}{}</w:tr>
+            </w:tbl>
+        </w:body></w:document>"#,
+            cell(r#"<w:vMerge w:val="restart"/>"#, "Metric"),
+            cell(r#"<w:gridSpan w:val="2"/>"#, "Period"),
+            cell("<w:vMerge/>", ""),
+            cell("", "Q3 2025"),
+            cell("", "FY2026"),
+            cell("", "ARR"),
+            cell("", "$2.4M"),
+            cell("", "(€1,250.50)"),
+            cell("", "Gross margin"),
+            cell("", "35%"),
+            cell("", "n/a"),
+        );
+        let path = write_docx("typed.docx", &body);
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let table = &extracted.pages[0].tables[0];
+        assert_eq!(table.caption.as_deref(), Some("Table 1: Key metrics"));
+        assert_eq!(table.page, 1);
+        assert_eq!(table.header_rows, 2);
+        assert_eq!(table.headers, ["Metric", "Period Q3 2025", "Period FY2026"]);
+        assert_eq!(table.rows, [["ARR", "$2.4M", "(€1,250.50)"], ["Gross margin", "35%", "n/a"]]);
+
+        let metric = &table.cells[0];
+        assert_eq!((metric.row_span, metric.col_span, metric.header), (2, 1, true));
+        assert_eq!(table.cells[1].col_span, 2);
+        let value = |row: usize, col: usize| {
+            table.cells.iter().find(|c| c.row == row && c.col == col).unwrap().value.clone()
+        };
+        assert_eq!(value(1, 1), CellValue::Date { value: "2025-Q3".into() });
+        assert_eq!(value(1, 2), CellValue::Date { value: "2026".into() });
+        assert_eq!(value(2, 0), CellValue::Text);
+        assert_eq!(value(2, 1), CellValue::Currency { value: 2_400_000.0, currency: "USD".into() });
+        assert_eq!(value(2, 2), CellValue::Currency { value: -1250.5, currency: "EUR".into() });
+        assert_eq!(value(3, 1), CellValue::Percentage { value: 35.0 });
+        assert_eq!(value(3, 2), CellValue::Empty);
+
+        // Months are whole words, not prefixes of other words.
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:tbl><w:tr>{}{}{}{}{}</w:tr></w:tbl></w:body></w:document>"#,
+            cell("", "Marketing 2024"),
+            cell("", "Decade 2020"),
+            cell("", "Junk 25"),
+            cell("", "Mar. 2024"),
+            cell("", "September 2024"),
+        );
+        let path = write_docx("months.docx", &body);
+        let extracted = DocumentParser::new(path.to_str().unwrap()).unwrap().extract().await.unwrap();
+        let values: Vec<CellValue> = extracted.pages[0].tables[0].cells.iter().map(|c| c.value.clone()).collect();
+        assert_eq!(
+            values,
+            [
+                CellValue::Text,
+                CellValue::Text,
+                CellValue::Text,
+                CellValue::Date { value: "2024-03".into() },
+                CellValue::Date { value: "2024-09".into() },
+            ]
+        );
+
+        // Tables found on PDF pages carry their page and position.
+        let ruled = "0 0 0 RG 0.5 w 72 500 m 300 500 l S 72 480 m 300 480 l S 72 460 m 300 460 l S \
+            72 460 m 72 500 l S 180 460 m 180 500 l S 300 460 m 300 500 l S \
+            BT /F1 10 Tf 72 510 Td (Headcount by team) Tj ET \
+            BT /F1 10 Tf 80 486 Td (Team) Tj ET BT /F1 10 Tf 190 486 Td (Staff) Tj ET \
+            BT /F1 10 Tf 80 466 Td (Sales) Tj ET BT /F1 10 Tf 190 466 Td (1,200) Tj ET";
+        let path = write_pdf("typed.pdf", &["BT /F1 12 Tf 72 700 Td (Cover) Tj ET", ruled]);
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let table = &extracted.pages[1].tables[0];
+        assert_eq!(table.page, 2);
+        assert_eq!(table.caption.as_deref(), Some("Headcount by team"));
+        assert_eq!(table.bbox.unwrap().width, 228.0);
+        assert_eq!(table.cells[3].value, CellValue::Number { value: 1200.0 });
+    }
+
+    #[tokio::test]
+    async fn test_text_block_classification() {
+        let line = |font: &str, size: u32, x: u32, y: u32, text: &str| {
+            format!("BT /{font} {size} Tf {x} {y} Td ({text}) Tj ET ")
+        };
+        let footer = |page: &str| {
+            line("F1", 9, 72, 20, "Acme Robotics confidential") + &line("F1", 9, 740, 20, page)
+        };
+        let traction = [
+            line("F2", 32, 72, 540, "Traction"),
+            line("F1", 14, 72, 480, "\\225 Revenue grew 3x year over year"),
+            line("F1", 14, 72, 460, "\\225 Enterprise pilots under way"),
+            line("F1", 14, 96, 440, "- Two Fortune 500 logos"),
+            line("F1", 14, 72, 380, "Our customers deploy robots in under a week,"),
+            line("F1", 14, 72, 364, "cutting integration costs by half."),
+            line("F1", 10, 72, 300, "Figure 1: Monthly revenue"),
+            footer("1"),
+        ]
+        .concat();
+        let team = line("F2", 32, 72, 540, "Team") + &footer("2");
+        let path = write_pdf("blocks.pdf", &[&traction, &team]);
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let blocks = extract_text(&extracted).unwrap();
+        let kinds: Vec<(&str, &str, Option<u32>)> = blocks
+            .iter()
+            .filter(|b| b.page == 1)
+            .map(|b| (b.block_type.as_str(), b.content.as_str(), b.level))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("title", "Traction", Some(1)),
+                ("bullet", "Revenue grew 3x year over year", Some(0)),
+                ("bullet", "Enterprise pilots under way", Some(0)),
+                ("bullet", "Two Fortune 500 logos", Some(1)),
+                ("paragraph", "Our customers deploy robots in under a week,\ncutting integration costs by half.", None),
+                ("caption", "Figure 1: Monthly revenue", None),
+                ("footer", "Acme Robotics confidential", None),
+                ("page_number", "1", None),
+            ]
+        );
+        assert!(blocks[0].confidence > 0.9);
+        assert!(blocks.iter().all(|b| b.bbox.is_some() && b.confidence > 0.5 && b.confidence <= 1.0));
+        // The footer recurs on every page, so it is a confident call.
+        let footers: Vec<&TextBlock> = blocks.iter().filter(|b| b.block_type == "footer").collect();
+        assert_eq!(footers.len(), 2);
+        assert!(footers[1].confidence >= 0.95);
+        assert_eq!(blocks.iter().find(|b| b.page == 2).unwrap().block_type, "title");
+
+        // Slide decks mark titles and list items in their placeholders.
+        let path = write_pptx("blocks.pptx");
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let blocks = extract_text(&extracted).unwrap();
+        assert_eq!((blocks[0].block_type.as_str(), blocks[0].content.as_str()), ("title", "Traction"));
+    }
+
+    #[tokio::test]
+    async fn test_pitch_section_detection() {
+        let slides = [
+            deck_slide("Acme Robotics", &["Seed deck, 2025"]),
+            deck_slide("The Problem", &["Integrating warehouse robots takes months."]),
+            deck_slide("Our Solution", &["A plug-and-play robotics layer."]),
+            deck_slide("Market Opportunity", &["TAM of $40B across 3 regions."]),
+            deck_slide("Go-to-Market", &["Direct sales to 3PL operators."]),
+            deck_slide("Traction", &["$2.4M ARR, 40 customers."]),
+            deck_slide("Traction \\(cont.\\)", &["Net revenue retention of 130%."]),
+            deck_slide("The People", &["Founders: Jane Doe, ex-Amazon Robotics", "Our founders and advisors", "previously scaled a team of 200"]),
+            deck_slide("Use of Funds", &["Raising $5M to expand sales."]),
+        ];
+        let pages: Vec<&str> = slides.iter().map(String::as_str).collect();
+        let path = write_pdf("sections.pdf", &pages);
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let structure = extract_structure(&extracted).unwrap();
+        let sections: Vec<(&str, Option<SectionKind>, &[u32])> = structure
+            .sections
+            .iter()
+            .map(|s| (s.title.as_str(), s.kind, s.pages.as_slice()))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("Acme Robotics", None, &[1][..]),
+                ("The Problem", Some(SectionKind::Problem), &[2]),
+                ("Our Solution", Some(SectionKind::Solution), &[3]),
+                ("Market Opportunity", Some(SectionKind::Market), &[4]),
+                ("Go-to-Market", Some(SectionKind::BusinessModel), &[5]),
+                ("Traction", Some(SectionKind::Traction), &[6, 7]),
+                ("The People", Some(SectionKind::Team), &[8]),
+                ("Use of Funds", Some(SectionKind::Ask), &[9]),
+            ]
+        );
+        let traction = &structure.sections[5];
+        assert_eq!(traction.content_blocks, 2);
+        assert!(traction.confidence >= 0.9);
+        // Mapped from body text alone, so held with less confidence.
+        let team = &structure.sections[6];
+        assert!(team.confidence > 0.3 && team.confidence < traction.confidence);
+        assert_eq!(SectionKind::Ask.label(), "Ask / Use of Funds");
+    }
+
+    #[tokio::test]
+    async fn test_language_detection() {
+        let page = |text: &str| format!("BT /F1 12 Tf 72 500 Td ({text}) Tj ET");
+        let pages = [
+            page("Nuestra plataforma reduce los costos de integracion para los operadores de la region y el mercado."),
+            page("Ingresos recurrentes de 1.200,50 por cliente en el primer trimestre del ano."),
+            page("Unsere Plattform ist die erste Loesung fuer den Mittelstand und wir wachsen mit der Industrie."),
+            page("Acme"),
+        ];
+        let pages: Vec<&str> = pages.iter().map(String::as_str).collect();
+        let path = write_pdf("spanish.pdf", &pages);
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let structure = extract_structure(&extracted).unwrap();
+        assert_eq!(structure.language.as_deref(), Some("es"));
+        let languages: Vec<(u32, &str)> = structure
+            .page_languages
+            .iter()
+            .map(|p| (p.page, p.language.as_str()))
+            .collect();
+        // The one-word page is too short to call.
+        assert_eq!(languages, [(1, "es"), (2, "es"), (3, "de")]);
+        assert!(structure.page_languages.iter().all(|p| p.confidence > 0.5));
+
+        for (text, language) in [
+            ("Nossa plataforma reduz o custo de integração para os operadores da região e não para.", "pt"),
+            ("Notre plateforme réduit les coûts pour les opérateurs de la région et des clients.", "fr"),
+            ("La nostra piattaforma riduce il costo per gli operatori della regione e non solo.", "it"),
+            ("Our platform cuts the cost of integration for operators in the region.", "en"),
+        ] {
+            assert_eq!(detect_language(text).unwrap().language, language, "{}", text);
+        }
+        assert_eq!(detect_language("Q3 2025"), None);
+
+        assert_eq!(NumberFormat::for_language("es"), NumberFormat { decimal: ',', grouping: '.' });
+        assert_eq!(NumberFormat::for_language("en"), NumberFormat::ENGLISH);
+    }
+
+    #[tokio::test]
+    async fn test_processing_pipeline() {
+        struct SlideCount;
+        impl Stage for SlideCount {
+            fn name(&self) -> &str {
+                "slide_count"
+            }
+            fn run(&self, ctx: &mut DocumentContext) -> Result<()> {
+                // Runs after the structure stage, so sections are in place.
+                let titled = ctx.processed.structure.sections.len();
+                ctx.processed.extensions.insert("slide_count".into(), serde_json::json!({ "titled": titled }));
+                Ok(())
+            }
+        }
+
+        let path = write_pptx("pipeline.pptx");
+        let path = path.to_str().unwrap();
+        let pipeline = Pipeline::default().with_stage_after("structure", SlideCount);
+        assert_eq!(
+            pipeline.stage_names(),
+            ["text_blocks", "structure", "slide_count", "metrics", "market", "charts", "visual_elements", "risk"]
+        );
+        let result = process_document_with(path, &pipeline).await.unwrap();
+        assert_eq!(result.processed.extensions["slide_count"]["titled"], 2);
+
+        // The default pipeline and the extractors agree on blocks and structure.
+        let result = process_document(path).await.unwrap();
+        let blocks = extract_text(&result.extracted).unwrap();
+        let types = |blocks: &[TextBlock]| blocks.iter().map(|b| b.block_type.clone()).collect::<Vec<_>>();
+        assert_eq!(types(&result.processed.text_blocks), types(&blocks));
+        assert_eq!(result.processed.text_blocks[0].block_type, "title");
+        assert_eq!(result.processed.structure.sections[0].kind, Some(SectionKind::Traction));
+        assert!(!result.processed.charts.is_empty());
+        assert!(!serde_json::to_value(&result.processed).unwrap().as_object().unwrap().contains_key("extensions"));
+
+        // A failing stage stops the run.
+        struct Fails;
+        impl Stage for Fails {
+            fn name(&self) -> &str {
+                "fails"
+            }
+            fn run(&self, _: &mut DocumentContext) -> Result<()> {
+                Err(TraceDeckError::ParseError("stage failed".into()))
+            }
+        }
+        let pipeline = Pipeline::new().with_stage(Fails).with_stage(extractors::TextBlockStage);
+        assert!(process_document_with(path, &pipeline).await.is_err());
+        assert_eq!(Pipeline::default().without_stage("charts").stage_names().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_metric_extraction() {
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let cell = |text: &str| format!("<w:tc><w:p><w:r><w:t>{text}</w:t></w:r></w:p></w:tc>");
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}
+            <w:tbl><w:tr>{}{}{}</w:tr><w:tr>{}{}{}</w:tr></w:tbl></w:body></w:document>"#,
+            [
+                "We reached $2.4M ARR as of Q3 2025, up from $1.1M a year earlier.",
+                "Gross margin of 72% and monthly churn below 1.5%.",
+                "Net revenue retention: 130%",
+                "CAC $1,200; LTV $9,600 (LTV/CAC 8x)",
+                "Monthly burn of $150K gives us 18 months of runway.",
+                "Team of 25 employees serving 12k MAU.",
+            ]
+            .map(paragraph)
+            .concat(),
+            cell("Metric"),
+            cell("2024"),
+            cell("2025"),
+            cell("Revenue ($M)"),
+            cell("1.2"),
+            cell("2.5"),
+        );
+        let path = write_docx("metrics.docx", &body);
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        let metrics = &result.processed.metrics;
+        let find = |kind: MetricKind| {
+            let found: Vec<&Metric> = metrics.iter().filter(|m| m.kind == kind).collect();
+            assert!(!found.is_empty(), "no {:?} in {:#?}", kind, metrics);
+            found
+        };
+
+        let arr = find(MetricKind::Arr);
+        assert_eq!(arr.len(), 1);
+        assert_eq!((arr[0].value, arr[0].unit.as_deref()), (2_400_000.0, Some("USD")));
+        assert_eq!(arr[0].period.as_deref(), Some("2025-Q3"));
+        assert_eq!((arr[0].page, arr[0].source.as_str()), (1, "text"));
+        assert!(arr[0].text.starts_with("We reached $2.4M ARR"));
+
+        assert_eq!(find(MetricKind::GrossMargin)[0].value, 72.0);
+        let churn = find(MetricKind::Churn)[0];
+        assert_eq!((churn.value, churn.cadence.as_deref()), (1.5, Some("monthly")));
+        assert_eq!(find(MetricKind::Nrr)[0].value, 130.0);
+        assert_eq!(find(MetricKind::Cac)[0].value, 1200.0);
+        assert_eq!(find(MetricKind::Ltv).iter().map(|m| m.value).collect::<Vec<_>>(), [9600.0]);
+        let burn = find(MetricKind::Burn)[0];
+        assert_eq!((burn.value, burn.cadence.as_deref()), (150_000.0, Some("monthly")));
+        let runway = find(MetricKind::Runway)[0];
+        assert_eq!((runway.value, runway.unit.as_deref()), (18.0, Some("months")));
+        assert_eq!(find(MetricKind::Headcount)[0].value, 25.0);
+        assert_eq!(find(MetricKind::Users)[0].value, 12_000.0);
+
+        let revenue: Vec<(f64, Option<&str>, &str)> = find(MetricKind::Revenue)
+            .iter()
+            .map(|m| (m.value, m.period.as_deref(), m.source.as_str()))
+            .collect();
+        assert_eq!(revenue, [(1_200_000.0, Some("2024"), "table"), (2_500_000.0, Some("2025"), "table")]);
+        assert!(metrics.iter().all(|m| m.confidence > 0.5));
+
+        // Table cells are read in the document's locale too.
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}
+            <w:tbl><w:tr>{}{}</w:tr><w:tr>{}{}</w:tr></w:tbl></w:body></w:document>"#,
+            paragraph("Unsere Plattform ist die erste Loesung fuer den Mittelstand und wir wachsen mit der Industrie."),
+            cell("Kennzahl"),
+            cell("2025"),
+            cell("ARR"),
+            cell("1.200 €"),
+        );
+        let path = write_docx("kennzahlen.docx", &body);
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(result.processed.structure.language.as_deref(), Some("de"));
+        let arr = result.processed.metrics.iter().find(|m| m.kind == MetricKind::Arr).unwrap();
+        assert_eq!((arr.value, arr.unit.as_deref(), arr.source.as_str()), (1200.0, Some("EUR"), "table"));
+    }
+
+    #[tokio::test]
+    async fn test_market_claim_extraction() {
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            [
+                "Total addressable market (TAM): $50B globally by 2030, growing at a 12% CAGR.\u{b9}",
+                "SAM: $8.5B in North America (Source: Gartner 2023)",
+                "Serviceable obtainable market (SOM)",
+                "$400M",
+                "The European pet care market is worth \u{20ac}120 billion.",
+                "We will spend $2M on our go-to-market and on the market launch.",
+                "Sam joined with $2M in sales from a previous role.",
+                "\u{b9} Grand View Research, Pet Care Market Report 2024",
+                "Sources: company analysis.",
+            ]
+            .map(paragraph)
+            .concat(),
+        );
+        let path = write_docx("market.docx", &body);
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        let claims = &result.processed.market_claims;
+        assert_eq!(claims.len(), 4, "{:#?}", claims);
+        let find = |kind: MarketSizeKind| claims.iter().find(|c| c.kind == kind).unwrap();
+
+        let tam = find(MarketSizeKind::Tam);
+        assert_eq!((tam.value, tam.currency.as_deref(), tam.year), (50e9, Some("USD"), Some(2030)));
+        assert_eq!((tam.geography.as_deref(), tam.cagr), (Some("Global"), Some(12.0)));
+        assert_eq!(tam.source.as_deref(), Some("Grand View Research, Pet Care Market Report 2024"));
+        assert!(tam.text.starts_with("Total addressable market (TAM)"));
+
+        let sam = find(MarketSizeKind::Sam);
+        assert_eq!((sam.value, sam.year, sam.cagr), (8.5e9, None, None));
+        assert_eq!(sam.geography.as_deref(), Some("North America"));
+        assert_eq!(sam.source.as_deref(), Some("Gartner 2023"));
+
+        // A label and its figure in separate paragraphs.
+        let som = find(MarketSizeKind::Som);
+        assert_eq!((som.value, som.currency.as_deref()), (400e6, Some("USD")));
+        assert_eq!(som.text, "Serviceable obtainable market (SOM) $400M");
+        assert_eq!(som.source.as_deref(), Some("company analysis"));
+
+        let market = find(MarketSizeKind::Market);
+        assert_eq!((market.value, market.currency.as_deref()), (120e9, Some("EUR")));
+        assert_eq!(market.geography.as_deref(), Some("Europe"));
+        assert!(market.confidence < tam.confidence);
+
+        // Letters that change length when lowercased precede the citation.
+        let body = paragraph("İstanbul, İzmir, İzmit ve İç Anadolu İçin İlk İhracat İstasyonları TAM: $3B (Source: TÜİK 2024)");
+        let path = write_docx(
+            "pazar.docx",
+            &format!(r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}</w:body></w:document>"#),
+        );
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(result.processed.market_claims[0].source.as_deref(), Some("TÜİK 2024"));
+    }
+
+    #[tokio::test]
+    async fn test_claim_verification() {
+        let paragraph = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            ["TAM: $50B globally by 2030.", "SAM: \u{20ac}9B in Europe by 2030.", "SOM: $400M by 2030."]
+                .map(paragraph)
+                .concat(),
+        );
+        let path = write_docx("claims.docx", &body);
+
+        let dir = std::env::temp_dir().join(format!("trace-deck-{}-datasets", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let markets = dir.join("markets.csv");
+        std::fs::write(
+            &markets,
+            "subject,attribute,value,unit,year,geography,source\n\
+             Pet care,tam,$40B,,2030,Global,\"Grand View Research, 2024\"\n\
+             Pet care,tam,$10B,,2030,United States,Other\n\
+             Pet care,sam,7000000000,USD,2030,Europe,Statista\n",
+        )
+        .unwrap();
+        let companies = dir.join("companies.json");
+        std::fs::write(
+            &companies,
+            r#"[{"subject": "Acme Pets", "attribute": "headquarters", "value": "Austin, TX", "source": "Registry"},
+                {"subject": "Acme Pets", "attribute": "founded", "value": 2019}]"#,
+        )
+        .unwrap();
+        let provider = FileProvider::load(&markets).unwrap();
+        assert_eq!(provider.records().len(), 3);
+        assert_eq!(provider.records()[0].value, ClaimValue::Number { value: 40e9, unit: Some("USD".to_string()) });
+        // Records about a named market say nothing about a claim naming none.
+        let unnamed = Claim::new(ClaimKind::MarketSize, "tam", ClaimValue::Number { value: 40e9, unit: Some("USD".to_string()) });
+        let verification = ClaimVerifier::new().with_provider(provider.clone()).verify(&unnamed);
+        assert_eq!((verification.verdict, verification.evidence.len()), (Verdict::Unverifiable, 0));
+
+        let verifier = ClaimVerifier::new()
+            .with_provider(provider)
+            .with_provider(FileProvider::load(&companies).unwrap())
+            .with_provider(HttpProvider::new("offline", "http://localhost/{attribute}").with_binary("/nonexistent/curl"))
+            .with_tolerance(0.3)
+            .with_fx_rates(FxRates::new("USD").with_rate("EUR", 1.1));
+        let headquarters = Claim::new(ClaimKind::CompanyFact, "headquarters", ClaimValue::Text { value: "Austin".to_string() })
+            .with_subject("Acme Pets");
+        let founded = Claim::new(ClaimKind::CompanyFact, "founded", ClaimValue::Number { value: 2017.0, unit: None })
+            .with_subject("Acme Pets");
+        let pipeline = Pipeline::default().with_stage(
+            VerificationStage::new(verifier)
+                .with_market("pet care")
+                .with_claim(headquarters)
+                .with_claim(founded),
+        );
+        let result = process_document_with(path.to_str().unwrap(), &pipeline).await.unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let verdicts: Vec<(&str, Verdict)> = result
+            .processed
+            .verifications
+            .iter()
+            .map(|v| (v.claim.attribute.as_str(), v.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("tam", Verdict::Supported),
+                ("sam", Verdict::Contradicted),
+                ("som", Verdict::Unverifiable),
+                ("headquarters", Verdict::Supported),
+                ("founded", Verdict::Contradicted),
+            ]
+        );
+        let tam = &result.processed.verifications[0];
+        assert_eq!(tam.claim.subject.as_deref(), Some("pet care"));
+        assert_eq!(tam.evidence.len(), 1);
+        assert_eq!(tam.evidence[0].source, "Grand View Research, 2024");
+        assert_eq!(tam.evidence[0].provider, "markets.csv");
+        assert!((tam.delta.unwrap() - 0.25).abs() < 1e-9);
+        // EUR 9B is USD 9.9B against a USD 7B reference.
+        let sam = &result.processed.verifications[1];
+        assert!((sam.delta.unwrap() - (9.9 / 7.0 - 1.0)).abs() < 1e-9);
+        assert!(result.processed.verifications[2].evidence.is_empty());
+        assert_eq!(result.processed.verifications[3].delta, None);
+        assert_eq!(result.processed.verifications[3].evidence[0].source, "Registry");
+
+        // HTTP providers drive a curl-compatible binary, passing headers as
+        // a config on stdin rather than as arguments.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let script = std::env::temp_dir().join(format!("trace-deck-{}-curl.sh", std::process::id()));
+            std::fs::write(
+                &script,
+                "#!/bin/sh\nconfig=$(cat)\ncase \"$*\" in *Bearer*) exit 1 ;; esac\n\
+                 case \"$config\" in *'header = \"Authorization: Bearer test\"'*) ;; *) exit 22 ;; esac\n\
+                 for url; do :; done\ncase \"$url\" in\n\
+                 *attribute=series_a*) echo '{\"records\": [{\"subject\": \"Acme Pets\", \"attribute\": \"series_a\", \"value\": \"$2.5M\", \"year\": 2021, \"source\": \"Mock API\"}]}' ;;\n\
+                 *) echo '[]' ;;\nesac\n",
+            )
+            .unwrap();
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+            let api = HttpProvider::new("funding-api", "https://api.example.com/rounds?attribute={attribute}&company={subject}&year={year}")
+                .with_binary(script.to_str().unwrap())
+                .with_header("Authorization", "Bearer test");
+            let round = Claim::new(ClaimKind::Funding, "series_a", ClaimValue::Number { value: 3.5e6, unit: Some("USD".to_string()) })
+                .with_subject("Acme Pets")
+                .with_year(2021);
+            assert_eq!(
+                api.url_for(&round),
+                "https://api.example.com/rounds?attribute=series_a&company=Acme%20Pets&year=2021"
+            );
+            let verifier = ClaimVerifier::new().with_provider(api);
+            let verification = verifier.verify(&round);
+            std::fs::remove_file(&script).ok();
+            assert_eq!(verification.verdict, Verdict::Contradicted);
+            assert!((verification.delta.unwrap() - 0.4).abs() < 1e-9);
+            assert_eq!(verification.evidence[0].provider, "funding-api");
+            assert_eq!(verifier.verify(&round.clone().with_year(2022)).verdict, Verdict::Unverifiable);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_risk_flags() {
+        let slides = [
+            deck_slide("Acme Robotics", &["Seed deck, 2025"]),
+            deck_slide("The Problem", &["Integrating warehouse robots takes months."]),
+            deck_slide("Market Opportunity", &["TAM of $40B and SAM of $60B."]),
+            deck_slide(
+                "Traction",
+                &[
+                    "$2.4M ARR as of Q3 2025.",
+                    "Our largest customer accounts for 55% of revenue.",
+                    "Monthly burn of $400K leaves 4 months of runway.",
+                ],
+            ),
+            deck_slide("Traction \\(cont.\\)", &["ARR of $3.1M as of Q3 2025."]),
+            deck_slide("Revenue Projections", &["2024 revenue $1.0M", "2025 revenue $1.2M", "2026 revenue $1.5M", "2027 revenue $12M"]),
+            deck_slide("Risks", &["We are defending a patent infringement lawsuit filed in 2024.", "We have no pending litigation."]),
+        ];
+        let pages: Vec<&str> = slides.iter().map(String::as_str).collect();
+        let path = write_pdf("risks.pdf", &pages);
+
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        let flags = &result.risk_flags;
+        let rules: Vec<(&str, Severity)> = flags.iter().map(|f| (f.rule.as_str(), f.severity)).collect();
+        assert_eq!(
+            rules,
+            [
+                ("short_runway", Severity::High),
+                ("customer_concentration", Severity::High),
+                ("market_size_order", Severity::High),
+                ("missing_team", Severity::Medium),
+                ("hockey_stick", Severity::Medium),
+                ("inconsistent_metrics", Severity::Medium),
+                ("legal_exposure", Severity::Medium),
+                ("unverifiable_claims", Severity::Low),
+            ],
+            "{:#?}",
+            flags
+        );
+        assert_eq!(find_flag(flags, "short_runway").pages, [4]);
+        assert_eq!(find_flag(flags, "short_runway").category, RiskCategory::Financial);
+        assert!(find_flag(flags, "customer_concentration").explanation.starts_with("55% of revenue"));
+        assert_eq!(find_flag(flags, "market_size_order").explanation, "SAM of $60B exceeds TAM of $40B; each market should contain the next");
+        assert_eq!(find_flag(flags, "hockey_stick").pages, [6]);
+        assert_eq!(find_flag(flags, "inconsistent_metrics").pages, [4, 5]);
+        assert_eq!(find_flag(flags, "inconsistent_metrics").category, RiskCategory::Consistency);
+        // The denied "no pending litigation" is not flagged.
+        assert_eq!(flags.iter().filter(|f| f.rule == "legal_exposure").count(), 1);
+        assert_eq!(find_flag(flags, "legal_exposure").category, RiskCategory::Legal);
+        assert!(find_flag(flags, "unverifiable_claims").explanation.starts_with("2 claims cite no source"));
+
+        // The same growth reported for past years is a track record.
+        let history = deck_slide("Revenue History", &["2021 revenue $1.0M", "2022 revenue $1.2M", "2023 revenue $1.5M", "2024 revenue $12M"]);
+        let history_path = write_pdf("history.pdf", &[&history]);
+        let history = process_document(history_path.to_str().unwrap()).await.unwrap();
+        assert!(history.risk_flags.iter().all(|f| f.rule != "hockey_stick"), "{:#?}", history.risk_flags);
+
+        // Once verification runs, unconfirmed claims are reported as such.
+        let pipeline = Pipeline::default().with_stage_after("market", VerificationStage::new(ClaimVerifier::new()));
+        let verified = process_document_with(path.to_str().unwrap(), &pipeline).await.unwrap();
+        assert!(find_flag(&verified.risk_flags, "unverifiable_claims")
+            .explanation
+            .starts_with("2 claims could not be checked"));
+
+        // A custom engine replaces the default risk stage.
+        let engine = RiskEngine::new().with_rule(ShortRunwayRule);
+        let pipeline = Pipeline::default().without_stage("risk").with_stage(RiskStage::new(engine));
+        let custom = process_document_with(path.to_str().unwrap(), &pipeline).await.unwrap();
+        assert_eq!(custom.risk_flags.iter().map(|f| f.rule.as_str()).collect::<Vec<_>>(), ["short_runway"]);
+
+        // A deck with no revenue or financial section.
+        let mut ctx = Pipeline::default().run(DocumentParser::new(path.to_str().unwrap()).unwrap().extract().await.unwrap()).unwrap();
+        ctx.processed.metrics.retain(|m| !matches!(m.kind, MetricKind::Revenue | MetricKind::Arr | MetricKind::Burn));
+        ctx.processed.structure.sections.retain(|s| s.kind != Some(SectionKind::Financials));
+        let engine = RiskEngine::default();
+        assert!(find_flag(&engine.evaluate(&ctx), "missing_financials").explanation.starts_with("No financials"));
+
+        let engine = engine.without_rule("missing_financials").without_rule("legal_exposure");
+        assert_eq!(engine.rule_names().len(), 8);
+        assert!(engine.evaluate(&ctx).iter().all(|f| f.rule != "missing_financials" && f.rule != "legal_exposure"));
+
+        // Two statements of zero agree, and concentration is read from whole
+        // words: "carrier" names no ARR and "topology" no top customer.
+        let engine = RiskEngine::new().with_rule(InconsistentMetricsRule).with_rule(CustomerConcentrationRule);
+        let arr = Metric {
+            kind: MetricKind::Arr,
+            value: 0.0,
+            unit: Some("USD".to_string()),
+            period: Some("2025-Q3".to_string()),
+            cadence: None,
+            text: String::new(),
+            page: 4,
+            source: "text".to_string(),
+            confidence: 0.9,
+        };
+        ctx.processed.metrics = vec![arr.clone(), Metric { page: 5, ..arr }];
+        let mut block = ctx.processed.text_blocks[0].clone();
+        block.content = "Our carrier network topology reaches 60% of accounts.".to_string();
+        ctx.processed.text_blocks = vec![block.clone()];
+        assert!(engine.evaluate(&ctx).is_empty());
+
+        // Bullets on one line are separate statements.
+        block.content = "\u{2022} Our largest customer accounts for 55% of revenue \u{2022} Seed round closed".to_string();
+        ctx.processed.text_blocks = vec![block];
+        let flags = engine.evaluate(&ctx);
+        assert_eq!(
+            find_flag(&flags, "customer_concentration").explanation,
+            "55% of revenue depends on the largest customers: \"Our largest customer accounts for 55% of revenue\""
+        );
+    }
+
+    #[test]
+    fn test_quantity_normalization() {
+        let parse = |text: &str| parse_quantity(text, None).unwrap_or_else(|| panic!("no quantity in {:?}", text));
+        let currency = |code: &str| Some(Unit::Currency(code.to_string()));
+
+        let q = parse("$1.2M");
+        assert_eq!((q.value, q.unit.clone(), q.scale, q.approximate), (1_200_000.0, currency("USD"), 1e6, false));
+        let q = parse("1,2 Mio. €");
+        assert_eq!((q.value, q.unit.clone(), q.scale), (1_200_000.0, currency("EUR"), 1e6));
+        let q = parse("₹50 Cr");
+        assert_eq!((q.value, q.unit.clone(), q.scale), (500_000_000.0, currency("INR"), 1e7));
+        let q = parse("12k MAU");
+        assert_eq!((q.value, q.unit.clone()), (12_000.0, Some(Unit::Other("MAU".to_string()))));
+        let q = parse("3x YoY");
+        assert_eq!((q.value, q.unit.clone(), q.period), (3.0, Some(Unit::Multiple), Some(Period::YearOverYear)));
+        let q = parse("~40%");
+        assert_eq!((q.value, q.unit.clone(), q.approximate), (40.0, Some(Unit::Percent), true));
+        let q = parse("$150K/mo");
+        assert_eq!((q.value, q.period), (150_000.0, Some(Period::Monthly)));
+        assert!(parse("10k+ users").approximate);
+        assert_eq!(parse("(1,200)").value, -1200.0);
+        assert_eq!(parse_quantity("Series A", None), None);
+
+        // Separators follow the locale when it is known.
+        assert_eq!(parse("1.200 €").value, 1.2);
+        let german = NumberFormat::for_language("de");
+        assert_eq!(parse_quantity("1.200 €", Some(german)).unwrap().value, 1200.0);
+        assert_eq!(parse_quantity("1.250.000,50", Some(german)).unwrap().value, 1_250_000.5);
+        assert_eq!(parse_quantity("$1.5M", Some(german)).unwrap().value, 1_500_000.0);
+        let french = NumberFormat::for_language("fr");
+        assert_eq!(parse_quantity("2 500,75 €", Some(french)).unwrap().value, 2500.75);
+
+        // Currency conversion uses a local table of rates.
+        let path = std::env::temp_dir().join(format!("trace-deck-{}-fx.json", std::process::id()));
+        std::fs::write(&path, r#"{"base": "USD", "rates": {"EUR": 1.1, "inr": 0.012}}"#).unwrap();
+        let rates = FxRates::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let usd = parse("1,2 Mio. €").in_currency(&rates, "USD").unwrap();
+        assert!((usd.value - 1_320_000.0).abs() < 1e-6);
+        assert_eq!(usd.currency(), Some("USD"));
+        let eur = parse("₹50 Cr").in_currency(&rates, "EUR").unwrap();
+        assert!((eur.value - 500_000_000.0 * 0.012 / 1.1).abs() < 1e-3);
+        assert_eq!(parse("$1.2M").in_currency(&rates, "GBP"), None);
+        assert_eq!(parse("~40%").in_currency(&rates, "USD"), None);
+    }
+
+    #[tokio::test]
+    async fn test_image_storage() {
+        let path = write_pptx("storage.pptx");
+        let result = process_document(path.to_str().unwrap()).await.unwrap();
+        let image = result.extracted.images().next().unwrap().clone();
+        assert!(!image.data.is_empty());
+        assert_eq!(image.sha256, content_hash(&image.data));
+
+        // Inline bytes serialize as base64 and read back unchanged.
+        let json = serde_json::to_value(&image).unwrap();
+        assert!(json["data"].is_string());
+        let round_trip: ImageData = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip.data, image.data);
+
+        let dir = std::env::temp_dir().join(format!("trace-deck-{}-images", std::process::id()));
+        let mut on_disk = result.extracted.clone();
+        store_images(&mut on_disk, &ImageStorage::Directory(dir.clone())).unwrap();
+        let stored = on_disk.images().next().unwrap();
+        assert!(stored.data.is_empty());
+        assert_eq!(stored.path.as_deref(), Some(dir.join(format!("{}.png", image.sha256)).to_str().unwrap()));
+        assert_eq!(stored.bytes().unwrap().as_ref(), image.data.as_slice());
+        assert!(!serde_json::to_value(stored).unwrap().as_object().unwrap().contains_key("data"));
+
+        let mut hashed = result.extracted.clone();
+        store_images(&mut hashed, &ImageStorage::HashOnly).unwrap();
+        let reference = hashed.images().next().unwrap();
+        assert_eq!((reference.data.len(), reference.path.as_ref()), (0, None));
+        assert!(reference.bytes().is_err());
+
+        // Formats are reduced to a plain extension before naming files.
+        let mut hostile = result.extracted.clone();
+        hostile.pages.iter_mut().flat_map(|p| p.images.iter_mut()).for_each(|i| i.format = "../PNG".to_string());
+        store_images(&mut hostile, &ImageStorage::Directory(dir.clone())).unwrap();
+        let stored = hostile.images().next().unwrap();
+        assert_eq!(stored.path.as_deref(), Some(dir.join(format!("{}.png", image.sha256)).to_str().unwrap()));
+
+        // Storage can also run as the last pipeline stage.
+        let pipeline = Pipeline::default().with_stage(ImageStorageStage::new(ImageStorage::HashOnly));
+        let result = process_document_with(path.to_str().unwrap(), &pipeline).await.unwrap();
+        assert!(result.extracted.images().all(|i| i.data.is_empty() && !i.sha256.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_docx_parsing() {
+        let body = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Investment Memo</w:t></w:r></w:p>
+            <w:p><w:pPr><w:pStyle w:val="ListParagraph"/><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Revenue grew 3x</w:t></w:r></w:p>
+            <w:tbl>
+              <w:tr><w:tc><w:p><w:r><w:t>Metric</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Value</w:t></w:r></w:p></w:tc></w:tr>
+              <w:tr><w:tc><w:p><w:r><w:t>ARR</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>$2.4M</w:t></w:r></w:p></w:tc></w:tr>
+            </w:tbl>
+            <w:p><w:r><w:drawing><wp:inline xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData>
+              <c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId7"/>
+            </a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>
+        </w:body></w:document>"#;
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="charts/chart1.xml"/></Relationships>"#;
+        let core = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/">
+            <dc:title>Acme One-Pager</dc:title><dc:creator>Jane Founder</dc:creator>
+            <dcterms:created>2025-09-01T10:00:00Z</dcterms:created></cp:coreProperties>"#;
+        let path = write_zip(
+            "memo.docx",
+            &[
+                ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+                ("_rels/.rels", ROOT_RELS.as_bytes()),
+                ("word/document.xml", body.as_bytes()),
+                ("word/_rels/document.xml.rels", rels.as_bytes()),
+                ("word/charts/chart1.xml", REVENUE_CHART.as_bytes()),
+                ("docProps/core.xml", core.as_bytes()),
+            ],
+        );
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.metadata.title.as_deref(), Some("Acme One-Pager"));
+        assert_eq!(extracted.metadata.author.as_deref(), Some("Jane Founder"));
+        assert_eq!(extracted.metadata.created_at.as_deref(), Some("2025-09-01T10:00:00Z"));
+
+        let page = &extracted.pages[0];
+        assert_eq!(page.paragraphs[0].style.as_deref(), Some("Heading1"));
+        assert_eq!(page.paragraphs[1].style.as_deref(), Some("ListParagraph"));
+        assert_eq!(page.paragraphs[1].list_level, Some(1));
+        assert_eq!(page.tables[0].headers, ["Metric", "Value"]);
+        assert_eq!(page.tables[0].rows, [["ARR", "$2.4M"]]);
+        assert!(extracted.text.starts_with("Investment Memo\nRevenue grew 3x"));
+        assert_eq!(page.charts[0].series[0].values[2], Some(340.5));
+    }
+
+    #[tokio::test]
+    async fn test_pptx_parsing() {
+        let path = write_pptx("deck.pptx");
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.metadata.file_type, "pptx");
+        assert_eq!(extracted.metadata.pages, Some(2));
+        assert_eq!(extracted.metadata.title.as_deref(), Some("Acme Seed Deck"));
+
+        let slide = &extracted.pages[0];
+        assert_eq!(slide.width, Some(720.0));
+        assert_eq!(slide.text, "Traction\nRevenue by quarter");
+        // The title placeholder has no transform and inherits the layout's.
+        assert_eq!(slide.spans[0].bbox.y, 20.0);
+        assert_eq!(slide.spans[0].font_size, 40.0);
+        // Grouped shape: group at (100, 200), child space scaled 2x.
+        assert_eq!(slide.spans[1].bbox.x, 100.0);
+        assert_eq!(slide.spans[1].bbox.width, 200.0);
+
+        assert_eq!(slide.tables[0].headers, ["Plan", "Price"]);
+        assert_eq!(slide.tables[0].rows, [["Pro", "$49"]]);
+        assert_eq!(slide.tables.len(), 1);
+
+        let chart = &slide.charts[0];
+        assert_eq!(chart.chart_type, "column");
+        assert_eq!(chart.title.as_deref(), Some("Quarterly revenue"));
+        assert_eq!(chart.categories, ["Q1", "Q2", "Q3"]);
+        assert_eq!(chart.series[0].name, "Revenue");
+        assert_eq!(chart.series[0].values, [Some(120.0), None, Some(340.5)]);
+        assert_eq!(chart.bbox.unwrap().width, 400.0);
+        let axis = chart.value_axis.as_ref().unwrap();
+        assert_eq!(axis.unit.as_deref(), Some("$"));
+        assert_eq!(axis.scale.as_deref(), Some("thousands"));
+
+        assert_eq!(slide.images.len(), 1);
+        assert_eq!(slide.images[0].width, 4);
+        assert_eq!(slide.images[0].bbox.unwrap().x, 360.0);
+        assert_eq!(slide.notes.as_deref(), Some("Mention the Q2 spike"));
+
+        assert_eq!(extracted.pages[1].text, "Team");
+    }
+
+    #[tokio::test]
+    async fn test_embedded_charts() {
+        const C: &str = r#"xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main""#;
+        let cache = |kind: &str, format: Option<&str>, points: &[(usize, &str)]| {
+            let pts: String = points.iter().map(|(i, v)| format!(r#"<c:pt idx="{i}"><c:v>{v}</c:v></c:pt>"#)).collect();
+            let format = format.map(|f| format!("<c:formatCode>{f}</c:formatCode>")).unwrap_or_default();
+            format!(r#"<c:{kind}Ref><c:{kind}Cache>{format}<c:ptCount val="3"/>{pts}</c:{kind}Cache></c:{kind}Ref>"#)
+        };
+        let name = |n: &str| format!(r#"<c:tx><c:strRef><c:strCache><c:ptCount val="1"/><c:pt idx="0"><c:v>{n}</c:v></c:pt></c:strCache></c:strRef></c:tx>"#);
+        let title = |t: &str| format!(r#"<c:title><c:tx><c:rich><a:p><a:r><a:t>{t}</a:t></a:r></a:p></c:rich></c:tx></c:title>"#);
+        let years = cache("str", None, &[(0, "2023"), (1, "2024"), (2, "2025")]);
+
+        // Two series over years, the second with a gap, on a currency axis.
+        let line = format!(
+            r#"<c:chartSpace {C}><c:chart>{}<c:plotArea><c:lineChart>
+                 <c:ser>{}<c:cat>{years}</c:cat><c:val>{}</c:val></c:ser>
+                 <c:ser>{}<c:cat>{years}</c:cat><c:val>{}</c:val></c:ser></c:lineChart>
+               <c:catAx><c:axPos val="b"/></c:catAx>
+               <c:valAx><c:axPos val="l"/>{}<c:numFmt formatCode="[$€-407]#,##0.0" sourceLinked="0"/><c:dispUnits><c:builtInUnit val="millions"/></c:dispUnits></c:valAx>
+             </c:plotArea></c:chart></c:chartSpace>"#,
+            title("Revenue and costs"),
+            name("Revenue"),
+            cache("num", Some("General"), &[(0, "1.2"), (1, "2.5"), (2, "4.8")]),
+            name("Costs"),
+            cache("num", Some("General"), &[(0, "2"), (2, "3.1")]),
+            title("EUR (millions)"),
+        );
+        // Scatter: x values as categories, the vertical axis takes its
+        // percent format from the source cells.
+        let scatter = format!(
+            r#"<c:chartSpace {C}><c:chart><c:plotArea><c:scatterChart>
+                 <c:ser>{}<c:xVal>{}</c:xVal><c:yVal>{}</c:yVal></c:ser></c:scatterChart>
+               <c:valAx><c:axPos val="b"/>{}</c:valAx>
+               <c:valAx><c:axPos val="l"/>{}<c:numFmt formatCode="General" sourceLinked="1"/></c:valAx>
+             </c:plotArea></c:chart></c:chartSpace>"#,
+            name("Cohorts"),
+            cache("num", None, &[(0, "10"), (1, "20"), (2, "40")]),
+            cache("num", Some("0.0%"), &[(0, "0.05"), (1, "0.04"), (2, "0.02")]),
+            title("Customers"),
+            title("Churn"),
+        );
+        let chart = |id: &str| {
+            format!(
+                r#"<w:p><w:r><w:drawing><wp:inline xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData>
+                   <c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="{id}"/>
+                   </a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#
+            )
+        };
+        let body = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}{}</w:body></w:document>"#,
+            chart("rId2"),
+            chart("rId1"),
+        );
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="charts/chart1.xml"/>
+            <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="charts/chart2.xml"/>
+        </Relationships>"#;
+        let path = write_zip(
+            "charts.docx",
+            &[
+                ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+                ("_rels/.rels", ROOT_RELS.as_bytes()),
+                ("word/document.xml", body.as_bytes()),
+                ("word/_rels/document.xml.rels", rels.as_bytes()),
+                ("word/charts/chart1.xml", scatter.as_bytes()),
+                ("word/charts/chart2.xml", line.as_bytes()),
+            ],
+        );
+
+        let extracted = DocumentParser::new(path.to_str().unwrap())
+            .unwrap()
+            .extract()
+            .await
+            .unwrap();
+        let charts = &extracted.pages[0].charts;
+        assert_eq!(charts.len(), 2);
+
+        // In document order, not part order.
+        let line = &charts[0];
+        assert_eq!(line.chart_type, "line");
+        assert_eq!(line.title.as_deref(), Some("Revenue and costs"));
+        assert_eq!(line.categories, ["2023", "2024", "2025"]);
+        let series: Vec<(&str, &[Option<f64>])> = line.series.iter().map(|s| (s.name.as_str(), s.values.as_slice())).collect();
+        assert_eq!(
+            series,
+            [("Revenue", &[Some(1.2), Some(2.5), Some(4.8)][..]), ("Costs", &[Some(2.0), None, Some(3.1)])]
+        );
+        assert!(line.series.iter().all(|s| s.confidence == [1.0; 3]));
+        let axis = line.value_axis.as_ref().unwrap();
+        assert_eq!(axis.title.as_deref(), Some("EUR (millions)"));
+        assert_eq!(axis.number_format.as_deref(), Some("[$€-407]#,##0.0"));
+        assert_eq!((axis.unit.as_deref(), axis.scale.as_deref()), (Some("€"), Some("millions")));
+
+        let scatter = &charts[1];
+        assert_eq!((scatter.chart_type.as_str(), scatter.title.as_deref()), ("scatter", None));
+        assert_eq!(scatter.categories, ["10", "20", "40"]);
+        assert_eq!(scatter.series[0].name, "Cohorts");
+        assert_eq!(scatter.series[0].values, [Some(0.05), Some(0.04), Some(0.02)]);
+        let axis = scatter.value_axis.as_ref().unwrap();
+        assert_eq!(axis.title.as_deref(), Some("Churn"));
+        assert_eq!((axis.number_format.as_deref(), axis.unit.as_deref(), axis.scale.as_deref()), (Some("0.0%"), Some("%"), None));
     }
 
     #[test]
@@ -17,9 +1500,268 @@ mod tests {
             title: Some("Test".to_string()),
             author: None,
             created_at: None,
+            modified_at: None,
+            creator_tool: None,
+            producer: None,
+            keywords: vec![],
         };
 
         assert_eq!(metadata.file_type, "pdf");
         assert_eq!(metadata.file_size, 1024);
     }
+
+    const CONTENT_TYPES: &str = r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+        <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+        <Default Extension="xml" ContentType="application/xml"/>
+        <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+    </Types>"#;
+
+    const ROOT_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+        <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+    </Relationships>"#;
+
+    /// Write a zip archive (an OOXML package) from `(path, bytes)` entries.
+    fn write_zip(name: &str, entries: &[(&str, &[u8])]) -> std::path::PathBuf {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("trace-deck-{}-{}", std::process::id(), name));
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        for (entry, data) in entries {
+            zip.start_file(*entry, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    /// Write a Word document whose `word/document.xml` is `body`, with no
+    /// other parts.
+    fn write_docx(name: &str, body: &str) -> std::path::PathBuf {
+        let rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"></Relationships>"#;
+        write_zip(
+            name,
+            &[
+                ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+                ("_rels/.rels", ROOT_RELS.as_bytes()),
+                ("word/document.xml", body.as_bytes()),
+                ("word/_rels/document.xml.rels", rels.as_bytes()),
+            ],
+        )
+    }
+
+    /// Column chart with a gap in its cached values and a dollar axis in
+    /// thousands.
+    const REVENUE_CHART: &str = r#"<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><c:chart>
+        <c:title><c:tx><c:rich><a:p><a:r><a:t>Quarterly revenue</a:t></a:r></a:p></c:rich></c:tx></c:title>
+        <c:plotArea><c:barChart><c:barDir val="col"/>
+          <c:ser><c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:ptCount val="1"/><c:pt idx="0"><c:v>Revenue</c:v></c:pt></c:strCache></c:strRef></c:tx>
+            <c:cat><c:strRef><c:strCache><c:ptCount val="3"/><c:pt idx="0"><c:v>Q1</c:v></c:pt><c:pt idx="1"><c:v>Q2</c:v></c:pt><c:pt idx="2"><c:v>Q3</c:v></c:pt></c:strCache></c:strRef></c:cat>
+            <c:val><c:numRef><c:numCache><c:formatCode>General</c:formatCode><c:ptCount val="3"/><c:pt idx="0"><c:v>120</c:v></c:pt><c:pt idx="2"><c:v>340.5</c:v></c:pt></c:numCache></c:numRef></c:val>
+          </c:ser></c:barChart>
+          <c:catAx><c:axId val="1"/><c:axPos val="b"/></c:catAx>
+          <c:valAx><c:axId val="2"/><c:axPos val="l"/><c:numFmt formatCode="&quot;$&quot;#,##0" sourceLinked="0"/><c:dispUnits><c:builtInUnit val="thousands"/></c:dispUnits></c:valAx>
+        </c:plotArea></c:chart></c:chartSpace>"#;
+
+    /// Write a two-slide deck exercising placeholders, groups, tables,
+    /// charts, pictures and speaker notes.
+    fn write_pptx(name: &str) -> std::path::PathBuf {
+        const P: &str = r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+        let rels = |entries: &[(&str, &str, &str)]| {
+            let body: String = entries
+                .iter()
+                .map(|(id, kind, target)| {
+                    format!(r#"<Relationship Id="{}" Type="{}/{}" Target="{}"/>"#, id, REL, kind, target)
+                })
+                .collect();
+            format!(
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#,
+                body
+            )
+        };
+        let slide = |tree: &str| {
+            format!(r#"<p:sld {}><p:cSld><p:spTree>{}</p:spTree></p:cSld></p:sld>"#, P, tree)
+        };
+        let text = |t: &str, sz: u32| {
+            format!(r#"<p:txBody><a:p><a:r><a:rPr sz="{}"/><a:t>{}</a:t></a:r></a:p></p:txBody>"#, sz, t)
+        };
+
+        let content_types = CONTENT_TYPES
+            .replace("/word/document.xml", "/ppt/presentation.xml")
+            .replace("wordprocessingml.document", "presentationml.presentation");
+        let root_rels = rels(&[("rId1", "officeDocument", "ppt/presentation.xml")]);
+        let presentation = format!(
+            r#"<p:presentation {}><p:sldIdLst><p:sldId id="256" r:id="rId2"/><p:sldId id="257" r:id="rId3"/></p:sldIdLst><p:sldSz cx="9144000" cy="6858000"/></p:presentation>"#,
+            P
+        );
+        let presentation_rels = rels(&[
+            ("rId3", "slide", "slides/slide2.xml"),
+            ("rId2", "slide", "slides/slide1.xml"),
+        ]);
+        let layout = format!(
+            r#"<p:sldLayout {}><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:spPr><a:xfrm><a:off x="254000" y="254000"/><a:ext cx="8636000" cy="1270000"/></a:xfrm></p:spPr></p:sp></p:spTree></p:cSld></p:sldLayout>"#,
+            P
+        );
+        let slide1 = slide(&format!(
+            r#"<p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:spPr/>{}</p:sp>
+            <p:grpSp><p:grpSpPr><a:xfrm><a:off x="1270000" y="2540000"/><a:ext cx="2540000" cy="1270000"/><a:chOff x="0" y="0"/><a:chExt cx="1270000" cy="635000"/></a:xfrm></p:grpSpPr>
+              <p:sp><p:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="1270000" cy="635000"/></a:xfrm></p:spPr>{}</p:sp></p:grpSp>
+            <p:graphicFrame><p:xfrm><a:off x="0" y="0"/><a:ext cx="1" cy="1"/></p:xfrm><a:graphic><a:graphicData><a:tbl>
+              <a:tr><a:tc><a:txBody><a:p><a:r><a:t>Plan</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>Price</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+              <a:tr><a:tc><a:txBody><a:p><a:r><a:t>Pro</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>$49</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+            </a:tbl></a:graphicData></a:graphic></p:graphicFrame>
+            <p:graphicFrame><p:xfrm><a:off x="0" y="3810000"/><a:ext cx="5080000" cy="2540000"/></p:xfrm><a:graphic><a:graphicData><c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" r:id="rId4"/></a:graphicData></a:graphic></p:graphicFrame>
+            <p:pic><p:blipFill><a:blip r:embed="rId5"/></p:blipFill><p:spPr><a:xfrm><a:off x="4572000" y="0"/><a:ext cx="635000" cy="635000"/></a:xfrm></p:spPr></p:pic>"#,
+            text("Traction", 4000),
+            text("Revenue by quarter", 1400),
+        ));
+        let slide1_rels = rels(&[
+            ("rId1", "slideLayout", "../slideLayouts/slideLayout1.xml"),
+            ("rId2", "notesSlide", "../notesSlides/notesSlide1.xml"),
+            ("rId4", "chart", "../charts/chart1.xml"),
+            ("rId5", "image", "../media/image1.png"),
+        ]);
+        let slide2 = slide(&format!(
+            r#"<p:sp><p:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="100" cy="100"/></a:xfrm></p:spPr>{}</p:sp>"#,
+            text("Team", 3200)
+        ));
+        let notes = format!(
+            r#"<p:notes {}><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>Mention the Q2 spike</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:notes>"#,
+            P
+        );
+        let core = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Acme Seed Deck</dc:title></cp:coreProperties>"#;
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(4, 3)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+
+        write_zip(
+            name,
+            &[
+                ("[Content_Types].xml", content_types.as_bytes()),
+                ("_rels/.rels", root_rels.as_bytes()),
+                ("docProps/core.xml", core.as_bytes()),
+                ("ppt/presentation.xml", presentation.as_bytes()),
+                ("ppt/_rels/presentation.xml.rels", presentation_rels.as_bytes()),
+                ("ppt/slideLayouts/slideLayout1.xml", layout.as_bytes()),
+                ("ppt/slides/slide1.xml", slide1.as_bytes()),
+                ("ppt/slides/_rels/slide1.xml.rels", slide1_rels.as_bytes()),
+                ("ppt/slides/slide2.xml", slide2.as_bytes()),
+                ("ppt/notesSlides/notesSlide1.xml", notes.as_bytes()),
+                ("ppt/charts/chart1.xml", REVENUE_CHART.as_bytes()),
+                ("ppt/media/image1.png", png.get_ref()),
+            ],
+        )
+    }
+
+    /// The flag raised by `rule`, which must be among `flags`.
+    fn find_flag<'a>(flags: &'a [RiskFlag], rule: &str) -> &'a RiskFlag {
+        flags.iter().find(|f| f.rule == rule).unwrap_or_else(|| panic!("no {} flag in {:#?}", rule, flags))
+    }
+
+    /// Content stream of a deck slide: a large title, then body lines.
+    fn deck_slide(title: &str, body: &[&str]) -> String {
+        let mut content = format!("BT /F2 32 Tf 72 540 Td ({title}) Tj ET ");
+        for (i, line) in body.iter().enumerate() {
+            content += &format!("BT /F1 14 Tf 72 {} Td ({line}) Tj ET ", 460 - 20 * i as u32);
+        }
+        content
+    }
+
+    /// Write a minimal PDF with one page per content stream, using a
+    /// WinAnsi-encoded Helvetica as `/F1`.
+    fn write_pdf(name: &str, pages: &[&str]) -> std::path::PathBuf {
+        write_pdf_with(name, pages, PdfExtras::default())
+    }
+
+    /// Optional parts of a test PDF beyond its pages.
+    #[derive(Default)]
+    struct PdfExtras<'a> {
+        info: Option<&'a str>,
+        xmp: Option<&'a str>,
+        /// Image XObject streams, available to every page as `/Im1`, `/Im2`, ...
+        images: &'a [String],
+        /// `/Rotate` of every page.
+        rotate: i32,
+    }
+
+    /// Like `write_pdf`, with an Info dictionary, XMP packet or images.
+    fn write_pdf_with(name: &str, pages: &[&str], extras: PdfExtras) -> std::path::PathBuf {
+        let PdfExtras { info, xmp, images, rotate } = extras;
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            String::new(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        ];
+        objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string());
+        let xobjects: String = images
+            .iter()
+            .enumerate()
+            .map(|(idx, image)| {
+                objects.push(image.clone());
+                format!("/Im{} {} 0 R ", idx + 1, objects.len())
+            })
+            .collect();
+        let mut kids = Vec::new();
+        for content in pages {
+            let content_id = objects.len() + 1;
+            kids.push(format!("{} 0 R", content_id + 1));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            ));
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 792 612] /Rotate {} \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {}>> >> /Contents {} 0 R >>",
+                rotate, xobjects, content_id
+            ));
+        }
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            kids.len()
+        );
+        let mut trailer = String::new();
+        if let Some(info) = info {
+            objects.push(info.to_string());
+            trailer = format!(" /Info {} 0 R", objects.len());
+        }
+        if let Some(xmp) = xmp {
+            objects.push(format!(
+                "<< /Type /Metadata /Subtype /XML /Length {} >>\nstream\n{}\nendstream",
+                xmp.len(),
+                xmp
+            ));
+            objects[0] = format!("<< /Type /Catalog /Pages 2 0 R /Metadata {} 0 R >>", objects.len());
+        }
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, obj) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, obj).as_bytes());
+        }
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R{} >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                trailer,
+                xref
+            )
+            .as_bytes(),
+        );
+
+        let path = std::env::temp_dir().join(format!("trace-deck-{}-{}", std::process::id(), name));
+        std::fs::write(&path, out).unwrap();
+        path
+    }
 }